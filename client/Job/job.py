@@ -8,9 +8,11 @@ sys.path.append("./proto")
 
 
 class Job(object):
-    def __init__(self, ip, length: int, job_name: str, dataset_name: str, job_id):
+    def __init__(self, ip, length: int, job_name: str, dataset_name: str, job_id, rank=0):
+        self.ip = ip
         self.length = length
         self.job_id = job_id
+        self.rank = rank
         channel = grpc.insecure_channel(
             ip, options=[
                 ('grpc.enable_http_proxy', 0),
@@ -18,9 +20,14 @@ class Job(object):
         self.client = job_pb2_grpc.JobSvcStub(channel)
         self.job_name = job_name
         self.dataset_name = dataset_name
+        # the epoch of the last batch, and whether it ends the epoch
+        self.epoch = 0
+        self.end_of_epoch = False
 
     @staticmethod
-    def new(dataset_name: str, name: str, ip: str, start="", end=""):
+    def new(dataset_name: str, name: str, ip: str, start="", end="", epochs=1,
+            order=job_pb2.SHUFFLED, seed=0, replacement=False, ranks=1, drop_uneven=False,
+            checkpoint=b""):
         expr_list = []
         if start != "":
             expr_list.append(job_pb2.Expr(op=job_pb2.Expr.GEQ, rhs=start))
@@ -31,13 +38,21 @@ class Job(object):
             ip, options=(('grpc.enable_http_proxy', 0),))
         client = job_pb2_grpc.JobSvcStub(channel)
         cond = job_pb2.Condition(exprs=expr_list)
+        # epochs is None for reading until the job is deleted
         request = job_pb2.CreateJobRequest(
-            dataset_name=dataset_name, name=name, condition=cond)
+            dataset_name=dataset_name, name=name, condition=cond,
+            epochs=epochs or 0, infinite=epochs is None, order=order, seed=seed,
+            replacement=replacement, ranks=ranks, drop_uneven=drop_uneven,
+            checkpoint=checkpoint)
         resp = client.CreateJob(request)
         job_id = resp.job_id
         length = resp.length
         return Job(ip, length, name, dataset_name, job_id)
 
+    # The reader of another rank of the job, e.g. for each process of DDP training
+    def with_rank(self, rank: int):
+        return Job(self.ip, self.length, self.job_name, self.dataset_name, self.job_id, rank)
+
     def transform(self, data: job_pb2.Data):
         if data.ty == job_pb2.Data.UINT:
             return int.from_bytes(data.bs, 'big', signed=False)
@@ -49,14 +64,58 @@ class Job(object):
         else:
             assert False
 
+    def update_epoch(self, resp):
+        self.epoch = resp.epoch
+        self.end_of_epoch = resp.end_of_epoch
+        return resp.data
+
     def next(self):
-        request = job_pb2.NextRequest(job_id=self.job_id)
-        data_list = self.client.Next(request).data
+        request = job_pb2.NextRequest(job_id=self.job_id, rank=self.rank)
+        data_list = self.update_epoch(self.client.Next(request))
         res = []
         for data in data_list:
             res.append(self.transform(data))
         return res
 
+    # A collated field is split into samples by lens, images become one [N, H, W, C] array
+    def transform_batch(self, data: job_pb2.Data):
+        if data.ty == job_pb2.Data.IMAGE:
+            return np.frombuffer(data.bs, dtype=np.uint8).reshape(len(data.lens), *data.shape)
+        res = []
+        start = 0
+        for length in data.lens:
+            res.append(self.transform(job_pb2.Data(bs=data.bs[start:start + length], ty=data.ty)))
+            start += length
+        return res
+
+    def next_batch(self, batch_size: int, drop_last=False):
+        request = job_pb2.NextRequest(
+            job_id=self.job_id, rank=self.rank, batch_size=batch_size, drop_last=drop_last)
+        data_list = self.update_epoch(self.client.Next(request))
+        return [self.transform_batch(data) for data in data_list]
+
+    # Batches are pushed by server until the end of the last epoch
+    def stream(self, batch_size: int, drop_last=False):
+        request = job_pb2.NextRequest(
+            job_id=self.job_id, rank=self.rank, batch_size=batch_size, drop_last=drop_last)
+        for resp in self.client.Stream(request):
+            yield [self.transform_batch(data) for data in self.update_epoch(resp)]
+
+    # Start over from the first epoch, e.g. after an evaluation in the middle of an epoch
+    def reset(self):
+        request = job_pb2.ResetJobRequest(
+            name=self.job_name, dataset_name=self.dataset_name)
+        self.length = self.client.ResetJob(request).length
+        self.epoch = 0
+        self.end_of_epoch = False
+
+    # The samples left in the current epoch of each rank, save it with the model and pass it
+    # to Job.new after restart to go on with the epoch
+    def checkpoint(self):
+        request = job_pb2.CheckpointJobRequest(
+            name=self.job_name, dataset_name=self.dataset_name)
+        return self.client.CheckpointJob(request).checkpoint
+
     def len(self):
         return self.length
 
@@ -17,9 +17,12 @@ class Dataset(object):
     name: str
     items: list
 
-    def __init__(self, name: str, location: str, ty: DatasetType):
+    def __init__(self, name: str, location: str, ty: DatasetType,
+                 sampler=dataset_pb2.CreateDatasetRequest.INDEPENDENT):
         self.name = name
         self.location = location
+        # INDEPENDENT or SHARED, see CreateDatasetRequest.Sampler
+        self.sampler = sampler
         if ty == DatasetType.FILESYSTEM:
             self.ty = dataset_pb2.CreateDatasetRequest.FILESYSTEM
         elif ty == DatasetType.DUMMY:
@@ -40,7 +43,8 @@ class Dataset(object):
             location=self.location,
             type=self.ty,
             items=self.items,
-            weights=[])
+            weights=[],
+            sampler=self.sampler)
         return client.CreateDataset(request)
 
     def delete(self, channel):
@@ -48,5 +52,24 @@ class Dataset(object):
         request = dataset_pb2.DeleteDatasetRequest(name=self.name)
         return client.DeleteDataset(request)
 
+    def prewarm(self, channel, start: int = 0, end: int = None):
+        # load the samples of [start, end) into cache, it yields the progress
+        client = dataset_pb2_grpc.DatasetSvcStub(channel)
+        if end is None:
+            end = len(self)
+        request = dataset_pb2.PrewarmRequest(name=self.name, start=start, end=end)
+        return client.Prewarm(request)
+
+    def pin(self, channel, condition=None):
+        # pin the samples selected by condition, it yields the progress
+        client = dataset_pb2_grpc.DatasetSvcStub(channel)
+        request = dataset_pb2.PinRequest(name=self.name, condition=condition)
+        return client.Pin(request)
+
+    def unpin(self, channel, condition=None):
+        client = dataset_pb2_grpc.DatasetSvcStub(channel)
+        request = dataset_pb2.UnpinRequest(name=self.name, condition=condition)
+        return client.Unpin(request).unpinned
+
     def __len__(self):
         return len(self.items)
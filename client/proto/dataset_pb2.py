@@ -12,6 +12,7 @@ _sym_db = _symbol_database.Default()
 
 
 import common_pb2 as common__pb2
+import job_pb2 as job__pb2
 
 
 DESCRIPTOR = _descriptor.FileDescriptor(
@@ -20,9 +21,9 @@ DESCRIPTOR = _descriptor.FileDescriptor(
   syntax='proto3',
   serialized_options=None,
   create_key=_descriptor._internal_create_key,
-  serialized_pb=b'\n\rdataset.proto\x12\x07\x64\x61taset\x1a\x0c\x63ommon.proto\"\x18\n\x08\x44\x61taItem\x12\x0c\n\x04keys\x18\x01 \x03(\t\"\xc8\x01\n\x14\x43reateDatasetRequest\x12\x0c\n\x04name\x18\x01 \x01(\t\x12\x10\n\x08location\x18\x02 \x01(\t\x12\x30\n\x04type\x18\x03 \x01(\x0e\x32\".dataset.CreateDatasetRequest.Type\x12 \n\x05items\x18\x04 \x03(\x0b\x32\x11.dataset.DataItem\x12\x0f\n\x07weights\x18\x05 \x03(\r\"+\n\x04Type\x12\x0e\n\nFILESYSTEM\x10\x00\x12\x08\n\x04LMDB\x10\x01\x12\t\n\x05\x44UMMY\x10\x02\"7\n\x15\x43reateDatasetResponse\x12\x1e\n\x06status\x18\x02 \x01(\x0b\x32\x0e.common.Status\"$\n\x14\x44\x65leteDatasetRequest\x12\x0c\n\x04name\x18\x01 \x01(\t\"7\n\x15\x44\x65leteDatasetResponse\x12\x1e\n\x06status\x18\x02 \x01(\x0b\x32\x0e.common.Status2\xac\x01\n\nDatasetSvc\x12N\n\rCreateDataset\x12\x1d.dataset.CreateDatasetRequest\x1a\x1e.dataset.CreateDatasetResponse\x12N\n\rDeleteDataset\x12\x1d.dataset.DeleteDatasetRequest\x1a\x1e.dataset.DeleteDatasetResponseb\x06proto3'
+  serialized_pb=b'\n\rdataset.proto\x12\x07\x64\x61taset\x1a\x0c\x63ommon.proto\x1a\tjob.proto\"\x18\n\x08\x44\x61taItem\x12\x0c\n\x04keys\x18\x01 \x03(\t\"\xa8\x02\n\x14\x43reateDatasetRequest\x12\x0c\n\x04name\x18\x01 \x01(\t\x12\x10\n\x08location\x18\x02 \x01(\t\x12\x30\n\x04type\x18\x03 \x01(\x0e\x32\".dataset.CreateDatasetRequest.Type\x12 \n\x05items\x18\x04 \x03(\x0b\x32\x11.dataset.DataItem\x12\x0f\n\x07weights\x18\x05 \x03(\r\x12\x36\n\x07sampler\x18\x06 \x01(\x0e\x32%.dataset.CreateDatasetRequest.Sampler\"+\n\x04Type\x12\x0e\n\nFILESYSTEM\x10\x00\x12\x08\n\x04LMDB\x10\x01\x12\t\n\x05\x44UMMY\x10\x02\"&\n\x07Sampler\x12\x0f\n\x0bINDEPENDENT\x10\x00\x12\n\n\x06SHARED\x10\x01\"7\n\x15\x43reateDatasetResponse\x12\x1e\n\x06status\x18\x02 \x01(\x0b\x32\x0e.common.Status\"$\n\x14\x44\x65leteDatasetRequest\x12\x0c\n\x04name\x18\x01 \x01(\t\"7\n\x15\x44\x65leteDatasetResponse\x12\x1e\n\x06status\x18\x02 \x01(\x0b\x32\x0e.common.Status\":\n\x0ePrewarmRequest\x12\x0c\n\x04name\x18\x01 \x01(\t\x12\r\n\x05start\x18\x02 \x01(\r\x12\x0b\n\x03\x65nd\x18\x03 \x01(\r\"N\n\x0fPrewarmResponse\x12\r\n\x05total\x18\x01 \x01(\r\x12\x0e\n\x06loaded\x18\x02 \x01(\r\x12\x0e\n\x06\x63\x61\x63hed\x18\x03 \x01(\r\x12\x0c\n\x04\x66ull\x18\x04 \x01(\x08\"=\n\nPinRequest\x12\x0c\n\x04name\x18\x01 \x01(\t\x12!\n\tcondition\x18\x02 \x01(\x0b\x32\x0e.job.Condition\":\n\x0bPinResponse\x12\r\n\x05total\x18\x01 \x01(\r\x12\x0e\n\x06pinned\x18\x02 \x01(\r\x12\x0c\n\x04\x66ull\x18\x03 \x01(\x08\"?\n\x0cUnpinRequest\x12\x0c\n\x04name\x18\x01 \x01(\t\x12!\n\tcondition\x18\x02 \x01(\x0b\x32\x0e.job.Condition\"!\n\rUnpinResponse\x12\x10\n\x08unpinned\x18\x01 \x01(\r2\xd8\x02\n\nDatasetSvc\x12N\n\rCreateDataset\x12\x1d.dataset.CreateDatasetRequest\x1a\x1e.dataset.CreateDatasetResponse\x12N\n\rDeleteDataset\x12\x1d.dataset.DeleteDatasetRequest\x1a\x1e.dataset.DeleteDatasetResponse\x12>\n\x07Prewarm\x12\x17.dataset.PrewarmRequest\x1a\x18.dataset.PrewarmResponse0\x01\x12\x32\n\x03Pin\x12\x13.dataset.PinRequest\x1a\x14.dataset.PinResponse0\x01\x12\x36\n\x05Unpin\x12\x15.dataset.UnpinRequest\x1a\x16.dataset.UnpinResponseb\x06proto3'
   ,
-  dependencies=[common__pb2.DESCRIPTOR,])
+  dependencies=[common__pb2.DESCRIPTOR,job__pb2.DESCRIPTOR,])
 
 
 
@@ -51,11 +52,36 @@ _CREATEDATASETREQUEST_TYPE = _descriptor.EnumDescriptor(
   ],
   containing_type=None,
   serialized_options=None,
-  serialized_start=224,
-  serialized_end=267,
+  serialized_start=291,
+  serialized_end=334,
 )
 _sym_db.RegisterEnumDescriptor(_CREATEDATASETREQUEST_TYPE)
 
+_CREATEDATASETREQUEST_SAMPLER = _descriptor.EnumDescriptor(
+  name='Sampler',
+  full_name='dataset.CreateDatasetRequest.Sampler',
+  filename=None,
+  file=DESCRIPTOR,
+  create_key=_descriptor._internal_create_key,
+  values=[
+    _descriptor.EnumValueDescriptor(
+      name='INDEPENDENT', index=0, number=0,
+      serialized_options=None,
+      type=None,
+      create_key=_descriptor._internal_create_key),
+    _descriptor.EnumValueDescriptor(
+      name='SHARED', index=1, number=1,
+      serialized_options=None,
+      type=None,
+      create_key=_descriptor._internal_create_key),
+  ],
+  containing_type=None,
+  serialized_options=None,
+  serialized_start=336,
+  serialized_end=374,
+)
+_sym_db.RegisterEnumDescriptor(_CREATEDATASETREQUEST_SAMPLER)
+
 
 _DATAITEM = _descriptor.Descriptor(
   name='DataItem',
@@ -84,8 +110,8 @@ _DATAITEM = _descriptor.Descriptor(
   extension_ranges=[],
   oneofs=[
   ],
-  serialized_start=40,
-  serialized_end=64,
+  serialized_start=51,
+  serialized_end=75,
 )
 
 
@@ -132,12 +158,20 @@ _CREATEDATASETREQUEST = _descriptor.Descriptor(
       message_type=None, enum_type=None, containing_type=None,
       is_extension=False, extension_scope=None,
       serialized_options=None, file=DESCRIPTOR,  create_key=_descriptor._internal_create_key),
+    _descriptor.FieldDescriptor(
+      name='sampler', full_name='dataset.CreateDatasetRequest.sampler', index=5,
+      number=6, type=14, cpp_type=8, label=1,
+      has_default_value=False, default_value=0,
+      message_type=None, enum_type=None, containing_type=None,
+      is_extension=False, extension_scope=None,
+      serialized_options=None, file=DESCRIPTOR,  create_key=_descriptor._internal_create_key),
   ],
   extensions=[
   ],
   nested_types=[],
   enum_types=[
     _CREATEDATASETREQUEST_TYPE,
+    _CREATEDATASETREQUEST_SAMPLER,
   ],
   serialized_options=None,
   is_extendable=False,
@@ -145,8 +179,8 @@ _CREATEDATASETREQUEST = _descriptor.Descriptor(
   extension_ranges=[],
   oneofs=[
   ],
-  serialized_start=67,
-  serialized_end=267,
+  serialized_start=78,
+  serialized_end=374,
 )
 
 
@@ -177,8 +211,8 @@ _CREATEDATASETRESPONSE = _descriptor.Descriptor(
   extension_ranges=[],
   oneofs=[
   ],
-  serialized_start=269,
-  serialized_end=324,
+  serialized_start=376,
+  serialized_end=431,
 )
 
 
@@ -209,8 +243,8 @@ _DELETEDATASETREQUEST = _descriptor.Descriptor(
   extension_ranges=[],
   oneofs=[
   ],
-  serialized_start=326,
-  serialized_end=362,
+  serialized_start=433,
+  serialized_end=469,
 )
 
 
@@ -241,20 +275,285 @@ _DELETEDATASETRESPONSE = _descriptor.Descriptor(
   extension_ranges=[],
   oneofs=[
   ],
-  serialized_start=364,
-  serialized_end=419,
+  serialized_start=471,
+  serialized_end=526,
+)
+
+
+_PREWARMREQUEST = _descriptor.Descriptor(
+  name='PrewarmRequest',
+  full_name='dataset.PrewarmRequest',
+  filename=None,
+  file=DESCRIPTOR,
+  containing_type=None,
+  create_key=_descriptor._internal_create_key,
+  fields=[
+    _descriptor.FieldDescriptor(
+      name='name', full_name='dataset.PrewarmRequest.name', index=0,
+      number=1, type=9, cpp_type=9, label=1,
+      has_default_value=False, default_value=b"".decode('utf-8'),
+      message_type=None, enum_type=None, containing_type=None,
+      is_extension=False, extension_scope=None,
+      serialized_options=None, file=DESCRIPTOR,  create_key=_descriptor._internal_create_key),
+    _descriptor.FieldDescriptor(
+      name='start', full_name='dataset.PrewarmRequest.start', index=1,
+      number=2, type=13, cpp_type=3, label=1,
+      has_default_value=False, default_value=0,
+      message_type=None, enum_type=None, containing_type=None,
+      is_extension=False, extension_scope=None,
+      serialized_options=None, file=DESCRIPTOR,  create_key=_descriptor._internal_create_key),
+    _descriptor.FieldDescriptor(
+      name='end', full_name='dataset.PrewarmRequest.end', index=2,
+      number=3, type=13, cpp_type=3, label=1,
+      has_default_value=False, default_value=0,
+      message_type=None, enum_type=None, containing_type=None,
+      is_extension=False, extension_scope=None,
+      serialized_options=None, file=DESCRIPTOR,  create_key=_descriptor._internal_create_key),
+  ],
+  extensions=[
+  ],
+  nested_types=[],
+  enum_types=[
+  ],
+  serialized_options=None,
+  is_extendable=False,
+  syntax='proto3',
+  extension_ranges=[],
+  oneofs=[
+  ],
+  serialized_start=528,
+  serialized_end=586,
+)
+
+
+_PREWARMRESPONSE = _descriptor.Descriptor(
+  name='PrewarmResponse',
+  full_name='dataset.PrewarmResponse',
+  filename=None,
+  file=DESCRIPTOR,
+  containing_type=None,
+  create_key=_descriptor._internal_create_key,
+  fields=[
+    _descriptor.FieldDescriptor(
+      name='total', full_name='dataset.PrewarmResponse.total', index=0,
+      number=1, type=13, cpp_type=3, label=1,
+      has_default_value=False, default_value=0,
+      message_type=None, enum_type=None, containing_type=None,
+      is_extension=False, extension_scope=None,
+      serialized_options=None, file=DESCRIPTOR,  create_key=_descriptor._internal_create_key),
+    _descriptor.FieldDescriptor(
+      name='loaded', full_name='dataset.PrewarmResponse.loaded', index=1,
+      number=2, type=13, cpp_type=3, label=1,
+      has_default_value=False, default_value=0,
+      message_type=None, enum_type=None, containing_type=None,
+      is_extension=False, extension_scope=None,
+      serialized_options=None, file=DESCRIPTOR,  create_key=_descriptor._internal_create_key),
+    _descriptor.FieldDescriptor(
+      name='cached', full_name='dataset.PrewarmResponse.cached', index=2,
+      number=3, type=13, cpp_type=3, label=1,
+      has_default_value=False, default_value=0,
+      message_type=None, enum_type=None, containing_type=None,
+      is_extension=False, extension_scope=None,
+      serialized_options=None, file=DESCRIPTOR,  create_key=_descriptor._internal_create_key),
+    _descriptor.FieldDescriptor(
+      name='full', full_name='dataset.PrewarmResponse.full', index=3,
+      number=4, type=8, cpp_type=7, label=1,
+      has_default_value=False, default_value=False,
+      message_type=None, enum_type=None, containing_type=None,
+      is_extension=False, extension_scope=None,
+      serialized_options=None, file=DESCRIPTOR,  create_key=_descriptor._internal_create_key),
+  ],
+  extensions=[
+  ],
+  nested_types=[],
+  enum_types=[
+  ],
+  serialized_options=None,
+  is_extendable=False,
+  syntax='proto3',
+  extension_ranges=[],
+  oneofs=[
+  ],
+  serialized_start=588,
+  serialized_end=666,
+)
+
+
+_PINREQUEST = _descriptor.Descriptor(
+  name='PinRequest',
+  full_name='dataset.PinRequest',
+  filename=None,
+  file=DESCRIPTOR,
+  containing_type=None,
+  create_key=_descriptor._internal_create_key,
+  fields=[
+    _descriptor.FieldDescriptor(
+      name='name', full_name='dataset.PinRequest.name', index=0,
+      number=1, type=9, cpp_type=9, label=1,
+      has_default_value=False, default_value=b"".decode('utf-8'),
+      message_type=None, enum_type=None, containing_type=None,
+      is_extension=False, extension_scope=None,
+      serialized_options=None, file=DESCRIPTOR,  create_key=_descriptor._internal_create_key),
+    _descriptor.FieldDescriptor(
+      name='condition', full_name='dataset.PinRequest.condition', index=1,
+      number=2, type=11, cpp_type=10, label=1,
+      has_default_value=False, default_value=None,
+      message_type=None, enum_type=None, containing_type=None,
+      is_extension=False, extension_scope=None,
+      serialized_options=None, file=DESCRIPTOR,  create_key=_descriptor._internal_create_key),
+  ],
+  extensions=[
+  ],
+  nested_types=[],
+  enum_types=[
+  ],
+  serialized_options=None,
+  is_extendable=False,
+  syntax='proto3',
+  extension_ranges=[],
+  oneofs=[
+  ],
+  serialized_start=668,
+  serialized_end=729,
+)
+
+
+_PINRESPONSE = _descriptor.Descriptor(
+  name='PinResponse',
+  full_name='dataset.PinResponse',
+  filename=None,
+  file=DESCRIPTOR,
+  containing_type=None,
+  create_key=_descriptor._internal_create_key,
+  fields=[
+    _descriptor.FieldDescriptor(
+      name='total', full_name='dataset.PinResponse.total', index=0,
+      number=1, type=13, cpp_type=3, label=1,
+      has_default_value=False, default_value=0,
+      message_type=None, enum_type=None, containing_type=None,
+      is_extension=False, extension_scope=None,
+      serialized_options=None, file=DESCRIPTOR,  create_key=_descriptor._internal_create_key),
+    _descriptor.FieldDescriptor(
+      name='pinned', full_name='dataset.PinResponse.pinned', index=1,
+      number=2, type=13, cpp_type=3, label=1,
+      has_default_value=False, default_value=0,
+      message_type=None, enum_type=None, containing_type=None,
+      is_extension=False, extension_scope=None,
+      serialized_options=None, file=DESCRIPTOR,  create_key=_descriptor._internal_create_key),
+    _descriptor.FieldDescriptor(
+      name='full', full_name='dataset.PinResponse.full', index=2,
+      number=3, type=8, cpp_type=7, label=1,
+      has_default_value=False, default_value=False,
+      message_type=None, enum_type=None, containing_type=None,
+      is_extension=False, extension_scope=None,
+      serialized_options=None, file=DESCRIPTOR,  create_key=_descriptor._internal_create_key),
+  ],
+  extensions=[
+  ],
+  nested_types=[],
+  enum_types=[
+  ],
+  serialized_options=None,
+  is_extendable=False,
+  syntax='proto3',
+  extension_ranges=[],
+  oneofs=[
+  ],
+  serialized_start=731,
+  serialized_end=789,
+)
+
+
+_UNPINREQUEST = _descriptor.Descriptor(
+  name='UnpinRequest',
+  full_name='dataset.UnpinRequest',
+  filename=None,
+  file=DESCRIPTOR,
+  containing_type=None,
+  create_key=_descriptor._internal_create_key,
+  fields=[
+    _descriptor.FieldDescriptor(
+      name='name', full_name='dataset.UnpinRequest.name', index=0,
+      number=1, type=9, cpp_type=9, label=1,
+      has_default_value=False, default_value=b"".decode('utf-8'),
+      message_type=None, enum_type=None, containing_type=None,
+      is_extension=False, extension_scope=None,
+      serialized_options=None, file=DESCRIPTOR,  create_key=_descriptor._internal_create_key),
+    _descriptor.FieldDescriptor(
+      name='condition', full_name='dataset.UnpinRequest.condition', index=1,
+      number=2, type=11, cpp_type=10, label=1,
+      has_default_value=False, default_value=None,
+      message_type=None, enum_type=None, containing_type=None,
+      is_extension=False, extension_scope=None,
+      serialized_options=None, file=DESCRIPTOR,  create_key=_descriptor._internal_create_key),
+  ],
+  extensions=[
+  ],
+  nested_types=[],
+  enum_types=[
+  ],
+  serialized_options=None,
+  is_extendable=False,
+  syntax='proto3',
+  extension_ranges=[],
+  oneofs=[
+  ],
+  serialized_start=791,
+  serialized_end=854,
+)
+
+
+_UNPINRESPONSE = _descriptor.Descriptor(
+  name='UnpinResponse',
+  full_name='dataset.UnpinResponse',
+  filename=None,
+  file=DESCRIPTOR,
+  containing_type=None,
+  create_key=_descriptor._internal_create_key,
+  fields=[
+    _descriptor.FieldDescriptor(
+      name='unpinned', full_name='dataset.UnpinResponse.unpinned', index=0,
+      number=1, type=13, cpp_type=3, label=1,
+      has_default_value=False, default_value=0,
+      message_type=None, enum_type=None, containing_type=None,
+      is_extension=False, extension_scope=None,
+      serialized_options=None, file=DESCRIPTOR,  create_key=_descriptor._internal_create_key),
+  ],
+  extensions=[
+  ],
+  nested_types=[],
+  enum_types=[
+  ],
+  serialized_options=None,
+  is_extendable=False,
+  syntax='proto3',
+  extension_ranges=[],
+  oneofs=[
+  ],
+  serialized_start=856,
+  serialized_end=889,
 )
 
 _CREATEDATASETREQUEST.fields_by_name['type'].enum_type = _CREATEDATASETREQUEST_TYPE
 _CREATEDATASETREQUEST.fields_by_name['items'].message_type = _DATAITEM
+_CREATEDATASETREQUEST.fields_by_name['sampler'].enum_type = _CREATEDATASETREQUEST_SAMPLER
 _CREATEDATASETREQUEST_TYPE.containing_type = _CREATEDATASETREQUEST
+_CREATEDATASETREQUEST_SAMPLER.containing_type = _CREATEDATASETREQUEST
 _CREATEDATASETRESPONSE.fields_by_name['status'].message_type = common__pb2._STATUS
 _DELETEDATASETRESPONSE.fields_by_name['status'].message_type = common__pb2._STATUS
+_PINREQUEST.fields_by_name['condition'].message_type = job__pb2._CONDITION
+_UNPINREQUEST.fields_by_name['condition'].message_type = job__pb2._CONDITION
 DESCRIPTOR.message_types_by_name['DataItem'] = _DATAITEM
 DESCRIPTOR.message_types_by_name['CreateDatasetRequest'] = _CREATEDATASETREQUEST
 DESCRIPTOR.message_types_by_name['CreateDatasetResponse'] = _CREATEDATASETRESPONSE
 DESCRIPTOR.message_types_by_name['DeleteDatasetRequest'] = _DELETEDATASETREQUEST
 DESCRIPTOR.message_types_by_name['DeleteDatasetResponse'] = _DELETEDATASETRESPONSE
+DESCRIPTOR.message_types_by_name['PrewarmRequest'] = _PREWARMREQUEST
+DESCRIPTOR.message_types_by_name['PrewarmResponse'] = _PREWARMRESPONSE
+DESCRIPTOR.message_types_by_name['PinRequest'] = _PINREQUEST
+DESCRIPTOR.message_types_by_name['PinResponse'] = _PINRESPONSE
+DESCRIPTOR.message_types_by_name['UnpinRequest'] = _UNPINREQUEST
+DESCRIPTOR.message_types_by_name['UnpinResponse'] = _UNPINRESPONSE
 _sym_db.RegisterFileDescriptor(DESCRIPTOR)
 
 DataItem = _reflection.GeneratedProtocolMessageType('DataItem', (_message.Message,), {
@@ -292,6 +591,48 @@ DeleteDatasetResponse = _reflection.GeneratedProtocolMessageType('DeleteDatasetR
   })
 _sym_db.RegisterMessage(DeleteDatasetResponse)
 
+PrewarmRequest = _reflection.GeneratedProtocolMessageType('PrewarmRequest', (_message.Message,), {
+  'DESCRIPTOR' : _PREWARMREQUEST,
+  '__module__' : 'dataset_pb2'
+  # @@protoc_insertion_point(class_scope:dataset.PrewarmRequest)
+  })
+_sym_db.RegisterMessage(PrewarmRequest)
+
+PrewarmResponse = _reflection.GeneratedProtocolMessageType('PrewarmResponse', (_message.Message,), {
+  'DESCRIPTOR' : _PREWARMRESPONSE,
+  '__module__' : 'dataset_pb2'
+  # @@protoc_insertion_point(class_scope:dataset.PrewarmResponse)
+  })
+_sym_db.RegisterMessage(PrewarmResponse)
+
+PinRequest = _reflection.GeneratedProtocolMessageType('PinRequest', (_message.Message,), {
+  'DESCRIPTOR' : _PINREQUEST,
+  '__module__' : 'dataset_pb2'
+  # @@protoc_insertion_point(class_scope:dataset.PinRequest)
+  })
+_sym_db.RegisterMessage(PinRequest)
+
+PinResponse = _reflection.GeneratedProtocolMessageType('PinResponse', (_message.Message,), {
+  'DESCRIPTOR' : _PINRESPONSE,
+  '__module__' : 'dataset_pb2'
+  # @@protoc_insertion_point(class_scope:dataset.PinResponse)
+  })
+_sym_db.RegisterMessage(PinResponse)
+
+UnpinRequest = _reflection.GeneratedProtocolMessageType('UnpinRequest', (_message.Message,), {
+  'DESCRIPTOR' : _UNPINREQUEST,
+  '__module__' : 'dataset_pb2'
+  # @@protoc_insertion_point(class_scope:dataset.UnpinRequest)
+  })
+_sym_db.RegisterMessage(UnpinRequest)
+
+UnpinResponse = _reflection.GeneratedProtocolMessageType('UnpinResponse', (_message.Message,), {
+  'DESCRIPTOR' : _UNPINRESPONSE,
+  '__module__' : 'dataset_pb2'
+  # @@protoc_insertion_point(class_scope:dataset.UnpinResponse)
+  })
+_sym_db.RegisterMessage(UnpinResponse)
+
 
 
 _DATASETSVC = _descriptor.ServiceDescriptor(
@@ -301,8 +642,8 @@ _DATASETSVC = _descriptor.ServiceDescriptor(
   index=0,
   serialized_options=None,
   create_key=_descriptor._internal_create_key,
-  serialized_start=422,
-  serialized_end=594,
+  serialized_start=892,
+  serialized_end=1236,
   methods=[
   _descriptor.MethodDescriptor(
     name='CreateDataset',
@@ -324,6 +665,36 @@ _DATASETSVC = _descriptor.ServiceDescriptor(
     serialized_options=None,
     create_key=_descriptor._internal_create_key,
   ),
+  _descriptor.MethodDescriptor(
+    name='Prewarm',
+    full_name='dataset.DatasetSvc.Prewarm',
+    index=2,
+    containing_service=None,
+    input_type=_PREWARMREQUEST,
+    output_type=_PREWARMRESPONSE,
+    serialized_options=None,
+    create_key=_descriptor._internal_create_key,
+  ),
+  _descriptor.MethodDescriptor(
+    name='Pin',
+    full_name='dataset.DatasetSvc.Pin',
+    index=3,
+    containing_service=None,
+    input_type=_PINREQUEST,
+    output_type=_PINRESPONSE,
+    serialized_options=None,
+    create_key=_descriptor._internal_create_key,
+  ),
+  _descriptor.MethodDescriptor(
+    name='Unpin',
+    full_name='dataset.DatasetSvc.Unpin',
+    index=4,
+    containing_service=None,
+    input_type=_UNPINREQUEST,
+    output_type=_UNPINRESPONSE,
+    serialized_options=None,
+    create_key=_descriptor._internal_create_key,
+  ),
 ])
 _sym_db.RegisterServiceDescriptor(_DATASETSVC)
 
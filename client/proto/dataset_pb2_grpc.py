@@ -24,6 +24,21 @@ class DatasetSvcStub(object):
                 request_serializer=dataset__pb2.DeleteDatasetRequest.SerializeToString,
                 response_deserializer=dataset__pb2.DeleteDatasetResponse.FromString,
                 )
+        self.Prewarm = channel.unary_stream(
+                '/dataset.DatasetSvc/Prewarm',
+                request_serializer=dataset__pb2.PrewarmRequest.SerializeToString,
+                response_deserializer=dataset__pb2.PrewarmResponse.FromString,
+                )
+        self.Pin = channel.unary_stream(
+                '/dataset.DatasetSvc/Pin',
+                request_serializer=dataset__pb2.PinRequest.SerializeToString,
+                response_deserializer=dataset__pb2.PinResponse.FromString,
+                )
+        self.Unpin = channel.unary_unary(
+                '/dataset.DatasetSvc/Unpin',
+                request_serializer=dataset__pb2.UnpinRequest.SerializeToString,
+                response_deserializer=dataset__pb2.UnpinResponse.FromString,
+                )
 
 
 class DatasetSvcServicer(object):
@@ -41,6 +56,28 @@ class DatasetSvcServicer(object):
         context.set_details('Method not implemented!')
         raise NotImplementedError('Method not implemented!')
 
+    def Prewarm(self, request, context):
+        """Load and decode samples into cache before jobs read them. It never evicts
+        cached samples, and it yields to the reads of jobs
+        """
+        context.set_code(grpc.StatusCode.UNIMPLEMENTED)
+        context.set_details('Method not implemented!')
+        raise NotImplementedError('Method not implemented!')
+
+    def Pin(self, request, context):
+        """Keep samples in cache until they are unpinned or the dataset is deleted. The
+        pinned samples are never evicted, and they are counted in a separate budget
+        """
+        context.set_code(grpc.StatusCode.UNIMPLEMENTED)
+        context.set_details('Method not implemented!')
+        raise NotImplementedError('Method not implemented!')
+
+    def Unpin(self, request, context):
+        """Missing associated documentation comment in .proto file."""
+        context.set_code(grpc.StatusCode.UNIMPLEMENTED)
+        context.set_details('Method not implemented!')
+        raise NotImplementedError('Method not implemented!')
+
 
 def add_DatasetSvcServicer_to_server(servicer, server):
     rpc_method_handlers = {
@@ -54,6 +91,21 @@ def add_DatasetSvcServicer_to_server(servicer, server):
                     request_deserializer=dataset__pb2.DeleteDatasetRequest.FromString,
                     response_serializer=dataset__pb2.DeleteDatasetResponse.SerializeToString,
             ),
+            'Prewarm': grpc.unary_stream_rpc_method_handler(
+                    servicer.Prewarm,
+                    request_deserializer=dataset__pb2.PrewarmRequest.FromString,
+                    response_serializer=dataset__pb2.PrewarmResponse.SerializeToString,
+            ),
+            'Pin': grpc.unary_stream_rpc_method_handler(
+                    servicer.Pin,
+                    request_deserializer=dataset__pb2.PinRequest.FromString,
+                    response_serializer=dataset__pb2.PinResponse.SerializeToString,
+            ),
+            'Unpin': grpc.unary_unary_rpc_method_handler(
+                    servicer.Unpin,
+                    request_deserializer=dataset__pb2.UnpinRequest.FromString,
+                    response_serializer=dataset__pb2.UnpinResponse.SerializeToString,
+            ),
     }
     generic_handler = grpc.method_handlers_generic_handler(
             'dataset.DatasetSvc', rpc_method_handlers)
@@ -97,3 +149,54 @@ class DatasetSvc(object):
             dataset__pb2.DeleteDatasetResponse.FromString,
             options, channel_credentials,
             insecure, call_credentials, compression, wait_for_ready, timeout, metadata)
+
+    @staticmethod
+    def Prewarm(request,
+            target,
+            options=(),
+            channel_credentials=None,
+            call_credentials=None,
+            insecure=False,
+            compression=None,
+            wait_for_ready=None,
+            timeout=None,
+            metadata=None):
+        return grpc.experimental.unary_stream(request, target, '/dataset.DatasetSvc/Prewarm',
+            dataset__pb2.PrewarmRequest.SerializeToString,
+            dataset__pb2.PrewarmResponse.FromString,
+            options, channel_credentials,
+            insecure, call_credentials, compression, wait_for_ready, timeout, metadata)
+
+    @staticmethod
+    def Pin(request,
+            target,
+            options=(),
+            channel_credentials=None,
+            call_credentials=None,
+            insecure=False,
+            compression=None,
+            wait_for_ready=None,
+            timeout=None,
+            metadata=None):
+        return grpc.experimental.unary_stream(request, target, '/dataset.DatasetSvc/Pin',
+            dataset__pb2.PinRequest.SerializeToString,
+            dataset__pb2.PinResponse.FromString,
+            options, channel_credentials,
+            insecure, call_credentials, compression, wait_for_ready, timeout, metadata)
+
+    @staticmethod
+    def Unpin(request,
+            target,
+            options=(),
+            channel_credentials=None,
+            call_credentials=None,
+            insecure=False,
+            compression=None,
+            wait_for_ready=None,
+            timeout=None,
+            metadata=None):
+        return grpc.experimental.unary_unary(request, target, '/dataset.DatasetSvc/Unpin',
+            dataset__pb2.UnpinRequest.SerializeToString,
+            dataset__pb2.UnpinResponse.FromString,
+            options, channel_credentials,
+            insecure, call_credentials, compression, wait_for_ready, timeout, metadata)
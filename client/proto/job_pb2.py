@@ -2,6 +2,7 @@
 # Generated by the protocol buffer compiler.  DO NOT EDIT!
 # source: job.proto
 """Generated protocol buffer code."""
+from google.protobuf.internal import enum_type_wrapper
 from google.protobuf import descriptor as _descriptor
 from google.protobuf import message as _message
 from google.protobuf import reflection as _reflection
@@ -19,9 +20,43 @@ DESCRIPTOR = _descriptor.FileDescriptor(
   syntax='proto3',
   serialized_options=None,
   create_key=_descriptor._internal_create_key,
-  serialized_pb=b'\n\tjob.proto\x12\x03job\"^\n\x04\x44\x61ta\x12\n\n\x02\x62s\x18\x01 \x01(\x0c\x12\x1f\n\x02ty\x18\x02 \x01(\x0e\x32\x13.job.Data.data_type\")\n\tdata_type\x12\x08\n\x04UINT\x10\x00\x12\x07\n\x03INT\x10\x01\x12\t\n\x05IMAGE\x10\x02\"k\n\x04\x45xpr\x12\x1f\n\x02op\x18\x01 \x01(\x0e\x32\x13.job.Expr.operation\x12\x0b\n\x03rhs\x18\x02 \x01(\t\"5\n\toperation\x12\x06\n\x02LT\x10\x00\x12\x07\n\x03LEQ\x10\x01\x12\x06\n\x02GT\x10\x02\x12\x07\n\x03GEQ\x10\x03\x12\x06\n\x02\x45Q\x10\x04\"%\n\tCondition\x12\x18\n\x05\x65xprs\x18\x01 \x03(\x0b\x32\t.job.Expr\"Y\n\x10\x43reateJobRequest\x12\x0c\n\x04name\x18\x01 \x01(\t\x12\x14\n\x0c\x64\x61taset_name\x18\x02 \x01(\t\x12!\n\tcondition\x18\x03 \x01(\x0b\x32\x0e.job.Condition\"3\n\x11\x43reateJobResponse\x12\x0e\n\x06length\x18\x01 \x01(\x04\x12\x0e\n\x06job_id\x18\x03 \x01(\x04\"\x1d\n\x0bNextRequest\x12\x0e\n\x06job_id\x18\x01 \x01(\x04\"\'\n\x0cNextResponse\x12\x17\n\x04\x64\x61ta\x18\x01 \x03(\x0b\x32\t.job.Data\"6\n\x10\x44\x65leteJobRequest\x12\x0c\n\x04name\x18\x01 \x01(\t\x12\x14\n\x0c\x64\x61taset_name\x18\x02 \x01(\t\"\x13\n\x11\x44\x65leteJobResponse2\xad\x01\n\x06JobSvc\x12:\n\tCreateJob\x12\x15.job.CreateJobRequest\x1a\x16.job.CreateJobResponse\x12+\n\x04Next\x12\x10.job.NextRequest\x1a\x11.job.NextResponse\x12:\n\tDeleteJob\x12\x15.job.DeleteJobRequest\x1a\x16.job.DeleteJobResponseb\x06proto3'
+  serialized_pb=b'\n\tjob.proto\x12\x03job\"{\n\x04\x44\x61ta\x12\n\n\x02\x62s\x18\x01 \x01(\x0c\x12\x1f\n\x02ty\x18\x02 \x01(\x0e\x32\x13.job.Data.data_type\x12\r\n\x05shape\x18\x03 \x03(\r\x12\x0c\n\x04lens\x18\x04 \x03(\x04\")\n\tdata_type\x12\x08\n\x04UINT\x10\x00\x12\x07\n\x03INT\x10\x01\x12\t\n\x05IMAGE\x10\x02\"k\n\x04\x45xpr\x12\x1f\n\x02op\x18\x01 \x01(\x0e\x32\x13.job.Expr.operation\x12\x0b\n\x03rhs\x18\x02 \x01(\t\"5\n\toperation\x12\x06\n\x02LT\x10\x00\x12\x07\n\x03LEQ\x10\x01\x12\x06\n\x02GT\x10\x02\x12\x07\n\x03GEQ\x10\x03\x12\x06\n\x02\x45Q\x10\x04\"%\n\tCondition\x12\x18\n\x05\x65xprs\x18\x01 \x03(\x0b\x32\t.job.Expr\"\xf1\x01\n\x10\x43reateJobRequest\x12\x0c\n\x04name\x18\x01 \x01(\t\x12\x14\n\x0c\x64\x61taset_name\x18\x02 \x01(\t\x12!\n\tcondition\x18\x03 \x01(\x0b\x32\x0e.job.Condition\x12\x0e\n\x06\x65pochs\x18\x04 \x01(\r\x12\x10\n\x08infinite\x18\x05 \x01(\x08\x12\x19\n\x05order\x18\x06 \x01(\x0e\x32\n.job.Order\x12\x0c\n\x04seed\x18\x07 \x01(\x04\x12\x13\n\x0breplacement\x18\x08 \x01(\x08\x12\r\n\x05ranks\x18\t \x01(\r\x12\x13\n\x0b\x64rop_uneven\x18\n \x01(\x08\x12\x12\n\ncheckpoint\x18\x0b \x01(\x0c\"3\n\x11\x43reateJobResponse\x12\x0e\n\x06length\x18\x01 \x01(\x04\x12\x0e\n\x06job_id\x18\x03 \x01(\x04\"R\n\x0bNextRequest\x12\x0e\n\x06job_id\x18\x01 \x01(\x04\x12\x12\n\nbatch_size\x18\x02 \x01(\r\x12\x11\n\tdrop_last\x18\x03 \x01(\x08\x12\x0c\n\x04rank\x18\x04 \x01(\r\"`\n\x0cNextResponse\x12\x17\n\x04\x64\x61ta\x18\x01 \x03(\x0b\x32\t.job.Data\x12\x12\n\nbatch_size\x18\x02 \x01(\r\x12\r\n\x05\x65poch\x18\x03 \x01(\r\x12\x14\n\x0c\x65nd_of_epoch\x18\x04 \x01(\x08\"6\n\x10\x44\x65leteJobRequest\x12\x0c\n\x04name\x18\x01 \x01(\t\x12\x14\n\x0c\x64\x61taset_name\x18\x02 \x01(\t\"\x13\n\x11\x44\x65leteJobResponse\"5\n\x0fResetJobRequest\x12\x0c\n\x04name\x18\x01 \x01(\t\x12\x14\n\x0c\x64\x61taset_name\x18\x02 \x01(\t\"\"\n\x10ResetJobResponse\x12\x0e\n\x06length\x18\x01 \x01(\x04\":\n\x14\x43heckpointJobRequest\x12\x0c\n\x04name\x18\x01 \x01(\t\x12\x14\n\x0c\x64\x61taset_name\x18\x02 \x01(\t\"+\n\x15\x43heckpointJobResponse\x12\x12\n\ncheckpoint\x18\x01 \x01(\x0c\"s\n\rJobCheckpoint\x12\x13\n\x0b\x66ingerprint\x18\x01 \x01(\x04\x12&\n\x05ranks\x18\x02 \x03(\x0b\x32\x17.job.JobCheckpoint.Rank\x1a%\n\x04Rank\x12\r\n\x05\x65poch\x18\x01 \x01(\r\x12\x0e\n\x06values\x18\x02 \x03(\r*1\n\x05Order\x12\x0c\n\x08SHUFFLED\x10\x00\x12\x0e\n\nSEQUENTIAL\x10\x01\x12\n\n\x06SEEDED\x10\x02\x32\xdf\x02\n\x06JobSvc\x12:\n\tCreateJob\x12\x15.job.CreateJobRequest\x1a\x16.job.CreateJobResponse\x12+\n\x04Next\x12\x10.job.NextRequest\x1a\x11.job.NextResponse\x12/\n\x06Stream\x12\x10.job.NextRequest\x1a\x11.job.NextResponse0\x01\x12:\n\tDeleteJob\x12\x15.job.DeleteJobRequest\x1a\x16.job.DeleteJobResponse\x12\x37\n\x08ResetJob\x12\x14.job.ResetJobRequest\x1a\x15.job.ResetJobResponse\x12\x46\n\rCheckpointJob\x12\x19.job.CheckpointJobRequest\x1a\x1a.job.CheckpointJobResponseb\x06proto3'
 )
 
+_ORDER = _descriptor.EnumDescriptor(
+  name='Order',
+  full_name='job.Order',
+  filename=None,
+  file=DESCRIPTOR,
+  create_key=_descriptor._internal_create_key,
+  values=[
+    _descriptor.EnumValueDescriptor(
+      name='SHUFFLED', index=0, number=0,
+      serialized_options=None,
+      type=None,
+      create_key=_descriptor._internal_create_key),
+    _descriptor.EnumValueDescriptor(
+      name='SEQUENTIAL', index=1, number=1,
+      serialized_options=None,
+      type=None,
+      create_key=_descriptor._internal_create_key),
+    _descriptor.EnumValueDescriptor(
+      name='SEEDED', index=2, number=2,
+      serialized_options=None,
+      type=None,
+      create_key=_descriptor._internal_create_key),
+  ],
+  containing_type=None,
+  serialized_options=None,
+  serialized_start=1160,
+  serialized_end=1209,
+)
+_sym_db.RegisterEnumDescriptor(_ORDER)
+
+Order = enum_type_wrapper.EnumTypeWrapper(_ORDER)
+SHUFFLED = 0
+SEQUENTIAL = 1
+SEEDED = 2
 
 
 _DATA_DATA_TYPE = _descriptor.EnumDescriptor(
@@ -49,8 +84,8 @@ _DATA_DATA_TYPE = _descriptor.EnumDescriptor(
   ],
   containing_type=None,
   serialized_options=None,
-  serialized_start=71,
-  serialized_end=112,
+  serialized_start=100,
+  serialized_end=141,
 )
 _sym_db.RegisterEnumDescriptor(_DATA_DATA_TYPE)
 
@@ -89,8 +124,8 @@ _EXPR_OPERATION = _descriptor.EnumDescriptor(
   ],
   containing_type=None,
   serialized_options=None,
-  serialized_start=168,
-  serialized_end=221,
+  serialized_start=197,
+  serialized_end=250,
 )
 _sym_db.RegisterEnumDescriptor(_EXPR_OPERATION)
 
@@ -117,6 +152,20 @@ _DATA = _descriptor.Descriptor(
       message_type=None, enum_type=None, containing_type=None,
       is_extension=False, extension_scope=None,
       serialized_options=None, file=DESCRIPTOR,  create_key=_descriptor._internal_create_key),
+    _descriptor.FieldDescriptor(
+      name='shape', full_name='job.Data.shape', index=2,
+      number=3, type=13, cpp_type=3, label=3,
+      has_default_value=False, default_value=[],
+      message_type=None, enum_type=None, containing_type=None,
+      is_extension=False, extension_scope=None,
+      serialized_options=None, file=DESCRIPTOR,  create_key=_descriptor._internal_create_key),
+    _descriptor.FieldDescriptor(
+      name='lens', full_name='job.Data.lens', index=3,
+      number=4, type=4, cpp_type=4, label=3,
+      has_default_value=False, default_value=[],
+      message_type=None, enum_type=None, containing_type=None,
+      is_extension=False, extension_scope=None,
+      serialized_options=None, file=DESCRIPTOR,  create_key=_descriptor._internal_create_key),
   ],
   extensions=[
   ],
@@ -131,7 +180,7 @@ _DATA = _descriptor.Descriptor(
   oneofs=[
   ],
   serialized_start=18,
-  serialized_end=112,
+  serialized_end=141,
 )
 
 
@@ -170,8 +219,8 @@ _EXPR = _descriptor.Descriptor(
   extension_ranges=[],
   oneofs=[
   ],
-  serialized_start=114,
-  serialized_end=221,
+  serialized_start=143,
+  serialized_end=250,
 )
 
 
@@ -202,8 +251,8 @@ _CONDITION = _descriptor.Descriptor(
   extension_ranges=[],
   oneofs=[
   ],
-  serialized_start=223,
-  serialized_end=260,
+  serialized_start=252,
+  serialized_end=289,
 )
 
 
@@ -236,6 +285,62 @@ _CREATEJOBREQUEST = _descriptor.Descriptor(
       message_type=None, enum_type=None, containing_type=None,
       is_extension=False, extension_scope=None,
       serialized_options=None, file=DESCRIPTOR,  create_key=_descriptor._internal_create_key),
+    _descriptor.FieldDescriptor(
+      name='epochs', full_name='job.CreateJobRequest.epochs', index=3,
+      number=4, type=13, cpp_type=3, label=1,
+      has_default_value=False, default_value=0,
+      message_type=None, enum_type=None, containing_type=None,
+      is_extension=False, extension_scope=None,
+      serialized_options=None, file=DESCRIPTOR,  create_key=_descriptor._internal_create_key),
+    _descriptor.FieldDescriptor(
+      name='infinite', full_name='job.CreateJobRequest.infinite', index=4,
+      number=5, type=8, cpp_type=7, label=1,
+      has_default_value=False, default_value=False,
+      message_type=None, enum_type=None, containing_type=None,
+      is_extension=False, extension_scope=None,
+      serialized_options=None, file=DESCRIPTOR,  create_key=_descriptor._internal_create_key),
+    _descriptor.FieldDescriptor(
+      name='order', full_name='job.CreateJobRequest.order', index=5,
+      number=6, type=14, cpp_type=8, label=1,
+      has_default_value=False, default_value=0,
+      message_type=None, enum_type=None, containing_type=None,
+      is_extension=False, extension_scope=None,
+      serialized_options=None, file=DESCRIPTOR,  create_key=_descriptor._internal_create_key),
+    _descriptor.FieldDescriptor(
+      name='seed', full_name='job.CreateJobRequest.seed', index=6,
+      number=7, type=4, cpp_type=4, label=1,
+      has_default_value=False, default_value=0,
+      message_type=None, enum_type=None, containing_type=None,
+      is_extension=False, extension_scope=None,
+      serialized_options=None, file=DESCRIPTOR,  create_key=_descriptor._internal_create_key),
+    _descriptor.FieldDescriptor(
+      name='replacement', full_name='job.CreateJobRequest.replacement', index=7,
+      number=8, type=8, cpp_type=7, label=1,
+      has_default_value=False, default_value=False,
+      message_type=None, enum_type=None, containing_type=None,
+      is_extension=False, extension_scope=None,
+      serialized_options=None, file=DESCRIPTOR,  create_key=_descriptor._internal_create_key),
+    _descriptor.FieldDescriptor(
+      name='ranks', full_name='job.CreateJobRequest.ranks', index=8,
+      number=9, type=13, cpp_type=3, label=1,
+      has_default_value=False, default_value=0,
+      message_type=None, enum_type=None, containing_type=None,
+      is_extension=False, extension_scope=None,
+      serialized_options=None, file=DESCRIPTOR,  create_key=_descriptor._internal_create_key),
+    _descriptor.FieldDescriptor(
+      name='drop_uneven', full_name='job.CreateJobRequest.drop_uneven', index=9,
+      number=10, type=8, cpp_type=7, label=1,
+      has_default_value=False, default_value=False,
+      message_type=None, enum_type=None, containing_type=None,
+      is_extension=False, extension_scope=None,
+      serialized_options=None, file=DESCRIPTOR,  create_key=_descriptor._internal_create_key),
+    _descriptor.FieldDescriptor(
+      name='checkpoint', full_name='job.CreateJobRequest.checkpoint', index=10,
+      number=11, type=12, cpp_type=9, label=1,
+      has_default_value=False, default_value=b"",
+      message_type=None, enum_type=None, containing_type=None,
+      is_extension=False, extension_scope=None,
+      serialized_options=None, file=DESCRIPTOR,  create_key=_descriptor._internal_create_key),
   ],
   extensions=[
   ],
@@ -248,8 +353,8 @@ _CREATEJOBREQUEST = _descriptor.Descriptor(
   extension_ranges=[],
   oneofs=[
   ],
-  serialized_start=262,
-  serialized_end=351,
+  serialized_start=292,
+  serialized_end=533,
 )
 
 
@@ -287,8 +392,8 @@ _CREATEJOBRESPONSE = _descriptor.Descriptor(
   extension_ranges=[],
   oneofs=[
   ],
-  serialized_start=353,
-  serialized_end=404,
+  serialized_start=535,
+  serialized_end=586,
 )
 
 
@@ -307,6 +412,27 @@ _NEXTREQUEST = _descriptor.Descriptor(
       message_type=None, enum_type=None, containing_type=None,
       is_extension=False, extension_scope=None,
       serialized_options=None, file=DESCRIPTOR,  create_key=_descriptor._internal_create_key),
+    _descriptor.FieldDescriptor(
+      name='batch_size', full_name='job.NextRequest.batch_size', index=1,
+      number=2, type=13, cpp_type=3, label=1,
+      has_default_value=False, default_value=0,
+      message_type=None, enum_type=None, containing_type=None,
+      is_extension=False, extension_scope=None,
+      serialized_options=None, file=DESCRIPTOR,  create_key=_descriptor._internal_create_key),
+    _descriptor.FieldDescriptor(
+      name='drop_last', full_name='job.NextRequest.drop_last', index=2,
+      number=3, type=8, cpp_type=7, label=1,
+      has_default_value=False, default_value=False,
+      message_type=None, enum_type=None, containing_type=None,
+      is_extension=False, extension_scope=None,
+      serialized_options=None, file=DESCRIPTOR,  create_key=_descriptor._internal_create_key),
+    _descriptor.FieldDescriptor(
+      name='rank', full_name='job.NextRequest.rank', index=3,
+      number=4, type=13, cpp_type=3, label=1,
+      has_default_value=False, default_value=0,
+      message_type=None, enum_type=None, containing_type=None,
+      is_extension=False, extension_scope=None,
+      serialized_options=None, file=DESCRIPTOR,  create_key=_descriptor._internal_create_key),
   ],
   extensions=[
   ],
@@ -319,8 +445,8 @@ _NEXTREQUEST = _descriptor.Descriptor(
   extension_ranges=[],
   oneofs=[
   ],
-  serialized_start=406,
-  serialized_end=435,
+  serialized_start=588,
+  serialized_end=670,
 )
 
 
@@ -339,6 +465,27 @@ _NEXTRESPONSE = _descriptor.Descriptor(
       message_type=None, enum_type=None, containing_type=None,
       is_extension=False, extension_scope=None,
       serialized_options=None, file=DESCRIPTOR,  create_key=_descriptor._internal_create_key),
+    _descriptor.FieldDescriptor(
+      name='batch_size', full_name='job.NextResponse.batch_size', index=1,
+      number=2, type=13, cpp_type=3, label=1,
+      has_default_value=False, default_value=0,
+      message_type=None, enum_type=None, containing_type=None,
+      is_extension=False, extension_scope=None,
+      serialized_options=None, file=DESCRIPTOR,  create_key=_descriptor._internal_create_key),
+    _descriptor.FieldDescriptor(
+      name='epoch', full_name='job.NextResponse.epoch', index=2,
+      number=3, type=13, cpp_type=3, label=1,
+      has_default_value=False, default_value=0,
+      message_type=None, enum_type=None, containing_type=None,
+      is_extension=False, extension_scope=None,
+      serialized_options=None, file=DESCRIPTOR,  create_key=_descriptor._internal_create_key),
+    _descriptor.FieldDescriptor(
+      name='end_of_epoch', full_name='job.NextResponse.end_of_epoch', index=3,
+      number=4, type=8, cpp_type=7, label=1,
+      has_default_value=False, default_value=False,
+      message_type=None, enum_type=None, containing_type=None,
+      is_extension=False, extension_scope=None,
+      serialized_options=None, file=DESCRIPTOR,  create_key=_descriptor._internal_create_key),
   ],
   extensions=[
   ],
@@ -351,8 +498,8 @@ _NEXTRESPONSE = _descriptor.Descriptor(
   extension_ranges=[],
   oneofs=[
   ],
-  serialized_start=437,
-  serialized_end=476,
+  serialized_start=672,
+  serialized_end=768,
 )
 
 
@@ -390,8 +537,8 @@ _DELETEJOBREQUEST = _descriptor.Descriptor(
   extension_ranges=[],
   oneofs=[
   ],
-  serialized_start=478,
-  serialized_end=532,
+  serialized_start=770,
+  serialized_end=824,
 )
 
 
@@ -415,8 +562,227 @@ _DELETEJOBRESPONSE = _descriptor.Descriptor(
   extension_ranges=[],
   oneofs=[
   ],
-  serialized_start=534,
-  serialized_end=553,
+  serialized_start=826,
+  serialized_end=845,
+)
+
+
+_RESETJOBREQUEST = _descriptor.Descriptor(
+  name='ResetJobRequest',
+  full_name='job.ResetJobRequest',
+  filename=None,
+  file=DESCRIPTOR,
+  containing_type=None,
+  create_key=_descriptor._internal_create_key,
+  fields=[
+    _descriptor.FieldDescriptor(
+      name='name', full_name='job.ResetJobRequest.name', index=0,
+      number=1, type=9, cpp_type=9, label=1,
+      has_default_value=False, default_value=b"".decode('utf-8'),
+      message_type=None, enum_type=None, containing_type=None,
+      is_extension=False, extension_scope=None,
+      serialized_options=None, file=DESCRIPTOR,  create_key=_descriptor._internal_create_key),
+    _descriptor.FieldDescriptor(
+      name='dataset_name', full_name='job.ResetJobRequest.dataset_name', index=1,
+      number=2, type=9, cpp_type=9, label=1,
+      has_default_value=False, default_value=b"".decode('utf-8'),
+      message_type=None, enum_type=None, containing_type=None,
+      is_extension=False, extension_scope=None,
+      serialized_options=None, file=DESCRIPTOR,  create_key=_descriptor._internal_create_key),
+  ],
+  extensions=[
+  ],
+  nested_types=[],
+  enum_types=[
+  ],
+  serialized_options=None,
+  is_extendable=False,
+  syntax='proto3',
+  extension_ranges=[],
+  oneofs=[
+  ],
+  serialized_start=847,
+  serialized_end=900,
+)
+
+
+_RESETJOBRESPONSE = _descriptor.Descriptor(
+  name='ResetJobResponse',
+  full_name='job.ResetJobResponse',
+  filename=None,
+  file=DESCRIPTOR,
+  containing_type=None,
+  create_key=_descriptor._internal_create_key,
+  fields=[
+    _descriptor.FieldDescriptor(
+      name='length', full_name='job.ResetJobResponse.length', index=0,
+      number=1, type=4, cpp_type=4, label=1,
+      has_default_value=False, default_value=0,
+      message_type=None, enum_type=None, containing_type=None,
+      is_extension=False, extension_scope=None,
+      serialized_options=None, file=DESCRIPTOR,  create_key=_descriptor._internal_create_key),
+  ],
+  extensions=[
+  ],
+  nested_types=[],
+  enum_types=[
+  ],
+  serialized_options=None,
+  is_extendable=False,
+  syntax='proto3',
+  extension_ranges=[],
+  oneofs=[
+  ],
+  serialized_start=902,
+  serialized_end=936,
+)
+
+
+_CHECKPOINTJOBREQUEST = _descriptor.Descriptor(
+  name='CheckpointJobRequest',
+  full_name='job.CheckpointJobRequest',
+  filename=None,
+  file=DESCRIPTOR,
+  containing_type=None,
+  create_key=_descriptor._internal_create_key,
+  fields=[
+    _descriptor.FieldDescriptor(
+      name='name', full_name='job.CheckpointJobRequest.name', index=0,
+      number=1, type=9, cpp_type=9, label=1,
+      has_default_value=False, default_value=b"".decode('utf-8'),
+      message_type=None, enum_type=None, containing_type=None,
+      is_extension=False, extension_scope=None,
+      serialized_options=None, file=DESCRIPTOR,  create_key=_descriptor._internal_create_key),
+    _descriptor.FieldDescriptor(
+      name='dataset_name', full_name='job.CheckpointJobRequest.dataset_name', index=1,
+      number=2, type=9, cpp_type=9, label=1,
+      has_default_value=False, default_value=b"".decode('utf-8'),
+      message_type=None, enum_type=None, containing_type=None,
+      is_extension=False, extension_scope=None,
+      serialized_options=None, file=DESCRIPTOR,  create_key=_descriptor._internal_create_key),
+  ],
+  extensions=[
+  ],
+  nested_types=[],
+  enum_types=[
+  ],
+  serialized_options=None,
+  is_extendable=False,
+  syntax='proto3',
+  extension_ranges=[],
+  oneofs=[
+  ],
+  serialized_start=938,
+  serialized_end=996,
+)
+
+
+_CHECKPOINTJOBRESPONSE = _descriptor.Descriptor(
+  name='CheckpointJobResponse',
+  full_name='job.CheckpointJobResponse',
+  filename=None,
+  file=DESCRIPTOR,
+  containing_type=None,
+  create_key=_descriptor._internal_create_key,
+  fields=[
+    _descriptor.FieldDescriptor(
+      name='checkpoint', full_name='job.CheckpointJobResponse.checkpoint', index=0,
+      number=1, type=12, cpp_type=9, label=1,
+      has_default_value=False, default_value=b"",
+      message_type=None, enum_type=None, containing_type=None,
+      is_extension=False, extension_scope=None,
+      serialized_options=None, file=DESCRIPTOR,  create_key=_descriptor._internal_create_key),
+  ],
+  extensions=[
+  ],
+  nested_types=[],
+  enum_types=[
+  ],
+  serialized_options=None,
+  is_extendable=False,
+  syntax='proto3',
+  extension_ranges=[],
+  oneofs=[
+  ],
+  serialized_start=998,
+  serialized_end=1041,
+)
+
+
+_JOBCHECKPOINT_RANK = _descriptor.Descriptor(
+  name='Rank',
+  full_name='job.JobCheckpoint.Rank',
+  filename=None,
+  file=DESCRIPTOR,
+  containing_type=None,
+  create_key=_descriptor._internal_create_key,
+  fields=[
+    _descriptor.FieldDescriptor(
+      name='epoch', full_name='job.JobCheckpoint.Rank.epoch', index=0,
+      number=1, type=13, cpp_type=3, label=1,
+      has_default_value=False, default_value=0,
+      message_type=None, enum_type=None, containing_type=None,
+      is_extension=False, extension_scope=None,
+      serialized_options=None, file=DESCRIPTOR,  create_key=_descriptor._internal_create_key),
+    _descriptor.FieldDescriptor(
+      name='values', full_name='job.JobCheckpoint.Rank.values', index=1,
+      number=2, type=13, cpp_type=3, label=3,
+      has_default_value=False, default_value=[],
+      message_type=None, enum_type=None, containing_type=None,
+      is_extension=False, extension_scope=None,
+      serialized_options=None, file=DESCRIPTOR,  create_key=_descriptor._internal_create_key),
+  ],
+  extensions=[
+  ],
+  nested_types=[],
+  enum_types=[
+  ],
+  serialized_options=None,
+  is_extendable=False,
+  syntax='proto3',
+  extension_ranges=[],
+  oneofs=[
+  ],
+  serialized_start=1121,
+  serialized_end=1158,
+)
+
+_JOBCHECKPOINT = _descriptor.Descriptor(
+  name='JobCheckpoint',
+  full_name='job.JobCheckpoint',
+  filename=None,
+  file=DESCRIPTOR,
+  containing_type=None,
+  create_key=_descriptor._internal_create_key,
+  fields=[
+    _descriptor.FieldDescriptor(
+      name='fingerprint', full_name='job.JobCheckpoint.fingerprint', index=0,
+      number=1, type=4, cpp_type=4, label=1,
+      has_default_value=False, default_value=0,
+      message_type=None, enum_type=None, containing_type=None,
+      is_extension=False, extension_scope=None,
+      serialized_options=None, file=DESCRIPTOR,  create_key=_descriptor._internal_create_key),
+    _descriptor.FieldDescriptor(
+      name='ranks', full_name='job.JobCheckpoint.ranks', index=1,
+      number=2, type=11, cpp_type=10, label=3,
+      has_default_value=False, default_value=[],
+      message_type=None, enum_type=None, containing_type=None,
+      is_extension=False, extension_scope=None,
+      serialized_options=None, file=DESCRIPTOR,  create_key=_descriptor._internal_create_key),
+  ],
+  extensions=[
+  ],
+  nested_types=[_JOBCHECKPOINT_RANK, ],
+  enum_types=[
+  ],
+  serialized_options=None,
+  is_extendable=False,
+  syntax='proto3',
+  extension_ranges=[],
+  oneofs=[
+  ],
+  serialized_start=1043,
+  serialized_end=1158,
 )
 
 _DATA.fields_by_name['ty'].enum_type = _DATA_DATA_TYPE
@@ -425,7 +791,10 @@ _EXPR.fields_by_name['op'].enum_type = _EXPR_OPERATION
 _EXPR_OPERATION.containing_type = _EXPR
 _CONDITION.fields_by_name['exprs'].message_type = _EXPR
 _CREATEJOBREQUEST.fields_by_name['condition'].message_type = _CONDITION
+_CREATEJOBREQUEST.fields_by_name['order'].enum_type = _ORDER
 _NEXTRESPONSE.fields_by_name['data'].message_type = _DATA
+_JOBCHECKPOINT_RANK.containing_type = _JOBCHECKPOINT
+_JOBCHECKPOINT.fields_by_name['ranks'].message_type = _JOBCHECKPOINT_RANK
 DESCRIPTOR.message_types_by_name['Data'] = _DATA
 DESCRIPTOR.message_types_by_name['Expr'] = _EXPR
 DESCRIPTOR.message_types_by_name['Condition'] = _CONDITION
@@ -435,6 +804,12 @@ DESCRIPTOR.message_types_by_name['NextRequest'] = _NEXTREQUEST
 DESCRIPTOR.message_types_by_name['NextResponse'] = _NEXTRESPONSE
 DESCRIPTOR.message_types_by_name['DeleteJobRequest'] = _DELETEJOBREQUEST
 DESCRIPTOR.message_types_by_name['DeleteJobResponse'] = _DELETEJOBRESPONSE
+DESCRIPTOR.message_types_by_name['ResetJobRequest'] = _RESETJOBREQUEST
+DESCRIPTOR.message_types_by_name['ResetJobResponse'] = _RESETJOBRESPONSE
+DESCRIPTOR.message_types_by_name['CheckpointJobRequest'] = _CHECKPOINTJOBREQUEST
+DESCRIPTOR.message_types_by_name['CheckpointJobResponse'] = _CHECKPOINTJOBRESPONSE
+DESCRIPTOR.message_types_by_name['JobCheckpoint'] = _JOBCHECKPOINT
+DESCRIPTOR.enum_types_by_name['Order'] = _ORDER
 _sym_db.RegisterFileDescriptor(DESCRIPTOR)
 
 Data = _reflection.GeneratedProtocolMessageType('Data', (_message.Message,), {
@@ -500,6 +875,49 @@ DeleteJobResponse = _reflection.GeneratedProtocolMessageType('DeleteJobResponse'
   })
 _sym_db.RegisterMessage(DeleteJobResponse)
 
+ResetJobRequest = _reflection.GeneratedProtocolMessageType('ResetJobRequest', (_message.Message,), {
+  'DESCRIPTOR' : _RESETJOBREQUEST,
+  '__module__' : 'job_pb2'
+  # @@protoc_insertion_point(class_scope:job.ResetJobRequest)
+  })
+_sym_db.RegisterMessage(ResetJobRequest)
+
+ResetJobResponse = _reflection.GeneratedProtocolMessageType('ResetJobResponse', (_message.Message,), {
+  'DESCRIPTOR' : _RESETJOBRESPONSE,
+  '__module__' : 'job_pb2'
+  # @@protoc_insertion_point(class_scope:job.ResetJobResponse)
+  })
+_sym_db.RegisterMessage(ResetJobResponse)
+
+CheckpointJobRequest = _reflection.GeneratedProtocolMessageType('CheckpointJobRequest', (_message.Message,), {
+  'DESCRIPTOR' : _CHECKPOINTJOBREQUEST,
+  '__module__' : 'job_pb2'
+  # @@protoc_insertion_point(class_scope:job.CheckpointJobRequest)
+  })
+_sym_db.RegisterMessage(CheckpointJobRequest)
+
+CheckpointJobResponse = _reflection.GeneratedProtocolMessageType('CheckpointJobResponse', (_message.Message,), {
+  'DESCRIPTOR' : _CHECKPOINTJOBRESPONSE,
+  '__module__' : 'job_pb2'
+  # @@protoc_insertion_point(class_scope:job.CheckpointJobResponse)
+  })
+_sym_db.RegisterMessage(CheckpointJobResponse)
+
+JobCheckpoint = _reflection.GeneratedProtocolMessageType('JobCheckpoint', (_message.Message,), {
+
+  'Rank' : _reflection.GeneratedProtocolMessageType('Rank', (_message.Message,), {
+    'DESCRIPTOR' : _JOBCHECKPOINT_RANK,
+    '__module__' : 'job_pb2'
+    # @@protoc_insertion_point(class_scope:job.JobCheckpoint.Rank)
+    })
+  ,
+  'DESCRIPTOR' : _JOBCHECKPOINT,
+  '__module__' : 'job_pb2'
+  # @@protoc_insertion_point(class_scope:job.JobCheckpoint)
+  })
+_sym_db.RegisterMessage(JobCheckpoint)
+_sym_db.RegisterMessage(JobCheckpoint.Rank)
+
 
 
 _JOBSVC = _descriptor.ServiceDescriptor(
@@ -509,8 +927,8 @@ _JOBSVC = _descriptor.ServiceDescriptor(
   index=0,
   serialized_options=None,
   create_key=_descriptor._internal_create_key,
-  serialized_start=556,
-  serialized_end=729,
+  serialized_start=1212,
+  serialized_end=1563,
   methods=[
   _descriptor.MethodDescriptor(
     name='CreateJob',
@@ -532,16 +950,46 @@ _JOBSVC = _descriptor.ServiceDescriptor(
     serialized_options=None,
     create_key=_descriptor._internal_create_key,
   ),
+  _descriptor.MethodDescriptor(
+    name='Stream',
+    full_name='job.JobSvc.Stream',
+    index=2,
+    containing_service=None,
+    input_type=_NEXTREQUEST,
+    output_type=_NEXTRESPONSE,
+    serialized_options=None,
+    create_key=_descriptor._internal_create_key,
+  ),
   _descriptor.MethodDescriptor(
     name='DeleteJob',
     full_name='job.JobSvc.DeleteJob',
-    index=2,
+    index=3,
     containing_service=None,
     input_type=_DELETEJOBREQUEST,
     output_type=_DELETEJOBRESPONSE,
     serialized_options=None,
     create_key=_descriptor._internal_create_key,
   ),
+  _descriptor.MethodDescriptor(
+    name='ResetJob',
+    full_name='job.JobSvc.ResetJob',
+    index=4,
+    containing_service=None,
+    input_type=_RESETJOBREQUEST,
+    output_type=_RESETJOBRESPONSE,
+    serialized_options=None,
+    create_key=_descriptor._internal_create_key,
+  ),
+  _descriptor.MethodDescriptor(
+    name='CheckpointJob',
+    full_name='job.JobSvc.CheckpointJob',
+    index=5,
+    containing_service=None,
+    input_type=_CHECKPOINTJOBREQUEST,
+    output_type=_CHECKPOINTJOBRESPONSE,
+    serialized_options=None,
+    create_key=_descriptor._internal_create_key,
+  ),
 ])
 _sym_db.RegisterServiceDescriptor(_JOBSVC)
 
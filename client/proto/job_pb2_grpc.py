@@ -24,11 +24,26 @@ class JobSvcStub(object):
                 request_serializer=job__pb2.NextRequest.SerializeToString,
                 response_deserializer=job__pb2.NextResponse.FromString,
                 )
+        self.Stream = channel.unary_stream(
+                '/job.JobSvc/Stream',
+                request_serializer=job__pb2.NextRequest.SerializeToString,
+                response_deserializer=job__pb2.NextResponse.FromString,
+                )
         self.DeleteJob = channel.unary_unary(
                 '/job.JobSvc/DeleteJob',
                 request_serializer=job__pb2.DeleteJobRequest.SerializeToString,
                 response_deserializer=job__pb2.DeleteJobResponse.FromString,
                 )
+        self.ResetJob = channel.unary_unary(
+                '/job.JobSvc/ResetJob',
+                request_serializer=job__pb2.ResetJobRequest.SerializeToString,
+                response_deserializer=job__pb2.ResetJobResponse.FromString,
+                )
+        self.CheckpointJob = channel.unary_unary(
+                '/job.JobSvc/CheckpointJob',
+                request_serializer=job__pb2.CheckpointJobRequest.SerializeToString,
+                response_deserializer=job__pb2.CheckpointJobResponse.FromString,
+                )
 
 
 class JobSvcServicer(object):
@@ -46,12 +61,36 @@ class JobSvcServicer(object):
         context.set_details('Method not implemented!')
         raise NotImplementedError('Method not implemented!')
 
+    def Stream(self, request, context):
+        """Push batches to the client until the end of the job. A job is streamed by
+        at most one client, and Next fails while the job is streamed
+        """
+        context.set_code(grpc.StatusCode.UNIMPLEMENTED)
+        context.set_details('Method not implemented!')
+        raise NotImplementedError('Method not implemented!')
+
     def DeleteJob(self, request, context):
         """Missing associated documentation comment in .proto file."""
         context.set_code(grpc.StatusCode.UNIMPLEMENTED)
         context.set_details('Method not implemented!')
         raise NotImplementedError('Method not implemented!')
 
+    def ResetJob(self, request, context):
+        """Restart the job from its first epoch, the samples not read are dropped. It fails
+        while the job is streamed. The other jobs of the dataset are not affected
+        """
+        context.set_code(grpc.StatusCode.UNIMPLEMENTED)
+        context.set_details('Method not implemented!')
+        raise NotImplementedError('Method not implemented!')
+
+    def CheckpointJob(self, request, context):
+        """Export the samples left in the current epoch of each rank, which were not returned by
+Next. It fails while a rank is streamed
+        """
+        context.set_code(grpc.StatusCode.UNIMPLEMENTED)
+        context.set_details('Method not implemented!')
+        raise NotImplementedError('Method not implemented!')
+
 
 def add_JobSvcServicer_to_server(servicer, server):
     rpc_method_handlers = {
@@ -65,11 +104,26 @@ def add_JobSvcServicer_to_server(servicer, server):
                     request_deserializer=job__pb2.NextRequest.FromString,
                     response_serializer=job__pb2.NextResponse.SerializeToString,
             ),
+            'Stream': grpc.unary_stream_rpc_method_handler(
+                    servicer.Stream,
+                    request_deserializer=job__pb2.NextRequest.FromString,
+                    response_serializer=job__pb2.NextResponse.SerializeToString,
+            ),
             'DeleteJob': grpc.unary_unary_rpc_method_handler(
                     servicer.DeleteJob,
                     request_deserializer=job__pb2.DeleteJobRequest.FromString,
                     response_serializer=job__pb2.DeleteJobResponse.SerializeToString,
             ),
+            'ResetJob': grpc.unary_unary_rpc_method_handler(
+                    servicer.ResetJob,
+                    request_deserializer=job__pb2.ResetJobRequest.FromString,
+                    response_serializer=job__pb2.ResetJobResponse.SerializeToString,
+            ),
+            'CheckpointJob': grpc.unary_unary_rpc_method_handler(
+                    servicer.CheckpointJob,
+                    request_deserializer=job__pb2.CheckpointJobRequest.FromString,
+                    response_serializer=job__pb2.CheckpointJobResponse.SerializeToString,
+            ),
     }
     generic_handler = grpc.method_handlers_generic_handler(
             'job.JobSvc', rpc_method_handlers)
@@ -114,6 +168,23 @@ class JobSvc(object):
             options, channel_credentials,
             insecure, call_credentials, compression, wait_for_ready, timeout, metadata)
 
+    @staticmethod
+    def Stream(request,
+            target,
+            options=(),
+            channel_credentials=None,
+            call_credentials=None,
+            insecure=False,
+            compression=None,
+            wait_for_ready=None,
+            timeout=None,
+            metadata=None):
+        return grpc.experimental.unary_stream(request, target, '/job.JobSvc/Stream',
+            job__pb2.NextRequest.SerializeToString,
+            job__pb2.NextResponse.FromString,
+            options, channel_credentials,
+            insecure, call_credentials, compression, wait_for_ready, timeout, metadata)
+
     @staticmethod
     def DeleteJob(request,
             target,
@@ -130,3 +201,37 @@ class JobSvc(object):
             job__pb2.DeleteJobResponse.FromString,
             options, channel_credentials,
             insecure, call_credentials, compression, wait_for_ready, timeout, metadata)
+
+    @staticmethod
+    def ResetJob(request,
+            target,
+            options=(),
+            channel_credentials=None,
+            call_credentials=None,
+            insecure=False,
+            compression=None,
+            wait_for_ready=None,
+            timeout=None,
+            metadata=None):
+        return grpc.experimental.unary_unary(request, target, '/job.JobSvc/ResetJob',
+            job__pb2.ResetJobRequest.SerializeToString,
+            job__pb2.ResetJobResponse.FromString,
+            options, channel_credentials,
+            insecure, call_credentials, compression, wait_for_ready, timeout, metadata)
+
+    @staticmethod
+    def CheckpointJob(request,
+            target,
+            options=(),
+            channel_credentials=None,
+            call_credentials=None,
+            insecure=False,
+            compression=None,
+            wait_for_ready=None,
+            timeout=None,
+            metadata=None):
+        return grpc.experimental.unary_unary(request, target, '/job.JobSvc/CheckpointJob',
+            job__pb2.CheckpointJobRequest.SerializeToString,
+            job__pb2.CheckpointJobResponse.FromString,
+            options, channel_credentials,
+            insecure, call_credentials, compression, wait_for_ready, timeout, metadata)
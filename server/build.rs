@@ -14,9 +14,12 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
       .map(|f| format!("{}/{}.proto", proto_dir, f))
       .collect();
   
+    // the payload is shared by jobs, so it's reference-counted instead of copied
+    let mut config = prost_build::Config::new();
+    config.bytes(&[".job.Data.bs"]);
     tonic_build::configure()
       .out_dir("./src/proto")
-      .compile(&protos, &[proto_dir.to_string()])
+      .compile_with_config(config, &protos, &[proto_dir.to_string()])
       .expect("Failed to compile grpc!");
     Ok(())
   }
\ No newline at end of file
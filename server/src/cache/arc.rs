@@ -0,0 +1,121 @@
+use std::collections::HashSet;
+
+use super::key::CacheKey;
+use super::lru::LruList;
+use super::policy::Policy;
+
+// Adaptive Replacement Cache (Megiddo and Modha, FAST'03).
+// t1 holds the keys read once and t2 the keys read at least twice, b1 and b2
+// remember the keys recently evicted from them. A hit in b1 (b2) means t1 (t2)
+// was too small, so the target size p of t1 is adapted accordingly.
+// The cache is sized in bytes, so the sizes here are numbers of keys.
+#[derive(Debug, Default)]
+pub struct AdaptiveReplacement {
+    t1: LruList,
+    t2: LruList,
+    b1: LruList,
+    b2: LruList,
+    p: usize,
+    // the largest number of resident keys, it's taken as the capacity c
+    capacity: usize,
+}
+
+impl AdaptiveReplacement {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn resident(&self) -> usize {
+        self.t1.len() + self.t2.len()
+    }
+
+    // The ghost lists hold at most c keys
+    fn trim_ghost(&mut self) {
+        while self.b1.len() + self.b2.len() > self.capacity {
+            if self.b1.len() > self.b2.len() {
+                self.b1.pop_front();
+            } else {
+                self.b2.pop_front();
+            }
+        }
+    }
+}
+
+impl Policy for AdaptiveReplacement {
+    fn set(&mut self, key: CacheKey, _ref_cnt: usize) {
+        if self.t1.remove(key) || self.t2.contains(key) {
+            self.t2.touch(key);
+        } else if self.b1.remove(key) {
+            let delta = (self.b2.len() / self.b1.len().max(1)).max(1);
+            self.p = (self.p + delta).min(self.resident() + 1);
+            self.t2.touch(key);
+        } else if self.b2.remove(key) {
+            let delta = (self.b1.len() / self.b2.len().max(1)).max(1);
+            self.p = self.p.saturating_sub(delta);
+            self.t2.touch(key);
+        } else {
+            self.t1.touch(key);
+        }
+        self.capacity = self.capacity.max(self.resident());
+        self.trim_ghost();
+    }
+
+    fn get(&mut self, key: CacheKey) {
+        if self.t1.remove(key) || self.t2.contains(key) {
+            self.t2.touch(key);
+        }
+    }
+
+    fn remove(&mut self, key: CacheKey) {
+        self.t1.remove(key);
+        self.t2.remove(key);
+    }
+
+    fn evict(&mut self) -> Option<HashSet<CacheKey>> {
+        let from_t1 = !self.t1.is_empty() && (self.t1.len() > self.p || self.t2.is_empty());
+        let key = if from_t1 {
+            let key = self.t1.pop_front()?;
+            self.b1.touch(key);
+            key
+        } else {
+            let key = self.t2.pop_front()?;
+            self.b2.touch(key);
+            key
+        };
+        self.trim_ghost();
+        Some(HashSet::from([key]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(name: char) -> CacheKey {
+        CacheKey::new(name as u64, 0)
+    }
+
+    fn evict(policy: &mut AdaptiveReplacement) -> CacheKey {
+        policy.evict().unwrap().into_iter().next().unwrap()
+    }
+
+    #[test]
+    fn test_arc() {
+        let mut policy = AdaptiveReplacement::new();
+        for name in ['a', 'b', 'c'] {
+            policy.set(key(name), 0);
+        }
+        // "a" is read twice, so it's moved to t2
+        policy.get(key('a'));
+        assert_eq!(evict(&mut policy), key('b'));
+        assert_eq!(evict(&mut policy), key('c'));
+        // a ghost hit in b1 enlarges t1
+        policy.set(key('b'), 0);
+        assert_eq!(policy.p, 1);
+        policy.set(key('d'), 0);
+        assert_eq!(evict(&mut policy), key('a'));
+        assert_eq!(evict(&mut policy), key('b'));
+        assert_eq!(evict(&mut policy), key('d'));
+        assert_eq!(policy.evict(), None);
+    }
+}
@@ -0,0 +1,111 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use super::key::CacheKey;
+use super::policy::Policy;
+
+// Belady's MIN with the lookahead of sampler: evict the key whose next use is the
+// furthest in the future, and the keys out of the lookahead window go first
+#[derive(Debug, Default)]
+pub struct Belady {
+    // key -> the distance of its next use, only the keys in the window are here
+    next_use: HashMap<CacheKey, usize>,
+    // resident key -> its distance in order
+    resident: HashMap<CacheKey, usize>,
+    order: BTreeSet<(usize, CacheKey)>,
+}
+
+impl Belady {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn distance(&self, key: CacheKey) -> usize {
+        *self.next_use.get(&key).unwrap_or(&usize::MAX)
+    }
+
+    fn reorder(&mut self, key: CacheKey) {
+        let distance = self.distance(key);
+        if let Some(old) = self.resident.get_mut(&key) {
+            self.order.remove(&(*old, key));
+            self.order.insert((distance, key));
+            *old = distance;
+        }
+    }
+}
+
+impl Policy for Belady {
+    fn set(&mut self, key: CacheKey, _ref_cnt: usize) {
+        self.remove(key);
+        let distance = self.distance(key);
+        self.resident.insert(key, distance);
+        self.order.insert((distance, key));
+    }
+
+    fn remove(&mut self, key: CacheKey) {
+        if let Some(distance) = self.resident.remove(&key) {
+            self.order.remove(&(distance, key));
+        }
+    }
+
+    // No job will read the key soon
+    fn admit(&mut self, key: CacheKey) -> bool {
+        self.next_use.contains_key(&key)
+    }
+
+    fn lookahead(&mut self, next_use: HashMap<CacheKey, usize>) {
+        let old = std::mem::replace(&mut self.next_use, next_use);
+        // only the keys whose distance is changed are reordered
+        let mut changed = old
+            .keys()
+            .filter(|key| !self.next_use.contains_key(*key))
+            .copied()
+            .collect::<Vec<_>>();
+        for (key, distance) in self.next_use.iter() {
+            if old.get(key) != Some(distance) {
+                changed.push(*key);
+            }
+        }
+        for key in changed {
+            self.reorder(key);
+        }
+    }
+
+    fn evict(&mut self) -> Option<HashSet<CacheKey>> {
+        let last = *self.order.iter().next_back()?;
+        self.order.remove(&last);
+        self.resident.remove(&last.1);
+        Some(HashSet::from([last.1]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(name: char) -> CacheKey {
+        CacheKey::new(name as u64, 0)
+    }
+
+    fn next_use(pairs: &[(char, usize)]) -> HashMap<CacheKey, usize> {
+        pairs.iter().map(|(k, d)| (key(*k), *d)).collect()
+    }
+
+    #[test]
+    fn test_belady() {
+        let mut policy = Belady::new();
+        policy.lookahead(next_use(&[('a', 1), ('b', 5), ('c', 3)]));
+        assert!(policy.admit(key('a')));
+        assert!(!policy.admit(key('d')));
+        for name in ['a', 'b', 'c', 'd'] {
+            policy.set(key(name), 0);
+        }
+        // "d" will not be read
+        assert_eq!(policy.evict(), Some(HashSet::from([key('d')])));
+        assert_eq!(policy.evict(), Some(HashSet::from([key('b')])));
+        // "a" is read, and it's read again later than "c"
+        policy.lookahead(next_use(&[('a', 4), ('c', 2)]));
+        assert_eq!(policy.evict(), Some(HashSet::from([key('a')])));
+        assert_eq!(policy.evict(), Some(HashSet::from([key('c')])));
+        assert_eq!(policy.evict(), None);
+    }
+}
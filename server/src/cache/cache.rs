@@ -2,52 +2,200 @@
 // 2. Remove value according reference
 
 use cached::{Cached, UnboundCache};
-use std::{collections::HashMap, sync::Arc};
+use std::mem::size_of;
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
 
 use crate::proto::job::Data;
 
-use super::policy::{Policy, RefCnt};
+use super::disk::DiskCache;
+use super::key::CacheKey;
+use super::policy::{build_policy, Policy, PolicyType};
+
+// The bookkeeping of an entry besides its data: the key is held by the cache, refs and
+// policy, and about 64 bytes are taken by the nodes of hash maps and trees
+const ENTRY_OVERHEAD: usize = 3 * size_of::<CacheKey>()
+    + size_of::<Arc<Vec<Data>>>()
+    + size_of::<Vec<Data>>()
+    + 2 * size_of::<usize>()
+    + 64;
+
+// The memory taken by an entry
+pub fn entry_size(value: &[Data]) -> usize {
+    let data_size = value
+        .iter()
+        .map(|data| {
+            size_of::<Data>()
+                + data.bs.len()
+                + data.shape.len() * size_of::<u32>()
+                + data.lens.len() * size_of::<u64>()
+        })
+        .sum::<usize>();
+    ENTRY_OVERHEAD + data_size
+}
+
+// The budget of cache in bytes (e.g. "1073741824") or as a percentage of the host memory
+// (e.g. "50%")
+pub fn parse_capacity(budget: &str) -> Result<usize, String> {
+    let budget = budget.trim();
+    match budget.strip_suffix('%') {
+        Some(percent) => {
+            let percent: f64 = percent
+                .trim()
+                .parse()
+                .map_err(|e| format!("invalid cache budget {:?}: {}", budget, e))?;
+            if !(0.0..=100.0).contains(&percent) {
+                return Err(format!("cache budget {:?} is not in [0%, 100%]", budget));
+            }
+            Ok((host_memory() as f64 * percent / 100.0) as usize)
+        }
+        None => budget
+            .parse()
+            .map_err(|e| format!("invalid cache budget {:?}: {}", budget, e)),
+    }
+}
+
+// The physical memory of host in bytes
+fn host_memory() -> usize {
+    let pages = unsafe { libc::sysconf(libc::_SC_PHYS_PAGES) };
+    let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) };
+    (pages.max(0) as usize).saturating_mul(page_size.max(0) as usize)
+}
+
+// The numbers reported in log
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct CacheStats {
+    pub size: usize,
+    pub capacity: usize,
+    pub items: usize,
+    pub hits: usize,
+    pub misses: usize,
+    pub evictions: usize,
+    // the items larger than the capacity
+    pub rejections: usize,
+    pub pinned_size: usize,
+    pub pin_capacity: usize,
+    pub pinned_items: usize,
+}
 
 #[derive(Debug)]
 pub struct Cache {
-    refs: HashMap<String, usize>,
-    cache: UnboundCache<String, Arc<Vec<Data>>>,
+    refs: HashMap<CacheKey, usize>,
+    cache: UnboundCache<CacheKey, Arc<Vec<Data>>>,
     capacity: usize,
     size: usize,
-    policy: RefCnt,
+    policy: Box<dyn Policy>,
+    // drop a value as soon as no job will read it
+    release_on_zero: bool,
+    hits: usize,
+    misses: usize,
+    evictions: usize,
+    rejections: usize,
+    // The pinned keys are out of policy so they are never evicted or released,
+    // and they are counted in a separate budget
+    pinned: HashSet<CacheKey>,
+    pin_capacity: usize,
+    pinned_size: usize,
+    // the evicted values are spilled to it
+    disk: Option<Arc<DiskCache>>,
 }
 
 impl Cache {
     pub fn new() -> Self {
-        Cache {
-            refs: HashMap::new(),
-            cache: UnboundCache::new(),
-            capacity: usize::MAX,
-            size: 0,
-            policy: RefCnt::new(64),
-        }
+        Self::with_capacity(usize::MAX)
     }
 
     pub fn with_capacity(cap: usize) -> Self {
+        Self::with_policy(cap, PolicyType::RefCnt)
+    }
+
+    pub fn with_policy(cap: usize, policy: PolicyType) -> Self {
         Cache {
             refs: HashMap::new(),
             cache: UnboundCache::new(),
             capacity: cap,
             size: 0,
-            policy: RefCnt::new(64),
+            policy: build_policy(policy),
+            release_on_zero: policy == PolicyType::RefCnt,
+            hits: 0,
+            misses: 0,
+            evictions: 0,
+            rejections: 0,
+            pinned: HashSet::new(),
+            pin_capacity: 0,
+            pinned_size: 0,
+            disk: None,
         }
     }
 
-    pub fn set(&mut self, key: &str, value: Arc<Vec<Data>>, ref_cnt: usize) {
-        for data in value.iter() {
-            self.size += data.bs.len();
+    pub fn set_pin_capacity(&mut self, cap: usize) {
+        self.pin_capacity = cap;
+    }
+
+    pub fn set_disk(&mut self, disk: DiskCache) {
+        self.disk = Some(Arc::new(disk));
+    }
+
+    // A value is dropped as soon as no job will read it, see PolicyType::RefCnt
+    pub fn releases_on_zero(&self) -> bool {
+        self.release_on_zero
+    }
+
+    // The disk tier is read in the read pool, so it's used without the cache lock
+    pub fn disk(&self) -> Option<Arc<DiskCache>> {
+        self.disk.clone()
+    }
+
+    pub fn set(&mut self, key: CacheKey, value: Arc<Vec<Data>>, ref_cnt: usize) {
+        if self.pinned.contains(&key) {
+            let old = self.cache.cache_set(key, value.clone()).unwrap();
+            self.pinned_size = self.pinned_size - entry_size(&old) + entry_size(&value);
+            self.refs.insert(key, ref_cnt);
+            return;
+        }
+        if self.release_on_zero && ref_cnt == 0 {
+            if self.remove(key).is_some() {
+                log::debug!("Cache release {:?}", key);
+            }
+            return;
+        }
+        let len = entry_size(&value);
+        if len > self.capacity {
+            // it would evict everything and still not fit
+            log::warn!(
+                "Cache (cap: {:}) reject {:?} of {:} bytes",
+                self.capacity,
+                key,
+                len
+            );
+            self.rejections += 1;
+            self.remove(key);
+            return;
+        }
+        match self.cache.cache_remove(&key) {
+            // the old value is replaced, and the policy keeps the state of the key
+            Some(old) => {
+                self.size -= entry_size(&old);
+                self.policy.update_ref(key, ref_cnt);
+            }
+            None => {
+                if self.size + len > self.capacity && !self.policy.admit(key) {
+                    log::debug!("Cache (size: {:}) reject {:?}", self.size, key);
+                    return;
+                }
+                self.policy.set(key, ref_cnt);
+            }
         }
+        self.size += len;
+        self.cache.cache_set(key, value);
+        self.refs.insert(key, ref_cnt);
         while self.size > self.capacity {
-            self.evict();
+            if !self.evict() {
+                break;
+            }
         }
-        self.policy.set(key.to_string(), ref_cnt);
-        self.cache.cache_set(key.to_string(), value);
-        self.refs.insert(key.to_string(), ref_cnt);
 
         log::debug!(
             "Cache(size: {:}, cap: {:}) set {:?} with ref_cnt {:} ",
@@ -56,26 +204,188 @@ impl Cache {
             key,
             ref_cnt
         );
-        
     }
 
-    pub fn get(&mut self, key: &String) -> Option<&Arc<Vec<Data>>> {
-        self.cache.cache_get(key)
+    // Cache a value read ahead of jobs. It never evicts others and returns false when the
+    // budget is used up. The value is kept as if a job would read it, so it's not released
+    // before jobs are added
+    pub fn prewarm(&mut self, key: CacheKey, value: Arc<Vec<Data>>, ref_cnt: usize) -> bool {
+        if self.contains(key) {
+            return true;
+        }
+        if self.size + entry_size(&value) > self.capacity {
+            return false;
+        }
+        self.set(key, value, ref_cnt.max(1));
+        self.contains(key)
+    }
+
+    // Unlike get, it's not counted as a read
+    pub fn contains(&self, key: CacheKey) -> bool {
+        self.cache.get_store().contains_key(&key)
     }
 
-    fn evict(&mut self) {
-        let evict_keys = self.policy.evict().unwrap();
+    pub fn get(&mut self, key: CacheKey) -> Option<&Arc<Vec<Data>>> {
+        self.policy.get(key);
+        let res = self.cache.cache_get(&key);
+        match res {
+            Some(_) => self.hits += 1,
+            None => self.misses += 1,
+        }
+        log::debug!(
+            "Cache get {:?} with hits {:} misses {:}",
+            key,
+            self.hits,
+            self.misses
+        );
+        res
+    }
+
+    // Drop count references of the key, e.g. the jobs which would read it are deleted
+    pub fn release(&mut self, key: CacheKey, count: usize) {
+        let ref_cnt = match self.refs.get_mut(&key) {
+            Some(ref_cnt) => {
+                *ref_cnt = ref_cnt.saturating_sub(count);
+                *ref_cnt
+            }
+            None => return,
+        };
+        if self.pinned.contains(&key) {
+            return;
+        }
+        if self.release_on_zero && ref_cnt == 0 {
+            log::debug!("Cache release {:?}", key);
+            self.remove(key);
+        } else {
+            self.policy.update_ref(key, ref_cnt);
+        }
+    }
+
+    pub fn remove(&mut self, key: CacheKey) -> Option<Arc<Vec<Data>>> {
+        self.refs.remove(&key);
+        self.policy.remove(key);
+        if let Some(disk) = &self.disk {
+            disk.remove(key);
+        }
+        let value = self.cache.cache_remove(&key)?;
+        if self.pinned.remove(&key) {
+            self.pinned_size -= entry_size(&value);
+        } else {
+            self.size -= entry_size(&value);
+        }
+        Some(value)
+    }
+
+    // Keep the value until it's unpinned, a resident value is moved to the pinned budget.
+    // It returns false when the pinned budget is used up
+    pub fn pin(&mut self, key: CacheKey, value: Arc<Vec<Data>>, ref_cnt: usize) -> bool {
+        if self.pinned.contains(&key) {
+            return true;
+        }
+        let len = entry_size(&value);
+        if self.pinned_size + len > self.pin_capacity {
+            return false;
+        }
+        if let Some(old) = self.cache.cache_remove(&key) {
+            self.size -= entry_size(&old);
+            self.policy.remove(key);
+        }
+        self.pinned.insert(key);
+        self.pinned_size += len;
+        self.cache.cache_set(key, value);
+        self.refs.insert(key, ref_cnt);
+        log::debug!("Cache (pinned: {:}) pin {:?}", self.pinned_size, key);
+        true
+    }
+
+    // The value goes back to policy, so it may be evicted or released right away
+    pub fn unpin(&mut self, key: CacheKey) -> bool {
+        if !self.pinned.remove(&key) {
+            return false;
+        }
+        let value = self.cache.cache_remove(&key).unwrap();
+        self.pinned_size -= entry_size(&value);
+        let ref_cnt = self.refs.remove(&key).unwrap_or(0);
+        self.set(key, value, ref_cnt);
+        true
+    }
+
+    pub fn is_pinned(&self, key: CacheKey) -> bool {
+        self.pinned.contains(&key)
+    }
+
+    // Get the value without counting it as a read
+    pub fn peek(&mut self, key: CacheKey) -> Option<Arc<Vec<Data>>> {
+        self.cache.cache_get(&key).cloned()
+    }
+
+    // The distance of the next use of the keys decided by the sampler
+    pub fn set_lookahead(&mut self, next_use: HashMap<CacheKey, usize>) {
+        self.policy.lookahead(next_use);
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    pub fn len(&self) -> usize {
+        self.cache.cache_size()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn hit_count(&self) -> usize {
+        self.hits
+    }
+
+    pub fn miss_count(&self) -> usize {
+        self.misses
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            size: self.size,
+            capacity: self.capacity,
+            items: self.len(),
+            hits: self.hits,
+            misses: self.misses,
+            evictions: self.evictions,
+            rejections: self.rejections,
+            pinned_size: self.pinned_size,
+            pin_capacity: self.pin_capacity,
+            pinned_items: self.pinned.len(),
+        }
+    }
+
+    // Return false if there is nothing to evict
+    fn evict(&mut self) -> bool {
+        let evict_keys = match self.policy.evict() {
+            Some(keys) => keys,
+            None => return false,
+        };
         log::debug!(
             "Cache (size: {:}) evict {:?}",
             self.size,
             evict_keys,
         );
-        for key in evict_keys.iter() {
-            let v = self.cache.cache_remove(key);
-            for data in v.unwrap().iter() {
-                self.size -= data.bs.len();
+        for key in evict_keys {
+            // the key may have been removed by set
+            let value = self.cache.cache_remove(&key);
+            if let Some(v) = &value {
+                self.size -= entry_size(v);
+                self.evictions += 1;
+            }
+            match (&self.disk, value) {
+                // the references are kept to release the spilled value
+                (Some(disk), Some(v)) => disk.put(key, v),
+                _ => {
+                    self.refs.remove(&key);
+                }
             }
         }
+        true
     }
 }
 
@@ -84,30 +394,259 @@ mod tests {
     use super::*;
     use crate::proto::job::data::DataType;
 
+    fn key(name: char) -> CacheKey {
+        CacheKey::new(name as u64, 0)
+    }
+
     fn construct_data(len: usize) -> Data {
         Data {
-            bs: (0..len).map(|x| (x % 256) as u8).collect::<Vec<_>>(),
+            bs: (0..len).map(|x| (x % 256) as u8).collect::<Vec<_>>().into(),
             ty: DataType::Image as i32,
+            ..Default::default()
         }
     }
+
+    // The capacity of n entries of len bytes
+    fn entries(n: usize, len: usize) -> usize {
+        n * entry_size(&[construct_data(len)])
+    }
+
     #[test]
     fn test_cache_simple() {
-        let mut cache = Cache::with_capacity(100);
+        let mut cache = Cache::with_capacity(entries(1, 100));
         let data = Arc::new(vec![construct_data(100)]);
-        cache.set("1", data.clone(), 0);
-        assert_eq!(*cache.get(&"1".to_string()).unwrap(), data);
+        cache.set(key('1'), data.clone(), 1);
+        assert_eq!(*cache.get(key('1')).unwrap(), data);
         let data = Arc::new(vec![construct_data(100)]);
-        cache.set("2", data.clone(), 0);
-        assert_eq!(cache.get(&"1".to_string()), None);
-        assert_eq!(*cache.get(&"2".to_string()).unwrap(), data);
+        cache.set(key('2'), data.clone(), 1);
+        assert_eq!(cache.get(key('1')), None);
+        assert_eq!(*cache.get(key('2')).unwrap(), data);
+    }
+
+    #[test]
+    fn test_cache_hit() {
+        let mut cache = Cache::with_capacity(entries(1, 100));
+        let data = Arc::new(vec![construct_data(60)]);
+        assert_eq!(cache.get(key('1')), None);
+        cache.set(key('1'), data.clone(), 2);
+        // set the same key again should not double count its size
+        cache.set(key('1'), data.clone(), 1);
+        assert_eq!(*cache.get(key('1')).unwrap(), data);
+        assert_eq!(cache.hit_count(), 1);
+        assert_eq!(cache.miss_count(), 1);
+    }
+
+    #[test]
+    fn test_cache_release() {
+        let mut cache = Cache::new();
+        // no job will read it
+        cache.set(key('0'), Arc::new(vec![construct_data(10)]), 0);
+        assert!(cache.is_empty());
+        cache.set(key('1'), Arc::new(vec![construct_data(10)]), 2);
+        cache.set(key('2'), Arc::new(vec![construct_data(10)]), 1);
+        // the last job consumes "2"
+        let data = cache.get(key('2')).unwrap().clone();
+        cache.set(key('2'), data, 0);
+        assert_eq!(cache.len(), 1);
+        cache.release(key('1'), 1);
+        assert_eq!(cache.size(), entries(1, 10));
+        cache.release(key('1'), 1);
+        assert!(cache.is_empty());
+        assert_eq!(cache.size(), 0);
+        // other policies keep the value for later epochs
+        let mut cache = Cache::with_policy(usize::MAX, PolicyType::Lru);
+        cache.set(key('1'), Arc::new(vec![construct_data(10)]), 0);
+        cache.release(key('1'), 1);
+        assert_eq!(cache.len(), 1);
+    }
+
+    #[test]
+    fn test_cache_policy() {
+        for policy in [
+            PolicyType::RefCnt,
+            PolicyType::Lru,
+            PolicyType::Lfu,
+            PolicyType::TinyLfu,
+            PolicyType::Arc,
+            PolicyType::Belady,
+        ] {
+            let mut cache = Cache::with_policy(entries(3, 100), policy);
+            for i in 0..10 {
+                let key = CacheKey::new(i as u64 % 4, 0);
+                if cache.get(key).is_none() {
+                    cache.set(key, Arc::new(vec![construct_data(100)]), i);
+                }
+                assert!(cache.size <= cache.capacity);
+            }
+            // an item larger than capacity is rejected without evicting others
+            let len = cache.len();
+            cache.set(key('B'), Arc::new(vec![construct_data(entries(4, 100))]), 1);
+            assert!(cache.get(key('B')).is_none(), "{:?}", policy);
+            assert_eq!(cache.len(), len);
+            assert_eq!(cache.stats().rejections, 1);
+        }
+    }
+
+    // The hit path of joader: a hit sets the value again with its new ref_cnt
+    fn load(cache: &mut Cache, key: CacheKey, ref_cnt: usize) {
+        let data = match cache.get(key) {
+            Some(data) => data.clone(),
+            None => Arc::new(vec![construct_data(100)]),
+        };
+        cache.set(key, data, ref_cnt);
+    }
+
+    #[test]
+    fn test_cache_policy_hit() {
+        for policy in [
+            PolicyType::RefCnt,
+            PolicyType::Lru,
+            PolicyType::Lfu,
+            PolicyType::TinyLfu,
+            PolicyType::Arc,
+            PolicyType::Belady,
+        ] {
+            let mut cache = Cache::with_policy(entries(3, 100), policy);
+            for name in ['a', 'b', 'c'] {
+                load(&mut cache, key(name), 2);
+            }
+            for _ in 0..3 {
+                load(&mut cache, key('a'), 2);
+            }
+            assert_eq!(cache.hit_count(), 3);
+            // a scan of the data read once
+            for i in 0..4 {
+                load(&mut cache, CacheKey::new(i, 0), 1);
+            }
+            assert!(cache.size <= cache.capacity);
+            let hot = cache.get(key('a')).is_some();
+            assert_eq!(hot, policy != PolicyType::Lru, "{:?}", policy);
+        }
+    }
+
+    #[test]
+    fn test_cache_lookahead() {
+        let mut cache = Cache::with_policy(entries(2, 100), PolicyType::Belady);
+        let next_use = [('a', 2), ('b', 0), ('c', 1)];
+        cache.set_lookahead(next_use.iter().map(|(k, d)| (key(*k), *d)).collect());
+        for name in ['a', 'b', 'c'] {
+            cache.set(key(name), Arc::new(vec![construct_data(100)]), 1);
+        }
+        // "a" is read the latest
+        assert!(cache.get(key('a')).is_none());
+        // "d" is not in the lookahead window
+        cache.set(key('d'), Arc::new(vec![construct_data(100)]), 1);
+        assert!(cache.get(key('d')).is_none());
+        assert!(cache.get(key('b')).is_some());
+        assert!(cache.get(key('c')).is_some());
+    }
+
+    #[test]
+    fn test_cache_spill() {
+        let dir = std::env::temp_dir().join(format!("joader-spill-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let mut cache = Cache::with_capacity(entries(2, 100));
+        cache.set_disk(DiskCache::open(&dir, 1000).unwrap());
+        let disk = cache.disk().unwrap();
+        for (name, ref_cnt) in [('1', 1), ('2', 2), ('3', 2)] {
+            cache.set(key(name), Arc::new(vec![construct_data(100)]), ref_cnt);
+        }
+        disk.flush();
+        assert!(cache.get(key('1')).is_none());
+        assert_eq!(disk.get(key('1')).unwrap(), vec![construct_data(100)]);
+        // the spilled value is dropped when no job will read it
+        cache.release(key('1'), 1);
+        disk.flush();
+        assert!(!disk.contains(key('1')));
+        drop(cache);
+        drop(disk);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_cache_stats() {
+        let data = vec![Data {
+            shape: vec![10, 10, 1],
+            lens: vec![100],
+            ..construct_data(100)
+        }];
+        // the shape and lens are counted
+        assert_eq!(entry_size(&data), entries(1, 100) + 3 * 4 + 8);
+        let mut cache = Cache::with_capacity(entries(2, 100));
+        for name in ['a', 'b', 'c'] {
+            cache.set(key(name), Arc::new(vec![construct_data(100)]), 1);
+        }
+        cache.get(key('c'));
+        let stats = cache.stats();
+        assert_eq!(stats.size, entries(2, 100));
+        assert_eq!(stats.items, 2);
+        assert_eq!(stats.evictions, 1);
+        assert_eq!((stats.hits, stats.misses), (1, 0));
+    }
+
+    #[test]
+    fn test_parse_capacity() {
+        assert_eq!(parse_capacity("1073741824"), Ok(1073741824));
+        assert_eq!(parse_capacity("0%"), Ok(0));
+        let half = parse_capacity("50%").unwrap();
+        assert!(half > 0 && half < parse_capacity("100%").unwrap());
+        assert!(parse_capacity("150%").is_err());
+        assert!(parse_capacity("1GB").is_err());
+    }
+
+    #[test]
+    fn test_cache_prewarm() {
+        let mut cache = Cache::with_capacity(entries(2, 100));
+        cache.set(key('a'), Arc::new(vec![construct_data(100)]), 1);
+        // no job reads it yet
+        assert!(cache.prewarm(key('b'), Arc::new(vec![construct_data(100)]), 0));
+        assert!(cache.contains(key('b')));
+        assert!(cache.prewarm(key('b'), Arc::new(vec![construct_data(100)]), 0));
+        // the cache is full, and nothing is evicted
+        assert!(!cache.prewarm(key('c'), Arc::new(vec![construct_data(100)]), 3));
+        assert!(cache.contains(key('a')) && !cache.contains(key('c')));
+        assert_eq!(cache.stats().evictions, 0);
+    }
+
+    #[test]
+    fn test_cache_pin() {
+        let mut cache = Cache::with_capacity(entries(1, 100));
+        cache.set_pin_capacity(entries(2, 100));
+        cache.set(key('a'), Arc::new(vec![construct_data(100)]), 1);
+        // the resident value is moved to the pinned budget
+        let data = cache.peek(key('a')).unwrap();
+        assert!(cache.pin(key('a'), data, 1));
+        assert_eq!(cache.size(), 0);
+        assert!(cache.pin(key('b'), Arc::new(vec![construct_data(100)]), 0));
+        assert!(!cache.pin(key('c'), Arc::new(vec![construct_data(100)]), 0));
+        // the pinned values are neither evicted nor released
+        for name in ['d', 'e'] {
+            cache.set(key(name), Arc::new(vec![construct_data(100)]), 1);
+        }
+        let data = cache.get(key('a')).unwrap().clone();
+        cache.set(key('a'), data, 0);
+        cache.release(key('b'), 1);
+        assert!(cache.contains(key('a')) && cache.contains(key('b')));
+        let stats = cache.stats();
+        assert_eq!(
+            (stats.pinned_items, stats.pinned_size),
+            (2, entries(2, 100))
+        );
+        // no job will read "a", so it's released once unpinned
+        assert!(cache.unpin(key('a')));
+        assert!(!cache.unpin(key('a')));
+        assert!(!cache.contains(key('a')));
+        assert!(cache.pin(key('c'), Arc::new(vec![construct_data(100)]), 0));
+        cache.remove(key('b'));
+        assert_eq!(cache.stats().pinned_size, entries(1, 100));
     }
 
     #[test]
     fn test_cache() {
-        let mut cache = Cache::with_capacity(500);
+        let mut cache = Cache::with_capacity(entries(5, 100));
         for i in 1..10 {
             let data = Arc::new(vec![construct_data(100)]);
-            cache.set(&i.to_string(), data, i);
+            cache.set(CacheKey::new(i as u64, 0), data, i);
         }
     }
 }
@@ -0,0 +1,424 @@
+// The second tier of cache: the values evicted from memory are spilled to files in a local
+// directory, and they are read back instead of reading and decoding the dataset again.
+// A spill file is written as a tmp file and renamed, and it carries a checksum, so a crash
+// leaves either a complete file or a file which is discarded when it's read.
+
+use prost::Message;
+use std::collections::{BTreeMap, HashMap};
+use std::convert::TryInto;
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use super::key::{fnv1a, CacheKey};
+use crate::proto::job::Data;
+
+const MAGIC: &[u8; 8] = b"JOADER01";
+const SPILL_EXT: &str = "spill";
+const TMP_EXT: &str = "tmp";
+
+// magic | data id (u64) | transform (u64) | payload len (u64) | checksum (u64) | payload,
+// the checksum covers the key and payload, it only needs to catch torn writes and bit rot
+pub(super) fn encode(key: CacheKey, value: &[Data]) -> Vec<u8> {
+    let mut buf = Vec::new();
+    buf.extend_from_slice(MAGIC);
+    buf.extend_from_slice(&key.data_id.to_le_bytes());
+    buf.extend_from_slice(&key.transform.to_le_bytes());
+    let mut payload = Vec::new();
+    for data in value {
+        data.encode_length_delimited(&mut payload).unwrap();
+    }
+    buf.extend_from_slice(&(payload.len() as u64).to_le_bytes());
+    let sum = fnv1a(&buf[MAGIC.len()..MAGIC.len() + 16]) ^ fnv1a(&payload);
+    buf.extend_from_slice(&sum.to_le_bytes());
+    buf.extend_from_slice(&payload);
+    buf
+}
+
+pub(super) fn invalid(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+pub(super) fn take<'a>(buf: &mut &'a [u8], len: usize) -> io::Result<&'a [u8]> {
+    if buf.len() < len {
+        return Err(invalid("truncated spill file"));
+    }
+    let (head, tail) = buf.split_at(len);
+    *buf = tail;
+    Ok(head)
+}
+
+pub(super) fn take_u64(buf: &mut &[u8]) -> io::Result<u64> {
+    Ok(u64::from_le_bytes(take(buf, 8)?.try_into().unwrap()))
+}
+
+pub(super) fn decode(mut buf: &[u8]) -> io::Result<(CacheKey, Vec<Data>)> {
+    if take(&mut buf, MAGIC.len())? != MAGIC {
+        return Err(invalid("bad magic"));
+    }
+    let key_bytes = &buf[..buf.len().min(16)];
+    let key = CacheKey::new(take_u64(&mut buf)?, take_u64(&mut buf)?);
+    let payload_len = take_u64(&mut buf)? as usize;
+    let sum = take_u64(&mut buf)?;
+    let mut payload = take(&mut buf, payload_len)?;
+    if !buf.is_empty() {
+        return Err(invalid("trailing bytes"));
+    }
+    if fnv1a(key_bytes) ^ fnv1a(payload) != sum {
+        return Err(invalid("checksum mismatch"));
+    }
+    let mut value = Vec::new();
+    while !payload.is_empty() {
+        value.push(Data::decode_length_delimited(&mut payload).map_err(|_| invalid("bad data"))?);
+    }
+    Ok((key, value))
+}
+
+// The inverse of CacheKey's Display, the files of the last run are indexed by their names
+fn parse_key(name: &str) -> Option<CacheKey> {
+    let (data_id, transform) = name.split_once('-')?;
+    Some(CacheKey::new(
+        u64::from_str_radix(data_id, 16).ok()?,
+        u64::from_str_radix(transform, 16).ok()?,
+    ))
+}
+
+#[derive(Debug, Default)]
+struct Index {
+    // key -> (file size, last use)
+    entries: HashMap<CacheKey, (usize, u64)>,
+    // last use -> key, the least recently used file is evicted first
+    lru: BTreeMap<u64, CacheKey>,
+    size: usize,
+    clock: u64,
+}
+
+impl Index {
+    fn touch(&mut self, key: CacheKey) {
+        if let Some((_, time)) = self.entries.get_mut(&key) {
+            self.lru.remove(time);
+            self.clock += 1;
+            *time = self.clock;
+            self.lru.insert(self.clock, key);
+        }
+    }
+
+    fn insert(&mut self, key: CacheKey, len: usize) {
+        self.remove(key);
+        self.clock += 1;
+        self.size += len;
+        self.lru.insert(self.clock, key);
+        self.entries.insert(key, (len, self.clock));
+    }
+
+    fn remove(&mut self, key: CacheKey) -> bool {
+        match self.entries.remove(&key) {
+            Some((len, time)) => {
+                self.lru.remove(&time);
+                self.size -= len;
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+#[derive(Debug)]
+enum Op {
+    Put(CacheKey, Arc<Vec<Data>>),
+    Remove(CacheKey),
+    Flush(Sender<()>),
+}
+
+// Writes go through a background thread in order, so evicting from memory never waits
+// for the disk. Reads are synchronous and should run in the read pool.
+#[derive(Debug)]
+pub struct DiskCache {
+    dir: PathBuf,
+    capacity: usize,
+    index: Arc<Mutex<Index>>,
+    // Sender is not Sync, but the cache is shared by the read pool
+    tx: Mutex<Sender<Op>>,
+}
+
+impl DiskCache {
+    // Open the tier in dir, the files of the last run are kept and checked when they are read
+    pub fn open<P: AsRef<Path>>(dir: P, capacity: usize) -> io::Result<Self> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir)?;
+        let mut index = Index::default();
+        if let Err(e) = Self::load(&dir, &mut index) {
+            log::warn!("Discard the corrupted disk cache in {:?}: {}", dir, e);
+            Self::clear(&dir)?;
+            index = Index::default();
+        }
+        log::info!(
+            "Disk cache in {:?} with {} files ({} bytes)",
+            dir,
+            index.entries.len(),
+            index.size
+        );
+        let index = Arc::new(Mutex::new(index));
+        let (tx, rx) = channel();
+        let (writer_dir, writer_index) = (dir.clone(), index.clone());
+        thread::Builder::new()
+            .name("joader-spill".to_string())
+            .spawn(move || {
+                for op in rx {
+                    Self::apply(&writer_dir, capacity, &writer_index, op);
+                }
+            })?;
+        Ok(DiskCache {
+            dir,
+            capacity,
+            index,
+            tx: Mutex::new(tx),
+        })
+    }
+
+    fn load(dir: &Path, index: &mut Index) -> io::Result<()> {
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            match path.extension().and_then(|ext| ext.to_str()) {
+                // a write was interrupted
+                Some(TMP_EXT) => fs::remove_file(&path)?,
+                // only the name and size are read, so opening a large tier is cheap
+                Some(SPILL_EXT) => match path
+                    .file_stem()
+                    .and_then(|s| s.to_str())
+                    .and_then(parse_key)
+                {
+                    Some(key) => index.insert(key, fs::metadata(&path)?.len() as usize),
+                    None => {
+                        log::warn!("Discard {:?} with a bad name in the disk cache", path);
+                        fs::remove_file(&path)?;
+                    }
+                },
+                // the directory may be shared with other files, they are left alone
+                _ => log::warn!("Skip {:?} in the disk cache", path),
+            }
+        }
+        Ok(())
+    }
+
+    fn clear(dir: &Path) -> io::Result<()> {
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            match path.extension().and_then(|ext| ext.to_str()) {
+                Some(TMP_EXT) | Some(SPILL_EXT) => fs::remove_file(&path)?,
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn path(dir: &Path, key: CacheKey, ext: &str) -> PathBuf {
+        dir.join(key.to_string()).with_extension(ext)
+    }
+
+    fn apply(dir: &Path, capacity: usize, index: &Mutex<Index>, op: Op) {
+        match op {
+            Op::Put(key, value) => {
+                if index.lock().unwrap().entries.contains_key(&key) {
+                    return;
+                }
+                let buf = encode(key, &value);
+                if buf.len() > capacity {
+                    log::debug!("Disk cache reject {:?} of {} bytes", key, buf.len());
+                    return;
+                }
+                let tmp = Self::path(dir, key, TMP_EXT);
+                let res = fs::write(&tmp, &buf)
+                    .and_then(|_| fs::rename(&tmp, Self::path(dir, key, SPILL_EXT)));
+                if let Err(e) = res {
+                    log::warn!("Fail to spill {:?}: {}", key, e);
+                    let _ = fs::remove_file(&tmp);
+                    return;
+                }
+                let mut index = index.lock().unwrap();
+                index.insert(key, buf.len());
+                while index.size > capacity {
+                    let key = *index.lru.values().next().unwrap();
+                    index.remove(key);
+                    log::debug!("Disk cache evict {:?}", key);
+                    let _ = fs::remove_file(Self::path(dir, key, SPILL_EXT));
+                }
+            }
+            Op::Remove(key) => {
+                if index.lock().unwrap().remove(key) {
+                    let _ = fs::remove_file(Self::path(dir, key, SPILL_EXT));
+                }
+            }
+            Op::Flush(done) => {
+                let _ = done.send(());
+            }
+        }
+    }
+
+    fn send(&self, op: Op) {
+        // the writer only stops when the cache is dropped
+        self.tx.lock().unwrap().send(op).unwrap();
+    }
+
+    // Spill the value in the background
+    pub fn put(&self, key: CacheKey, value: Arc<Vec<Data>>) {
+        self.send(Op::Put(key, value));
+    }
+
+    pub fn remove(&self, key: CacheKey) {
+        self.send(Op::Remove(key));
+    }
+
+    // Wait for the writes issued before
+    pub fn flush(&self) {
+        let (tx, rx) = channel();
+        self.send(Op::Flush(tx));
+        rx.recv().unwrap();
+    }
+
+    // A broken file is dropped and reported as a miss, the other files are kept
+    pub fn get(&self, key: CacheKey) -> Option<Vec<Data>> {
+        if !self.index.lock().unwrap().entries.contains_key(&key) {
+            return None;
+        }
+        let path = Self::path(&self.dir, key, SPILL_EXT);
+        let res = fs::File::open(&path).and_then(|mut f| {
+            let mut buf = Vec::new();
+            f.read_to_end(&mut buf)?;
+            decode(&buf)
+        });
+        match res {
+            Ok((stored, value)) if stored == key => {
+                self.index.lock().unwrap().touch(key);
+                Some(value)
+            }
+            Ok((stored, _)) => {
+                log::warn!(
+                    "Fail to read the spilled {:?}: the file holds {:?}",
+                    key,
+                    stored
+                );
+                self.remove(key);
+                None
+            }
+            Err(e) => {
+                log::warn!("Fail to read the spilled {:?}: {}", key, e);
+                self.remove(key);
+                None
+            }
+        }
+    }
+
+    pub fn contains(&self, key: CacheKey) -> bool {
+        self.index.lock().unwrap().entries.contains_key(&key)
+    }
+
+    pub fn size(&self) -> usize {
+        self.index.lock().unwrap().size
+    }
+
+    pub fn len(&self) -> usize {
+        self.index.lock().unwrap().entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::proto::job::data::DataType;
+
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("joader-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn key(name: char) -> CacheKey {
+        CacheKey::new(name as u64, 0)
+    }
+
+    fn construct_data(len: usize) -> Arc<Vec<Data>> {
+        Arc::new(vec![Data {
+            bs: (0..len).map(|x| (x % 256) as u8).collect::<Vec<_>>().into(),
+            ty: DataType::Image as i32,
+            shape: vec![len as u32],
+            ..Default::default()
+        }])
+    }
+
+    #[test]
+    fn test_disk_cache() {
+        let dir = test_dir("disk");
+        let disk = DiskCache::open(&dir, 1200).unwrap();
+        let data = construct_data(300);
+        for name in ['1', '2', '3', '4'] {
+            disk.put(key(name), data.clone());
+        }
+        disk.flush();
+        // each file has a header of about 40 bytes, so only 3 of them fit
+        assert_eq!(disk.len(), 3);
+        assert!(disk.get(key('1')).is_none());
+        assert_eq!(disk.get(key('2')).unwrap(), *data);
+        disk.put(key('5'), data);
+        disk.remove(key('4'));
+        disk.flush();
+        assert!(disk.get(key('3')).is_none());
+        assert!(!disk.contains(key('4')));
+        assert!(disk.contains(key('2')) && disk.contains(key('5')));
+        // an oversized value is not spilled
+        disk.put(key('B'), construct_data(2000));
+        disk.flush();
+        assert!(!disk.contains(key('B')));
+        drop(disk);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_disk_cache_restart() {
+        let dir = test_dir("restart");
+        let data = construct_data(100);
+        {
+            let disk = DiskCache::open(&dir, 1000).unwrap();
+            disk.put(key('a'), data.clone());
+            disk.put(key('b'), data.clone());
+            disk.flush();
+        }
+        // an interrupted write is dropped
+        fs::write(DiskCache::path(&dir, key('c'), TMP_EXT), b"JOAD").unwrap();
+        let disk = DiskCache::open(&dir, 1000).unwrap();
+        assert_eq!(disk.len(), 2);
+        assert_eq!(disk.get(key('a')).unwrap(), *data);
+        drop(disk);
+        // a corrupted file and a file holding another key are indexed by their names, and
+        // they are dropped when they are read
+        let path = DiskCache::path(&dir, key('b'), SPILL_EXT);
+        let mut buf = fs::read(&path).unwrap();
+        fs::write(DiskCache::path(&dir, key('d'), SPILL_EXT), &buf).unwrap();
+        let last = buf.len() - 1;
+        buf[last] ^= 0xff;
+        fs::write(&path, buf).unwrap();
+        // a spill file which is not named by a key
+        fs::write(dir.join("junk").with_extension(SPILL_EXT), b"JOADER01").unwrap();
+        let disk = DiskCache::open(&dir, 1000).unwrap();
+        assert_eq!(disk.len(), 3);
+        assert!(disk.get(key('b')).is_none());
+        assert!(disk.get(key('d')).is_none());
+        disk.flush();
+        assert_eq!(disk.len(), 1);
+        assert_eq!(disk.get(key('a')).unwrap(), *data);
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+        drop(disk);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,70 @@
+use std::fmt;
+
+// A cached artifact is the data read by the dataset and the transform applied to it,
+// e.g. the same image decoded at another size is another artifact
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct CacheKey {
+    // see dataset::data_id
+    pub data_id: u64,
+    // 0 if the data is not transformed
+    pub transform: u64,
+}
+
+impl CacheKey {
+    pub fn new(data_id: u64, transform: u64) -> Self {
+        CacheKey { data_id, transform }
+    }
+}
+
+impl fmt::Display for CacheKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:016x}-{:016x}", self.data_id, self.transform)
+    }
+}
+
+// FNV-1a, it's stable across runs and versions
+pub const fn fnv1a(bytes: &[u8]) -> u64 {
+    fnv1a_with(0xcbf29ce484222325, bytes)
+}
+
+// Continue the hash of the bytes before, so a hash is built from several pieces
+pub const fn fnv1a_with(mut hash: u64, bytes: &[u8]) -> u64 {
+    let mut i = 0;
+    while i < bytes.len() {
+        hash ^= bytes[i] as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+        i += 1;
+    }
+    hash
+}
+
+// The fingerprint of a transform from its description, the spilled samples are keyed by it
+pub const fn fingerprint(desc: &str) -> u64 {
+    fnv1a(desc.as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dataset::data_id;
+
+    #[test]
+    fn test_cache_key() {
+        // they were both "123"
+        assert_ne!(
+            CacheKey::new(data_id(1, 23), 0),
+            CacheKey::new(data_id(12, 3), 0)
+        );
+        assert_ne!(
+            CacheKey::new(data_id(1, 23), 0),
+            CacheKey::new(data_id(1, 23), fingerprint("resize"))
+        );
+        assert_eq!(fingerprint("resize"), fingerprint("resize"));
+        assert_ne!(fingerprint("resize_224"), fingerprint("resize_256"));
+        assert_eq!(fnv1a_with(fnv1a(b"res"), b"ize"), fnv1a(b"resize"));
+        assert_eq!(
+            CacheKey::new(1, 2).to_string(),
+            "0000000000000001-0000000000000002"
+        );
+    }
+}
@@ -0,0 +1,223 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fmt::{self, Debug};
+use std::hash::{Hash, Hasher};
+
+use super::key::CacheKey;
+use super::lru::LruList;
+use super::policy::Policy;
+
+// Evict the least frequently used key, the older one is evicted first on tie
+#[derive(Debug, Default)]
+pub struct Lfu {
+    clock: u64,
+    // key -> (frequency, last access time)
+    key_table: HashMap<CacheKey, (u64, u64)>,
+    freq_table: BTreeSet<(u64, u64, CacheKey)>,
+}
+
+impl Lfu {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn touch(&mut self, key: CacheKey, insert: bool) {
+        let freq = match self.key_table.remove(&key) {
+            Some((freq, time)) => {
+                self.freq_table.remove(&(freq, time, key));
+                freq + 1
+            }
+            None if insert => 1,
+            None => return,
+        };
+        self.clock += 1;
+        self.key_table.insert(key, (freq, self.clock));
+        self.freq_table.insert((freq, self.clock, key));
+    }
+}
+
+impl Policy for Lfu {
+    fn set(&mut self, key: CacheKey, _ref_cnt: usize) {
+        self.touch(key, true);
+    }
+
+    fn get(&mut self, key: CacheKey) {
+        self.touch(key, false);
+    }
+
+    fn remove(&mut self, key: CacheKey) {
+        if let Some((freq, time)) = self.key_table.remove(&key) {
+            self.freq_table.remove(&(freq, time, key));
+        }
+    }
+
+    fn evict(&mut self) -> Option<HashSet<CacheKey>> {
+        let first = *self.freq_table.iter().next()?;
+        self.freq_table.remove(&first);
+        self.key_table.remove(&first.2);
+        Some(HashSet::from([first.2]))
+    }
+}
+
+const DEPTH: usize = 4;
+const WIDTH: usize = 1 << 16;
+// counters are 4 bits in the paper
+const MAX_COUNT: u8 = 15;
+
+// The approximate access frequency of keys, counters are halved periodically
+// so that the old accesses are forgotten
+struct CountMinSketch {
+    table: Vec<u8>,
+    additions: usize,
+    sample_size: usize,
+}
+
+impl Debug for CountMinSketch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CountMinSketch")
+            .field("additions", &self.additions)
+            .field("sample_size", &self.sample_size)
+            .finish()
+    }
+}
+
+impl CountMinSketch {
+    fn new() -> Self {
+        CountMinSketch {
+            table: vec![0; DEPTH * WIDTH],
+            additions: 0,
+            sample_size: 10 * WIDTH,
+        }
+    }
+
+    fn index(key: CacheKey, row: usize) -> usize {
+        let mut hasher = DefaultHasher::new();
+        (row, key).hash(&mut hasher);
+        row * WIDTH + (hasher.finish() as usize & (WIDTH - 1))
+    }
+
+    fn increment(&mut self, key: CacheKey) {
+        for row in 0..DEPTH {
+            let idx = Self::index(key, row);
+            if self.table[idx] < MAX_COUNT {
+                self.table[idx] += 1;
+            }
+        }
+        self.additions += 1;
+        if self.additions >= self.sample_size {
+            for cnt in self.table.iter_mut() {
+                *cnt /= 2;
+            }
+            self.additions /= 2;
+        }
+    }
+
+    fn estimate(&self, key: CacheKey) -> u8 {
+        (0..DEPTH)
+            .map(|row| self.table[Self::index(key, row)])
+            .min()
+            .unwrap()
+    }
+}
+
+// LRU eviction with TinyLFU admission: a new key is admitted only when it's
+// accessed more frequently than the key it would evict
+#[derive(Debug)]
+pub struct TinyLfu {
+    sketch: CountMinSketch,
+    list: LruList,
+}
+
+impl TinyLfu {
+    pub fn new() -> Self {
+        TinyLfu {
+            sketch: CountMinSketch::new(),
+            list: LruList::new(),
+        }
+    }
+}
+
+impl Policy for TinyLfu {
+    fn set(&mut self, key: CacheKey, _ref_cnt: usize) {
+        self.list.touch(key);
+    }
+
+    // Every read is recorded in sketch, including the misses
+    fn get(&mut self, key: CacheKey) {
+        self.sketch.increment(key);
+        if self.list.contains(key) {
+            self.list.touch(key);
+        }
+    }
+
+    fn remove(&mut self, key: CacheKey) {
+        self.list.remove(key);
+    }
+
+    fn admit(&mut self, key: CacheKey) -> bool {
+        match self.list.front() {
+            Some(victim) => self.sketch.estimate(key) > self.sketch.estimate(victim),
+            None => true,
+        }
+    }
+
+    fn evict(&mut self) -> Option<HashSet<CacheKey>> {
+        self.list.pop_front().map(|key| HashSet::from([key]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(name: char) -> CacheKey {
+        CacheKey::new(name as u64, 0)
+    }
+
+    #[test]
+    fn test_lfu() {
+        let mut policy = Lfu::new();
+        for name in ['a', 'b', 'c'] {
+            policy.set(key(name), 0);
+        }
+        policy.get(key('a'));
+        policy.get(key('a'));
+        policy.get(key('c'));
+        // not in cache
+        policy.get(key('d'));
+        assert_eq!(policy.evict(), Some(HashSet::from([key('b')])));
+        assert_eq!(policy.evict(), Some(HashSet::from([key('c')])));
+        assert_eq!(policy.evict(), Some(HashSet::from([key('a')])));
+        assert_eq!(policy.evict(), None);
+    }
+
+    #[test]
+    fn test_tiny_lfu() {
+        let mut policy = TinyLfu::new();
+        assert!(policy.admit(key('a')));
+        policy.get(key('a'));
+        policy.set(key('a'), 0);
+        policy.get(key('a'));
+        // "b" is read less than "a"
+        policy.get(key('b'));
+        assert!(!policy.admit(key('b')));
+        for _ in 0..3 {
+            policy.get(key('b'));
+        }
+        assert!(policy.admit(key('b')));
+        assert_eq!(policy.evict(), Some(HashSet::from([key('a')])));
+    }
+
+    #[test]
+    fn test_sketch_reset() {
+        let mut sketch = CountMinSketch::new();
+        for _ in 0..MAX_COUNT as usize + 1 {
+            sketch.increment(key('a'));
+        }
+        assert_eq!(sketch.estimate(key('a')), MAX_COUNT);
+        for i in 0..sketch.sample_size {
+            sketch.increment(CacheKey::new(i as u64, 1));
+        }
+        assert!(sketch.estimate(key('a')) < MAX_COUNT);
+    }
+}
@@ -0,0 +1,112 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use super::key::CacheKey;
+use super::policy::Policy;
+
+// Keys ordered by the time they are touched, the front is the least recently used
+#[derive(Debug, Default)]
+pub struct LruList {
+    clock: u64,
+    key_table: HashMap<CacheKey, u64>,
+    time_table: BTreeMap<u64, CacheKey>,
+}
+
+impl LruList {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn touch(&mut self, key: CacheKey) {
+        self.remove(key);
+        self.clock += 1;
+        self.key_table.insert(key, self.clock);
+        self.time_table.insert(self.clock, key);
+    }
+
+    pub fn remove(&mut self, key: CacheKey) -> bool {
+        match self.key_table.remove(&key) {
+            Some(time) => {
+                self.time_table.remove(&time);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn contains(&self, key: CacheKey) -> bool {
+        self.key_table.contains_key(&key)
+    }
+
+    pub fn front(&self) -> Option<CacheKey> {
+        self.time_table.values().next().copied()
+    }
+
+    pub fn pop_front(&mut self) -> Option<CacheKey> {
+        let time = *self.time_table.keys().next()?;
+        let key = self.time_table.remove(&time)?;
+        self.key_table.remove(&key);
+        Some(key)
+    }
+
+    pub fn len(&self) -> usize {
+        self.key_table.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.key_table.is_empty()
+    }
+}
+
+// Evict the least recently used key
+#[derive(Debug, Default)]
+pub struct Lru {
+    list: LruList,
+}
+
+impl Lru {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Policy for Lru {
+    fn set(&mut self, key: CacheKey, _ref_cnt: usize) {
+        self.list.touch(key);
+    }
+
+    fn get(&mut self, key: CacheKey) {
+        if self.list.contains(key) {
+            self.list.touch(key);
+        }
+    }
+
+    fn remove(&mut self, key: CacheKey) {
+        self.list.remove(key);
+    }
+
+    fn evict(&mut self) -> Option<HashSet<CacheKey>> {
+        self.list.pop_front().map(|key| HashSet::from([key]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(name: char) -> CacheKey {
+        CacheKey::new(name as u64, 0)
+    }
+
+    #[test]
+    fn test_lru() {
+        let mut policy = Lru::new();
+        for name in ['a', 'b', 'c'] {
+            policy.set(key(name), 0);
+        }
+        policy.get(key('a'));
+        policy.remove(key('c'));
+        assert_eq!(policy.evict(), Some(HashSet::from([key('b')])));
+        assert_eq!(policy.evict(), Some(HashSet::from([key('a')])));
+        assert_eq!(policy.evict(), None);
+    }
+}
@@ -1,2 +1,9 @@
 pub mod cache;
-mod policy;
\ No newline at end of file
+pub mod policy;
+mod lru;
+mod lfu;
+mod arc;
+mod belady;
+pub mod disk;
+pub mod key;
+pub mod snapshot;
@@ -1,50 +1,155 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fmt::Debug;
+use std::str::FromStr;
 
-pub trait Policy {
-    fn set(&mut self, key: String, ref_cnt: usize);
-    fn evict(&mut self) -> Option<HashSet<String>>;
+use super::arc::AdaptiveReplacement;
+use super::belady::Belady;
+use super::key::CacheKey;
+use super::lfu::{Lfu, TinyLfu};
+use super::lru::Lru;
+
+pub trait Policy: Debug + Send {
+    // The key is inserted or updated, ref_cnt is the number of jobs which will read it
+    fn set(&mut self, key: CacheKey, ref_cnt: usize);
+    // The key is read from cache
+    fn get(&mut self, _key: CacheKey) {}
+    fn remove(&mut self, key: CacheKey);
+    // The number of jobs which will read the key is changed
+    fn update_ref(&mut self, _key: CacheKey, _ref_cnt: usize) {}
+    // Whether a new key is worth evicting others, it's asked only when the cache is full
+    fn admit(&mut self, _key: CacheKey) -> bool {
+        true
+    }
+    // The distance of the next use of the keys which will be read soon,
+    // the keys not in it will not be read in the lookahead window
+    fn lookahead(&mut self, _next_use: HashMap<CacheKey, usize>) {}
+    // Return None if there is nothing to evict
+    fn evict(&mut self) -> Option<HashSet<CacheKey>>;
 }
 
-#[derive(Debug)]
-pub struct RefCnt {
-    key_table: HashMap<String, usize>,
-    ref_table: Vec<HashSet<String>>,
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PolicyType {
+    RefCnt,
+    Lru,
+    Lfu,
+    TinyLfu,
+    Arc,
+    Belady,
 }
 
-impl RefCnt {
-    pub fn new(level: usize) -> Self {
-        let mut ref_table = Vec::new();
-        for _ in 0..level {
-            ref_table.push(HashSet::new());
-        }
-        RefCnt {
-            key_table: HashMap::new(),
-            ref_table,
+impl FromStr for PolicyType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "refcnt" => Ok(PolicyType::RefCnt),
+            "lru" => Ok(PolicyType::Lru),
+            "lfu" => Ok(PolicyType::Lfu),
+            "tinylfu" => Ok(PolicyType::TinyLfu),
+            "arc" => Ok(PolicyType::Arc),
+            "belady" => Ok(PolicyType::Belady),
+            err => Err(format!("unknown cache policy {:?}", err)),
         }
     }
 }
 
+pub fn build_policy(ty: PolicyType) -> Box<dyn Policy> {
+    match ty {
+        PolicyType::RefCnt => Box::new(RefCnt::new()),
+        PolicyType::Lru => Box::new(Lru::new()),
+        PolicyType::Lfu => Box::new(Lfu::new()),
+        PolicyType::TinyLfu => Box::new(TinyLfu::new()),
+        PolicyType::Arc => Box::new(AdaptiveReplacement::new()),
+        PolicyType::Belady => Box::new(Belady::new()),
+    }
+}
+
+// Evict the key which will be read by the fewest jobs, the older one is evicted first on tie
+#[derive(Debug, Default)]
+pub struct RefCnt {
+    clock: u64,
+    // key -> (ref_cnt, set time)
+    key_table: HashMap<CacheKey, (usize, u64)>,
+    ref_table: BTreeSet<(usize, u64, CacheKey)>,
+}
+
+impl RefCnt {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
 
 impl Policy for RefCnt {
-    fn set(&mut self, key: String, ref_cnt: usize) {
+    fn set(&mut self, key: CacheKey, ref_cnt: usize) {
+        self.remove(key);
+        self.clock += 1;
+        self.key_table.insert(key, (ref_cnt, self.clock));
+        self.ref_table.insert((ref_cnt, self.clock, key));
+    }
+
+    fn update_ref(&mut self, key: CacheKey, ref_cnt: usize) {
         if self.key_table.contains_key(&key) {
-            let old_ref = self.key_table[&key];
-            self.ref_table[old_ref].remove(&key);
+            self.set(key, ref_cnt);
+        }
+    }
+
+    fn remove(&mut self, key: CacheKey) {
+        if let Some((ref_cnt, time)) = self.key_table.remove(&key) {
+            self.ref_table.remove(&(ref_cnt, time, key));
         }
-        self.key_table.insert(key.clone(), ref_cnt);
-        self.ref_table[ref_cnt].insert(key);
-    }
-
-    fn evict(&mut self) -> Option<HashSet<String>> {
-        let mut res = None;
-        for node in self.ref_table.iter_mut() {
-            if node.is_empty() {
-                continue;
-            }
-            res = Some(node.clone());
-            node.clear();
-            break;
+    }
+
+    fn evict(&mut self) -> Option<HashSet<CacheKey>> {
+        let first = *self.ref_table.iter().next()?;
+        self.ref_table.remove(&first);
+        self.key_table.remove(&first.2);
+        Some(HashSet::from([first.2]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(name: char) -> CacheKey {
+        CacheKey::new(name as u64, 0)
+    }
+
+    fn evict_one(policy: &mut dyn Policy) -> CacheKey {
+        let keys = policy.evict().unwrap();
+        assert_eq!(keys.len(), 1);
+        keys.into_iter().next().unwrap()
+    }
+
+    #[test]
+    fn test_ref_cnt() {
+        let mut policy = RefCnt::new();
+        assert_eq!(policy.evict(), None);
+        policy.set(key('a'), 128);
+        policy.set(key('b'), 3);
+        policy.set(key('c'), 3);
+        policy.set(key('b'), 1);
+        policy.update_ref(key('a'), 2);
+        policy.update_ref(key('d'), 0);
+        assert_eq!(evict_one(&mut policy), key('b'));
+        // only one key is evicted at a time
+        policy.set(key('e'), 2);
+        assert_eq!(evict_one(&mut policy), key('a'));
+        policy.remove(key('c'));
+        assert_eq!(evict_one(&mut policy), key('e'));
+        assert_eq!(policy.evict(), None);
+    }
+
+    #[test]
+    fn test_build_policy() {
+        for name in ["refcnt", "lru", "lfu", "tinylfu", "arc", "belady"] {
+            let mut policy = build_policy(name.parse().unwrap());
+            assert_eq!(policy.evict(), None);
+            policy.set(key('a'), 1);
+            policy.get(key('a'));
+            assert_eq!(evict_one(policy.as_mut()), key('a'));
+            assert_eq!(policy.evict(), None);
         }
-        res
+        assert!("fifo".parse::<PolicyType>().is_err());
     }
 }
@@ -0,0 +1,230 @@
+// The cached samples of each dataset are saved to a local directory on graceful shutdown,
+// and they are restored when the dataset is created again after restart, so the first
+// epoch does not run at cold-cache speed. The dataset ids are given at creation, so a
+// snapshot is found by the dataset name, and it's discarded if the dataset has changed.
+
+use std::convert::TryInto;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use super::disk::{decode, encode, invalid, take, take_u64};
+use super::key::{fnv1a, CacheKey};
+use crate::proto::job::Data;
+
+const MAGIC: &[u8; 8] = b"JOADSNAP";
+// bump it when the layout or the keys change, the older snapshots are discarded
+const VERSION: u64 = 1;
+const SNAPSHOT_EXT: &str = "snap";
+const TMP_EXT: &str = "tmp";
+
+// A cached sample of a dataset
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entry {
+    // the index in dataset, it's stable across restarts unlike the data id
+    pub idx: u32,
+    pub value: Arc<Vec<Data>>,
+    pub pinned: bool,
+}
+
+// What is checked before restoring a snapshot
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Version<'a> {
+    pub name: &'a str,
+    // see Dataset::fingerprint
+    pub fingerprint: u64,
+    // see Dataset::transform, the samples transformed by another one are discarded
+    pub transform: u64,
+}
+
+// magic | version | fingerprint | name len | name | count, and then count entries of
+// pinned (u8) | record len (u64) | record. A record is the one of spill files, which is
+// keyed by the index and transform and carries a checksum
+fn encode_snapshot(version: Version, entries: &[Entry]) -> Vec<u8> {
+    let mut buf = Vec::new();
+    buf.extend_from_slice(MAGIC);
+    buf.extend_from_slice(&VERSION.to_le_bytes());
+    buf.extend_from_slice(&version.fingerprint.to_le_bytes());
+    buf.extend_from_slice(&(version.name.len() as u64).to_le_bytes());
+    buf.extend_from_slice(version.name.as_bytes());
+    buf.extend_from_slice(&(entries.len() as u64).to_le_bytes());
+    for entry in entries {
+        let record = encode(
+            CacheKey::new(entry.idx as u64, version.transform),
+            &entry.value,
+        );
+        buf.push(entry.pinned as u8);
+        buf.extend_from_slice(&(record.len() as u64).to_le_bytes());
+        buf.extend_from_slice(&record);
+    }
+    buf
+}
+
+// It returns None if the snapshot was taken by another version or of another dataset
+fn decode_snapshot(mut buf: &[u8], version: Version) -> io::Result<Option<Vec<Entry>>> {
+    if take(&mut buf, MAGIC.len())? != MAGIC {
+        return Err(invalid("bad magic"));
+    }
+    if take_u64(&mut buf)? != VERSION || take_u64(&mut buf)? != version.fingerprint {
+        return Ok(None);
+    }
+    let name_len = take_u64(&mut buf)? as usize;
+    if take(&mut buf, name_len)? != version.name.as_bytes() {
+        return Ok(None);
+    }
+    let count = take_u64(&mut buf)?;
+    let mut entries = Vec::new();
+    for _ in 0..count {
+        let pinned = take(&mut buf, 1)?[0] != 0;
+        let record_len = take_u64(&mut buf)? as usize;
+        let (key, value) = decode(take(&mut buf, record_len)?)?;
+        if key.transform != version.transform {
+            continue;
+        }
+        entries.push(Entry {
+            idx: key.data_id.try_into().map_err(|_| invalid("bad index"))?,
+            value: Arc::new(value),
+            pinned,
+        });
+    }
+    if !buf.is_empty() {
+        return Err(invalid("trailing bytes"));
+    }
+    Ok(Some(entries))
+}
+
+#[derive(Debug)]
+pub struct Snapshot {
+    dir: PathBuf,
+}
+
+impl Snapshot {
+    pub fn open<P: AsRef<Path>>(dir: P) -> io::Result<Self> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir)?;
+        Ok(Snapshot { dir })
+    }
+
+    // The names may not be valid file names
+    fn path(&self, name: &str, ext: &str) -> PathBuf {
+        self.dir
+            .join(format!("{:016x}", fnv1a(name.as_bytes())))
+            .with_extension(ext)
+    }
+
+    // Replace the snapshot of the dataset, a crash leaves either the old one or the new one
+    pub fn save(&self, version: Version, entries: &[Entry]) -> io::Result<()> {
+        let tmp = self.path(version.name, TMP_EXT);
+        let res = fs::write(&tmp, encode_snapshot(version, entries))
+            .and_then(|_| fs::File::open(&tmp)?.sync_all())
+            .and_then(|_| fs::rename(&tmp, self.path(version.name, SNAPSHOT_EXT)));
+        if res.is_err() {
+            let _ = fs::remove_file(&tmp);
+        }
+        res
+    }
+
+    // Take the entries of the dataset, the snapshot is removed after it's read since the
+    // cache changes from then on. The snapshot of a changed dataset is discarded
+    pub fn restore(&self, version: Version) -> io::Result<Vec<Entry>> {
+        let path = self.path(version.name, SNAPSHOT_EXT);
+        let buf = match fs::read(&path) {
+            Ok(buf) => buf,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+        fs::remove_file(&path)?;
+        match decode_snapshot(&buf, version)? {
+            Some(entries) => Ok(entries),
+            None => {
+                log::info!(
+                    "Discard the snapshot of {:?}, the dataset has changed",
+                    version.name
+                );
+                Ok(Vec::new())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::proto::job::data::DataType;
+
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("joader-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn entry(idx: u32, pinned: bool) -> Entry {
+        let value = Data {
+            bs: idx.to_be_bytes().to_vec().into(),
+            ty: DataType::Uint as i32,
+            ..Default::default()
+        };
+        Entry {
+            idx,
+            value: Arc::new(vec![value]),
+            pinned,
+        }
+    }
+
+    #[test]
+    fn test_snapshot() {
+        let dir = test_dir("snapshot");
+        let snapshot = Snapshot::open(&dir).unwrap();
+        let version = Version {
+            name: "imagenet/train",
+            fingerprint: 1,
+            transform: 2,
+        };
+        let entries = vec![entry(0, false), entry(7, true)];
+        snapshot.save(version, &entries).unwrap();
+        // another dataset
+        assert!(snapshot
+            .restore(Version {
+                name: "imagenet/val",
+                ..version
+            })
+            .unwrap()
+            .is_empty());
+        assert_eq!(snapshot.restore(version).unwrap(), entries);
+        // it's taken
+        assert!(snapshot.restore(version).unwrap().is_empty());
+
+        // the dataset has changed
+        snapshot.save(version, &entries).unwrap();
+        assert!(snapshot
+            .restore(Version {
+                fingerprint: 3,
+                ..version
+            })
+            .unwrap()
+            .is_empty());
+        assert!(snapshot.restore(version).unwrap().is_empty());
+
+        // the samples are transformed by another one
+        snapshot.save(version, &entries).unwrap();
+        assert!(snapshot
+            .restore(Version {
+                transform: 3,
+                ..version
+            })
+            .unwrap()
+            .is_empty());
+
+        // a corrupted snapshot is an error, and it's removed
+        snapshot.save(version, &entries).unwrap();
+        let path = snapshot.path(version.name, SNAPSHOT_EXT);
+        let mut buf = fs::read(&path).unwrap();
+        let last = buf.len() - 1;
+        buf[last] ^= 0xff;
+        fs::write(&path, buf).unwrap();
+        assert!(snapshot.restore(version).is_err());
+        assert!(!path.exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,5 +1,7 @@
 use super::Dataset;
 use super::DatasetRef;
+use super::items_fingerprint;
+use crate::cache::key::fnv1a;
 use crate::proto::job::Condition;
 use crate::proto::job::{Data, data::DataType};
 use crate::proto::dataset::{CreateDatasetRequest, DataItem};
@@ -52,12 +54,17 @@ impl Dataset for DummyDataset {
 
     fn read(&self, idx: u32) -> Arc<Vec<Data>> {
         let data = Data {
-            bs: idx.to_be_bytes().to_vec(),
+            bs: idx.to_be_bytes().to_vec().into(),
             ty: DataType::Uint as i32,
+            ..Default::default()
         };
         Arc::new(vec![data])
     }
 
+    fn fingerprint(&self) -> u64 {
+        items_fingerprint(fnv1a(b"dummy"), &self.items)
+    }
+
     fn len(&self) -> usize {
         self.items.len()
     }
@@ -1,6 +1,8 @@
+use super::items_fingerprint;
 use super::Dataset;
 use super::DatasetRef;
-use crate::process::decode_resize_224_opencv;
+use crate::cache::key::{fingerprint, fnv1a, fnv1a_with};
+use crate::process::decode_resize_224_reduced;
 use crate::process::msg_unpack;
 use crate::process::MsgObject;
 use crate::proto::dataset::{CreateDatasetRequest, DataItem};
@@ -11,13 +13,32 @@ use lmdb::Database;
 use lmdb::EnvironmentFlags;
 use lmdb::Transaction;
 use std::path::Path;
+use std::time::UNIX_EPOCH;
 use std::{fmt::Debug, sync::Arc};
+// The images are decoded and resized to 224x224 RGB
+const TRANSFORM: u64 = fingerprint("decode_resize_224_reduced");
+
 #[derive(Debug)]
 struct LmdbDataset {
     items: Vec<DataItem>,
     id: u64,
     env: Arc<lmdb::Environment>,
     db: Database,
+    fingerprint: u64,
+}
+
+// The file is rewritten if its size or modified time changes
+fn file_fingerprint(location: &str, items: &[DataItem]) -> u64 {
+    let meta = std::fs::metadata(location).unwrap();
+    let modified = meta
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map_or(0, |t| t.as_nanos() as u64);
+    let mut hash = fnv1a(location.as_bytes());
+    hash = fnv1a_with(hash, &meta.len().to_le_bytes());
+    hash = fnv1a_with(hash, &modified.to_le_bytes());
+    items_fingerprint(hash, items)
 }
 
 pub fn from_proto(request: CreateDatasetRequest, id: u64) -> DatasetRef {
@@ -35,6 +56,7 @@ pub fn from_proto(request: CreateDatasetRequest, id: u64) -> DatasetRef {
         .open_with_permissions(p, 0o600)
         .unwrap();
     Arc::new(LmdbDataset {
+        fingerprint: file_fingerprint(&location, &items),
         items,
         id,
         db: env.open_db(None).unwrap(),
@@ -62,7 +84,7 @@ fn preprocess<'a>(data: &'a [u8], key: &str) -> (u64, Vec<u8>) {
         MsgObject::Bin(bin) => bin,
         _ => unimplemented!(),
     };
-    (label, decode_resize_224_opencv(data))
+    (label, decode_resize_224_reduced(data))
 }
 
 impl Dataset for LmdbDataset {
@@ -87,16 +109,27 @@ impl Dataset for LmdbDataset {
         let data: &[u8] = txn.get(self.db, &key.to_string()).unwrap();
         let (label, image) = preprocess(data.as_ref(), &key);
         let label = Data {
-            bs: label.to_be_bytes().to_vec(),
+            bs: label.to_be_bytes().to_vec().into(),
             ty: DataType::Uint as i32,
+            ..Default::default()
         };
         let data = Data {
-            bs: image,
+            bs: image.into(),
             ty: DataType::Image as i32,
+            shape: vec![224, 224, 3],
+            ..Default::default()
         };
         Arc::new(vec![label, data])
     }
 
+    fn transform(&self) -> u64 {
+        TRANSFORM
+    }
+
+    fn fingerprint(&self) -> u64 {
+        self.fingerprint
+    }
+
     fn len(&self) -> usize {
         self.items.len() as usize
     }
@@ -106,8 +139,11 @@ impl Dataset for LmdbDataset {
 mod tests {
     use super::*;
     use crate::dataset::build_dataset;
+    #[cfg(feature = "bench")]
     use test::Bencher;
+    #[cfg(feature = "bench")]
     extern crate test;
+    #[cfg(feature = "bench")]
     #[bench]
     fn test_bench(b: &mut Bencher) {
         b.iter(|| test_tensor());
@@ -129,6 +165,7 @@ mod tests {
             r#type: crate::proto::dataset::create_dataset_request::Type::Lmdb as i32,
             items,
             weights: vec![0],
+            sampler: 0,
         };
         let dataset = build_dataset(proto, 0);
         dataset.read(0);
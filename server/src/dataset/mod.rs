@@ -1,14 +1,22 @@
 mod j_lmdb;
 pub use j_lmdb::*;
 mod dummy;
-use crate::proto::{dataset::{create_dataset_request::Type, CreateDatasetRequest}, job::Condition};
+use crate::proto::{dataset::{create_dataset_request::Type, CreateDatasetRequest, DataItem}, job::Condition};
 pub use dummy::*;
 use std::{fmt::Debug, sync::Arc};
 use crate::proto::job::Data;
+use crate::cache::key::fnv1a_with;
 pub trait Dataset: Sync + Send + Debug {
     fn get_id(&self) -> u64;
     fn get_indices(&self, cond: Option<Condition>) -> Vec<u32>;
     fn read(&self, _idx: u32) -> Arc<Vec<Data>> {todo!()}
+    // The fingerprint of the transform applied in read, the samples are cached by it
+    fn transform(&self) -> u64 {
+        0
+    }
+    // It changes when the samples may change, e.g. the items or the file of the dataset,
+    // and the snapshot of cache taken before is discarded
+    fn fingerprint(&self) -> u64;
     fn len(&self) -> usize;
 }
 pub type DatasetRef = Arc<dyn Dataset>;
@@ -22,6 +30,17 @@ pub fn build_dataset(request: CreateDatasetRequest, dataset_id: u64) -> DatasetR
     }
 }
 
+// The unique id of a sample across datasets
 pub fn data_id(dataset_id: u32, data_idx: u32) -> u64 {
     ((dataset_id as u64) << 32) + (data_idx as u64)
 }
+
+// Hash the keys of items in order, a key is ended with 0 and an item with 0xff
+pub fn items_fingerprint(hash: u64, items: &[DataItem]) -> u64 {
+    items.iter().fold(hash, |hash, item| {
+        let hash = item.keys.iter().fold(hash, |hash, key| {
+            fnv1a_with(fnv1a_with(hash, key.as_bytes()), &[0])
+        });
+        fnv1a_with(hash, &[0xff])
+    })
+}
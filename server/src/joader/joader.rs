@@ -1,61 +1,214 @@
+use super::prewarm::Prewarmer;
+use super::read_pool::ReadPool;
 use crate::cache::cache::Cache;
-use crate::dataset::DatasetRef;
+use crate::cache::disk::DiskCache;
+use crate::cache::key::{fnv1a_with, CacheKey};
+use crate::dataset::{data_id, DatasetRef};
 use crate::job::Job;
 use crate::proto::job::Condition;
-use crate::sampler::isa_sampler_tree::SamplerTree;
+use crate::proto::job::Data;
+use crate::sampler::isa_sampler_tree::{Checkpoint, SamplerTree, Sampling};
+use crate::sampler::{Sampler, Skew};
 use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use tokio::sync::Mutex;
 #[derive(Debug)]
 pub struct Joader {
     dataset: DatasetRef,
-    sampler_tree: Arc<Mutex<SamplerTree>>,
+    sampler: Arc<Mutex<Box<dyn Sampler>>>,
     // map loader id to loader
     job_table: HashMap<u64, Arc<Job>>,
-    ref_table: HashMap<u32, usize>,
+    // map data index to the number of jobs which will read it
+    ref_table: RefTable,
     size: usize,
+    // how many values of each job are decided ahead, 0 disables it
+    lookahead: usize,
+    rounds: usize,
+    // the jobs which have epochs left after this one
+    repeating: HashSet<u64>,
+    // the jobs sampled with replacement, they never end and are not counted in size
+    endless: HashSet<u64>,
+    // the condition and sampling of each job, a job is inserted again by them when it's reset
+    jobs: HashMap<u64, (Option<Condition>, Sampling)>,
+    // some samples are prewarmed or pinned, so the cache favors them
+    warmed: Arc<AtomicBool>,
 }
 
-async fn read(
+pub(crate) type RefTable = Arc<std::sync::Mutex<HashMap<u32, usize>>>;
+
+// Dataset ids restart in every run, so the fingerprint keeps the samples spilled by another
+// dataset from being read back
+pub(crate) fn cache_key(dataset: &DatasetRef, idx: u32) -> CacheKey {
+    let transform = fnv1a_with(dataset.transform(), &dataset.fingerprint().to_le_bytes());
+    CacheKey::new(data_id(dataset.get_id() as u32, idx), transform)
+}
+
+// The spilled value saves reading and decoding
+pub(crate) fn read_through(
+    idx: u32,
+    disk: Option<Arc<DiskCache>>,
+    dataset: &DatasetRef,
+) -> Arc<Vec<Data>> {
+    match disk.and_then(|disk| disk.get(cache_key(dataset, idx))) {
+        Some(data) => Arc::new(data),
+        None => dataset.read(idx),
+    }
+}
+
+// It runs in the read pool, and the data is pushed to jobs in the async runtime
+fn read(
     idx: u32,
-    ref_cnt: usize,
+    ref_table: RefTable,
     cache: Arc<Mutex<Cache>>,
+    disk: Option<Arc<DiskCache>>,
     dataset: DatasetRef,
     job_set: Vec<Arc<Job>>,
+    pool: ReadPool,
 ) {
-    let data = dataset.read(idx);
-    let key = dataset.get_id().to_string() + &idx.to_string();
-    let mut cache_lock = cache.lock().await;
-    cache_lock.set(&key, data.clone(), ref_cnt);
-    for job in job_set {
-        job.push(data.clone()).await;
-    }
+    let key = cache_key(&dataset, idx);
+    let data = read_through(idx, disk, &dataset);
+    pool.spawn(async move {
+        let mut cache_lock = cache.lock().await;
+        // jobs may be deleted during reading, so the references are counted under the cache lock
+        let ref_cnt = ref_table.lock().unwrap()[&idx];
+        cache_lock.set(key, data.clone(), ref_cnt);
+        drop(cache_lock);
+        for job in job_set {
+            job.push(idx, data.clone()).await;
+        }
+    });
 }
 
 impl Joader {
-    fn get_ref_cnt(&mut self, idx: u32, count: usize) -> usize {
-        *self.ref_table.get_mut(&idx).unwrap() -= count;
+    // Serve the data from cache if it's there, otherwise read it in the read pool
+    async fn load(
+        &self,
+        idx: u32,
+        cache: Arc<Mutex<Cache>>,
+        job_set: Vec<Arc<Job>>,
+        pool: &ReadPool,
+    ) {
+        let key = cache_key(&self.dataset, idx);
+        let (cached, disk) = {
+            let mut cache_lock = cache.lock().await;
+            let data = cache_lock.get(key).cloned();
+            if let Some(data) = &data {
+                let ref_cnt = self.ref_table.lock().unwrap()[&idx];
+                cache_lock.set(key, data.clone(), ref_cnt);
+            }
+            (data, cache_lock.disk())
+        };
+        match cached {
+            Some(data) => {
+                tokio::spawn(async move {
+                    for job in job_set {
+                        job.push(idx, data.clone()).await;
+                    }
+                });
+            }
+            None => {
+                let dataset = self.dataset.clone();
+                let ref_table = self.ref_table.clone();
+                let clone_pool = pool.clone();
+                pool.execute(move || {
+                    read(idx, ref_table, cache, disk, dataset, job_set, clone_pool);
+                });
+            }
+        }
+    }
+
+    // the jobs consume the data, the endless jobs keep their references since they
+    // may read it again
+    fn consume(&mut self, idx: u32, job_ids: &HashSet<u64>) {
+        let count = job_ids
+            .iter()
+            .filter(|id| !self.endless.contains(id))
+            .count();
+        *self.ref_table.lock().unwrap().get_mut(&idx).unwrap() -= count;
         self.size -= count;
-        self.ref_table[&idx]
     }
 
     pub fn new(dataset: DatasetRef) -> Joader {
+        Joader::with_sampler(dataset, Box::new(SamplerTree::new()))
+    }
+
+    pub fn with_sampler(dataset: DatasetRef, sampler: Box<dyn Sampler>) -> Joader {
         let mut ref_table = HashMap::new();
         for i in dataset.get_indices(None) {
             ref_table.insert(i, 0);
         }
-        let sampler_tree = Arc::new(Mutex::new(SamplerTree::new()));
+        let sampler = Arc::new(Mutex::new(sampler));
         let joader = Joader {
             dataset,
-            sampler_tree: sampler_tree.clone(),
+            sampler: sampler.clone(),
             job_table: HashMap::new(),
-            ref_table,
+            ref_table: Arc::new(std::sync::Mutex::new(ref_table)),
             size: 0,
+            lookahead: 0,
+            rounds: 0,
+            repeating: HashSet::new(),
+            endless: HashSet::new(),
+            jobs: HashMap::new(),
+            warmed: Arc::new(AtomicBool::new(false)),
         };
         joader
     }
 
-    pub async fn atomic_next(&mut self, cache: Arc<Mutex<Cache>>) {
+    pub fn set_lookahead(&mut self, lookahead: usize) {
+        self.lookahead = lookahead;
+    }
+
+    // Tell the cache when the decided values will be used again
+    async fn push_lookahead(&mut self, cache: Arc<Mutex<Cache>>) {
+        if self.lookahead == 0 {
+            return;
+        }
+        self.rounds += 1;
+        if self.rounds % (self.lookahead / 2).max(1) != 0 {
+            return;
+        }
+        let next_use = {
+            let sampler = self.sampler.lock().await;
+            sampler.lookahead()
+        };
+        let dataset = &self.dataset;
+        let next_use = next_use
+            .into_iter()
+            .map(|(idx, pos)| (cache_key(dataset, idx), pos))
+            .collect();
+        cache.lock().await.set_lookahead(next_use);
+    }
+
+    // Sample a round, the sampler may prefer the samples in cache
+    async fn sample(
+        &self,
+        mask: &HashSet<u64>,
+        cache: &Arc<Mutex<Cache>>,
+    ) -> HashMap<u32, HashSet<u64>> {
+        let mut sampler = self.sampler.lock().await;
+        let cache = cache.lock().await;
+        let resident = |idx| cache.contains(cache_key(&self.dataset, idx));
+        match self.cache_aware(&cache) {
+            true => sampler.sample(mask, Some(&resident)),
+            false => sampler.sample(mask, None),
+        }
+    }
+
+    // The cached samples are preferred only if the order of each job stays uniform, i.e.
+    // the cache holds what the jobs ahead have read and the others will read, see Residency.
+    // A prewarmed or pinned sample is cached on its own, and it turns the preference off
+    // for good
+    pub fn cache_aware(&self, cache: &Cache) -> bool {
+        let mut conditions = self.jobs.values().map(|(condition, _)| condition);
+        let same = match conditions.next() {
+            Some(first) => conditions.all(|condition| condition == first),
+            None => true,
+        };
+        same && cache.releases_on_zero() && !self.warmed.load(Ordering::Relaxed)
+    }
+
+    pub async fn atomic_next(&mut self, cache: Arc<Mutex<Cache>>, pool: &ReadPool) {
         // shadown the job
         let mask = HashSet::new();
         let mut can_push = true;
@@ -66,49 +219,38 @@ impl Joader {
         if !can_push {
             return;
         }
-        for (_, job) in self.job_table.iter() {
-            // if all job read in the same order, then we stop it when a buffer is full
-            job.add_pending();
-        }
-        let sample_res = {
-            let mut sampler_tree_lock = self.sampler_tree.lock().await;
-            sampler_tree_lock.sample(&mask)
-        };
+        self.next_epochs().await;
+        let sample_res = self.sample(&mask, &cache).await;
         log::debug!(
             "sampling result (data_set, job_set){:?} with mask {:?}",
             sample_res,
             mask
         );
         for (data_idx, job_id_set) in sample_res {
-            let ref_cnt = self.get_ref_cnt(data_idx, job_id_set.len());
-            let dataset = self.dataset.clone();
-            let clone_cache = cache.clone();
+            self.consume(data_idx, &job_id_set);
             let mut job_set = Vec::new();
             for job_id in job_id_set {
-                job_set.push(self.job_table[&job_id].clone());
+                let job = self.job_table[&job_id].clone();
+                // it's pushed after loading
+                job.add_pending();
+                job_set.push(job);
             }
-            tokio::spawn(async move {
-                read(data_idx, ref_cnt, clone_cache, dataset, job_set).await;
-            });
+            self.load(data_idx, cache.clone(), job_set, pool).await;
         }
+        self.push_lookahead(cache).await;
     }
 
-    pub async fn next(&mut self, cache: Arc<Mutex<Cache>>) {
+    pub async fn next(&mut self, cache: Arc<Mutex<Cache>>, pool: &ReadPool) {
+        self.next_epochs().await;
         // shadown the job
         let mut mask = HashSet::new();
         for (id, job) in self.job_table.iter() {
             // if all job read in the same order, then we stop it when a buffer is full
-            if job.can_push() {
-                // return;
-                job.add_pending();
-            } else {
+            if !job.can_push() {
                 mask.insert(*id);
             }
         }
-        let sample_res = {
-            let mut sampler_tree_lock = self.sampler_tree.lock().await;
-            sampler_tree_lock.sample_with_buffer(&mask)
-        };
+        let sample_res = self.sample(&mask, &cache).await;
         log::debug!(
             "sampling result (data_set, job_set){:?} with mask {:?}",
             sample_res,
@@ -118,55 +260,205 @@ impl Joader {
             tokio::time::sleep(std::time::Duration::from_millis(10)).await;
         }
         for (data_idx, job_id_set) in sample_res {
-            let ref_cnt = self.get_ref_cnt(data_idx, job_id_set.len());
-            let dataset = self.dataset.clone();
-            let clone_cache = cache.clone();
+            self.consume(data_idx, &job_id_set);
             let mut job_set = Vec::new();
             for job_id in job_id_set {
-                job_set.push(self.job_table[&job_id].clone());
+                let job = self.job_table[&job_id].clone();
+                // it's pushed after loading
+                job.add_pending();
+                job_set.push(job);
+            }
+            self.load(data_idx, cache.clone(), job_set, pool).await;
+        }
+        self.push_lookahead(cache).await;
+    }
+
+    // A job goes to the next epoch after all samples of this epoch are pushed, so the
+    // samples of two epochs are never mixed in its channel
+    async fn next_epochs(&mut self) {
+        if self.repeating.is_empty() {
+            return;
+        }
+        let mut sampler = self.sampler.lock().await;
+        for id in self.repeating.clone() {
+            if self.job_table[&id].pending() != 0 {
+                continue;
+            }
+            if let Some(indices) = sampler.next_epoch(id) {
+                log::debug!("Job {} starts a new epoch", id);
+                let mut ref_table = self.ref_table.lock().unwrap();
+                for idx in indices.iter() {
+                    *ref_table.get_mut(idx).unwrap() += 1;
+                }
+                self.size += indices.len();
+            }
+            if !sampler.has_next_epoch(id) {
+                self.repeating.remove(&id);
             }
-            tokio::spawn(async move {
-                read(data_idx, ref_cnt, clone_cache, dataset, job_set).await;
-            });
         }
     }
 
-    pub async fn del_job(&mut self, id: u64) {
+    pub async fn del_job(&mut self, id: u64, cache: Arc<Mutex<Cache>>) {
         log::debug!("Del job {}", id);
-        let mut sampler_tree = self.sampler_tree.lock().await;
-        let valuse = sampler_tree.get_job_values(id);
-        sampler_tree.delete(id);
-        // Todo(xj): clear cache
-        for v in valuse.iter() {
-            self.size -= 1;
-            *self.ref_table.get_mut(v).unwrap() -= 1;
+        let mut sampler = self.sampler.lock().await;
+        let valuse = sampler.get_job_values(id);
+        sampler.delete(id);
+        // the job will not read them, so drop its references in cache
+        let mut cache = cache.lock().await;
+        {
+            let mut ref_table = self.ref_table.lock().unwrap();
+            let endless = self.endless.contains(&id);
+            for v in valuse.iter() {
+                if !endless {
+                    self.size -= 1;
+                }
+                *ref_table.get_mut(v).unwrap() -= 1;
+                cache.release(cache_key(&self.dataset, *v), 1);
+            }
         }
         self.job_table.remove(&id);
+        self.repeating.remove(&id);
+        self.endless.remove(&id);
+        self.jobs.remove(&id);
+    }
+
+    // Restart a job from its first epoch with a new channel. The samples decided for the
+    // old one are released, and the ones being pushed go to the old channel
+    pub async fn reset_job(&mut self, job: Arc<Job>, cache: Arc<Mutex<Cache>>) -> Option<usize> {
+        let (condition, sampling) = self.jobs.get(&job.get_id())?.clone();
+        self.del_job(job.get_id(), cache).await;
+        Some(self.add_job_with(job, condition, sampling).await)
     }
 
     pub async fn add_job(&mut self, job: Arc<Job>, condition: Option<Condition>) -> usize {
+        self.add_job_with(job, condition, Sampling::default()).await
+    }
+
+    // It returns the length of an epoch
+    pub async fn add_job_with(
+        &mut self,
+        job: Arc<Job>,
+        condition: Option<Condition>,
+        sampling: Sampling,
+    ) -> usize {
+        self.add_job_from(job, condition, sampling, None).await
+    }
+
+    // Add a job which resumes from the checkpoint, see Sampler::insert. The values
+    // of the checkpoint must be selected by the condition
+    pub async fn add_job_from(
+        &mut self,
+        job: Arc<Job>,
+        condition: Option<Condition>,
+        sampling: Sampling,
+        checkpoint: Option<Checkpoint>,
+    ) -> usize {
+        self.jobs.insert(job.get_id(), (condition.clone(), sampling));
         let indices  = self.dataset.get_indices(condition);
         log::debug!("Add a loader {} at {}: {:?}", job.get_id(), self.dataset.get_id(), indices);
-        let len = indices.len();
-        self.sampler_tree
-            .lock()
-            .await
-            .insert(indices, job.get_id());
+        let epoch_len = sampling.epoch_len(indices.len());
+        let values = {
+            let mut sampler = self.sampler.lock().await;
+            sampler.set_lookahead(self.lookahead);
+            sampler.insert(indices, job.get_id(), sampling, checkpoint);
+            if sampler.has_next_epoch(job.get_id()) {
+                self.repeating.insert(job.get_id());
+            }
+            // a rank reads a part of them, maybe padded
+            sampler.get_job_values(job.get_id())
+        };
+        // only the selected data will be read by the job
+        {
+            let mut ref_table = self.ref_table.lock().unwrap();
+            for idx in values.iter() {
+                *ref_table.get_mut(idx).unwrap() += 1;
+            }
+        }
+        match sampling.replacement {
+            true if !values.is_empty() => {
+                self.endless.insert(job.get_id());
+            }
+            _ => self.size += values.len(),
+        }
         let job_id = job.get_id();
         self.job_table.insert(job_id, job);
-        for (_, cnt) in self.ref_table.iter_mut() {
-            *cnt += 1;
-            self.size += 1;
+        epoch_len
+    }
+
+    // Whether the sampler of the dataset can sample a job so
+    pub async fn supports(&self, sampling: &Sampling) -> bool {
+        self.sampler.lock().await.supports(sampling)
+    }
+
+    pub async fn skew(&self) -> Skew {
+        self.sampler.lock().await.skew()
+    }
+
+    pub fn sampling(&self, id: u64) -> Option<Sampling> {
+        self.jobs.get(&id).map(|(_, sampling)| *sampling)
+    }
+
+    // The values left in the epoch of the job, after the client has read the seen ones
+    pub fn checkpoint(&self, id: u64, epoch: usize, seen: &[u32]) -> Option<Checkpoint> {
+        let (condition, sampling) = self.jobs.get(&id)?.clone();
+        let indices = self.dataset.get_indices(condition);
+        let mut values = sampling.epoch_values(indices, epoch);
+        // the part of a rank may be padded with the same value twice
+        let mut seen_count = HashMap::<u32, usize>::new();
+        for idx in seen {
+            *seen_count.entry(*idx).or_default() += 1;
         }
-        len
+        values.retain(|idx| match seen_count.get_mut(idx) {
+            Some(count) if *count > 0 => {
+                *count -= 1;
+                false
+            }
+            _ => true,
+        });
+        Some(Checkpoint { epoch, values })
+    }
+
+    // The values are selected by the condition, so a checkpoint of them can be resumed
+    pub fn selects(&self, condition: Option<Condition>, values: &[u32]) -> bool {
+        let indices = self
+            .dataset
+            .get_indices(condition)
+            .into_iter()
+            .collect::<HashSet<_>>();
+        values.iter().all(|idx| indices.contains(idx))
+    }
+
+    // See Dataset::fingerprint
+    pub fn fingerprint(&self) -> u64 {
+        self.dataset.fingerprint()
     }
 
     pub fn get_id(&self) -> u64 {
         self.dataset.get_id()
     }
 
+    // The keys of the samples selected by condition
+    pub fn cache_keys(&self, condition: Option<Condition>) -> Vec<CacheKey> {
+        self.dataset
+            .get_indices(condition)
+            .into_iter()
+            .map(|idx| cache_key(&self.dataset, idx))
+            .collect()
+    }
+
+    pub fn prewarmer(&self, cache: Arc<Mutex<Cache>>, pool: ReadPool) -> Prewarmer {
+        Prewarmer::new(
+            self.dataset.clone(),
+            self.ref_table.clone(),
+            cache,
+            pool,
+            self.warmed.clone(),
+        )
+    }
+
+    // The jobs in their last epoch have read all samples
     pub fn is_empty(&self) -> bool {
-        self.size == 0
+        self.size == 0 && self.repeating.is_empty() && self.endless.is_empty()
     }
 
     pub fn len(&self) -> usize {
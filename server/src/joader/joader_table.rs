@@ -1,40 +1,141 @@
 use std::{collections::HashMap, sync::Arc};
 // casue aysnc trait has not been supported, we use thread pool
 use super::joader::Joader;
+use super::prewarm::{Prewarmer, Progress};
+use super::read_pool::ReadPool;
 use crate::cache::cache::Cache;
+use crate::cache::snapshot::Snapshot;
+use crate::job::Job;
+use crate::proto::job::Condition;
+use crate::sampler::Skew;
 use tokio::sync::Mutex;
+use tokio::task::JoinHandle;
 
 #[derive(Debug)]
 pub struct JoaderTable {
     // Joader is hash by the name of dataset
     joader_table: HashMap<u64, Joader>,
-    cache: Arc<Mutex<Cache>>
+    cache: Arc<Mutex<Cache>>,
+    pool: ReadPool,
+    lookahead: usize,
+    // the cached samples are saved in it on shutdown
+    snapshot: Option<Arc<Snapshot>>,
 }
 
 impl JoaderTable {
-    pub fn new(cache: Arc<Mutex<Cache>>) -> JoaderTable {
+    pub fn new(cache: Arc<Mutex<Cache>>, pool: ReadPool) -> JoaderTable {
         JoaderTable {
             joader_table: HashMap::new(),
-            cache
+            cache,
+            pool,
+            lookahead: 0,
+            snapshot: None,
         }
     }
 
-    pub fn add_joader(&mut self, joader: Joader) {
+    pub fn set_lookahead(&mut self, lookahead: usize) {
+        self.lookahead = lookahead;
+        for joader in self.joader_table.values_mut() {
+            joader.set_lookahead(lookahead);
+        }
+    }
+
+    pub fn set_snapshot(&mut self, snapshot: Snapshot) {
+        self.snapshot = Some(Arc::new(snapshot));
+    }
+
+    // Restore the cached samples of the dataset saved before restart in the background
+    pub fn restore(&self, name: &str, dataset_id: u64) -> Option<JoinHandle<Progress>> {
+        let snapshot = self.snapshot.clone()?;
+        let prewarmer = self.prewarmer(dataset_id)?;
+        let name = name.to_string();
+        Some(tokio::spawn(async move {
+            let entries = {
+                let (prewarmer, name) = (prewarmer.clone(), name.clone());
+                tokio::task::spawn_blocking(move || snapshot.restore(prewarmer.version(&name)))
+                    .await
+                    .unwrap()
+            };
+            let progress = match entries {
+                Ok(entries) => prewarmer.restore(entries).await,
+                Err(e) => {
+                    log::warn!("Fail to restore the snapshot of {:?}: {}", name, e);
+                    Progress::default()
+                }
+            };
+            if progress != Progress::default() {
+                log::info!("Restore {:?}: {:?}", name, progress);
+            }
+            progress
+        }))
+    }
+
+    // Save the cached samples of the datasets, which are given by names
+    pub async fn save_snapshot(&self, names: &HashMap<String, u64>) {
+        let snapshot = match &self.snapshot {
+            Some(snapshot) => snapshot,
+            None => return,
+        };
+        for (name, id) in names {
+            let prewarmer = match self.prewarmer(*id) {
+                Some(prewarmer) => prewarmer,
+                None => continue,
+            };
+            let entries = prewarmer.cached().await;
+            if entries.is_empty() {
+                continue;
+            }
+            match snapshot.save(prewarmer.version(name), &entries) {
+                Ok(()) => log::info!("Save {} samples of {:?} in snapshot", entries.len(), name),
+                Err(e) => log::warn!("Fail to save the snapshot of {:?}: {}", name, e),
+            }
+        }
+    }
+
+    pub fn add_joader(&mut self, mut joader: Joader) {
+        joader.set_lookahead(self.lookahead);
         log::debug!("Add Joader {:?}", joader.get_id());
         let id = joader.get_id();
         self.joader_table.insert(id, joader);
     }
 
-    pub fn del_joader(&mut self, id: u64) {
+    pub async fn del_joader(&mut self, id: u64) {
         log::debug!("Del joader {:?}", id);
+        // the pinned samples would take the pinned budget forever
+        self.unpin(id, None).await;
         self.joader_table.remove(&id);
     }
 
+    // Unpin the samples selected by condition, it returns the number of them
+    pub async fn unpin(&mut self, id: u64, condition: Option<Condition>) -> usize {
+        let keys = match self.joader_table.get(&id) {
+            Some(joader) => joader.cache_keys(condition),
+            None => return 0,
+        };
+        let mut cache = self.cache.lock().await;
+        keys.into_iter().filter(|key| cache.unpin(*key)).count()
+    }
+
     pub fn get_mut(&mut self, id: u64) -> &mut Joader {
         log::debug!("Get joader {:?}", id);
         self.joader_table.get_mut(&id).unwrap()
     }
 
+    pub fn prewarmer(&self, dataset_id: u64) -> Option<Prewarmer> {
+        let joader = self.joader_table.get(&dataset_id)?;
+        Some(joader.prewarmer(self.cache.clone(), self.pool.clone()))
+    }
+
+    pub async fn del_job(&mut self, dataset_id: u64, job_id: u64) {
+        let joader = self.joader_table.get_mut(&dataset_id).unwrap();
+        joader.del_job(job_id, self.cache.clone()).await;
+    }
+
+    pub async fn reset_job(&mut self, dataset_id: u64, job: Arc<Job>) -> Option<usize> {
+        let joader = self.joader_table.get_mut(&dataset_id)?;
+        joader.reset_job(job, self.cache.clone()).await
+    }
+
     pub fn is_empty(&self) -> bool {
         let mut empty = true;
         for (_, joader) in self.joader_table.iter() {
@@ -43,18 +144,37 @@ impl JoaderTable {
         empty
     }
 
+    // Backpressure: no more read is queued when the read pool is full,
+    // the caller should wait for the pool to drain
+    pub fn is_busy(&self) -> bool {
+        self.pool.is_full()
+    }
+
     pub async fn next(&mut self) -> i32 {
         let mut cnt = 0;
         for (_, joader) in self.joader_table.iter_mut() {
+            if self.pool.is_full() {
+                log::debug!("Read pool is full with {} queued", self.pool.queued_count());
+                break;
+            }
             if !joader.is_empty() {
-                joader.next( self.cache.clone()).await;
-                // joader.atomic_next(self.cache.clone()).await;
+                joader.next(self.cache.clone(), &self.pool).await;
+                // joader.atomic_next(self.cache.clone(), &self.pool).await;
                 cnt += 1;
             }
         }
         cnt
     }
 
+    // The skew between the jobs of each dataset
+    pub async fn skews(&self) -> Vec<(u64, Skew)> {
+        let mut res = Vec::new();
+        for (id, joader) in self.joader_table.iter() {
+            res.push((*id, joader.skew().await));
+        }
+        res
+    }
+
     pub fn contains_dataset(&self, id: u64) -> bool {
         self.joader_table.contains_key(&id)
     }
@@ -1,4 +1,6 @@
 pub mod joader;
 pub mod joader_table;
+pub mod prewarm;
+pub mod read_pool;
 #[cfg(test)]
 mod tests;
\ No newline at end of file
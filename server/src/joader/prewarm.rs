@@ -0,0 +1,187 @@
+use super::joader::{cache_key, read_through, RefTable};
+use super::read_pool::ReadPool;
+use crate::cache::cache::Cache;
+use crate::cache::snapshot::{Entry, Version};
+use crate::dataset::DatasetRef;
+use crate::proto::job::{Condition, Data};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{oneshot, Mutex};
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Progress {
+    // the samples loaded into cache
+    pub loaded: usize,
+    // the samples which were in cache already
+    pub cached: usize,
+    // the cache budget is used up
+    pub full: bool,
+}
+
+// Load samples into cache before jobs read them, or pin them in cache. It's at a lower
+// priority than the reads of jobs: a batch of samples is queued only when the read pool
+// is idle, so a job waits for at most one batch
+#[derive(Debug, Clone)]
+pub struct Prewarmer {
+    dataset: DatasetRef,
+    ref_table: RefTable,
+    cache: Arc<Mutex<Cache>>,
+    pool: ReadPool,
+    // set once a sample is loaded or pinned, see Joader::cache_aware
+    warmed: Arc<AtomicBool>,
+}
+
+impl Prewarmer {
+    pub fn new(
+        dataset: DatasetRef,
+        ref_table: RefTable,
+        cache: Arc<Mutex<Cache>>,
+        pool: ReadPool,
+        warmed: Arc<AtomicBool>,
+    ) -> Self {
+        Prewarmer {
+            dataset,
+            ref_table,
+            cache,
+            pool,
+            warmed,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.dataset.len()
+    }
+
+    pub fn indices(&self, condition: Option<Condition>) -> Vec<u32> {
+        self.dataset.get_indices(condition)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    // The number of samples loaded at a time
+    pub fn batch_size(&self) -> usize {
+        self.pool.max_count()
+    }
+
+    pub async fn load(&self, indices: &[u32]) -> Progress {
+        self.load_with(indices, false).await
+    }
+
+    // The samples are kept in the pinned budget until they are unpinned
+    pub async fn pin(&self, indices: &[u32]) -> Progress {
+        self.load_with(indices, true).await
+    }
+
+    pub fn version<'a>(&self, name: &'a str) -> Version<'a> {
+        Version {
+            name,
+            fingerprint: self.dataset.fingerprint(),
+            transform: self.dataset.transform(),
+        }
+    }
+
+    // The samples of the dataset in cache, they are saved in a snapshot
+    pub async fn cached(&self) -> Vec<Entry> {
+        let mut cache = self.cache.lock().await;
+        self.indices(None)
+            .into_iter()
+            .filter_map(|idx| {
+                let key = cache_key(&self.dataset, idx);
+                let value = cache.peek(key)?;
+                Some(Entry {
+                    idx,
+                    value,
+                    pinned: cache.is_pinned(key),
+                })
+            })
+            .collect()
+    }
+
+    // Cache the samples of a snapshot like the loaded ones, the pinned samples are pinned again
+    pub async fn restore(&self, entries: Vec<Entry>) -> Progress {
+        let mut progress = Progress::default();
+        for entry in entries {
+            if entry.idx as usize >= self.len() {
+                continue;
+            }
+            // jobs are not blocked by a large snapshot
+            let mut cache = self.cache.lock().await;
+            if cache.contains(cache_key(&self.dataset, entry.idx)) {
+                progress.cached += 1;
+            } else if self.store(&mut cache, entry.idx, entry.value, entry.pinned) {
+                progress.loaded += 1;
+            } else {
+                progress.full = true;
+            }
+        }
+        progress
+    }
+
+    // The references are counted under the cache lock, since jobs may be added during reading
+    fn store(&self, cache: &mut Cache, idx: u32, data: Arc<Vec<Data>>, pin: bool) -> bool {
+        let key = cache_key(&self.dataset, idx);
+        let ref_cnt = self.ref_table.lock().unwrap()[&idx];
+        self.warmed.store(true, Ordering::Relaxed);
+        if pin {
+            cache.pin(key, data, ref_cnt)
+        } else {
+            cache.prewarm(key, data, ref_cnt)
+        }
+    }
+
+    async fn load_with(&self, indices: &[u32], pin: bool) -> Progress {
+        let mut progress = Progress::default();
+        let mut pending = Vec::new();
+        let disk = {
+            let mut cache = self.cache.lock().await;
+            for idx in indices {
+                let key = cache_key(&self.dataset, *idx);
+                // the cached samples are pinned in place
+                match cache.peek(key) {
+                    Some(_) if !pin || cache.is_pinned(key) => progress.cached += 1,
+                    Some(data) => {
+                        if !self.store(&mut cache, *idx, data, pin) {
+                            progress.full = true;
+                            return progress;
+                        }
+                        progress.cached += 1;
+                    }
+                    None => pending.push(*idx),
+                }
+            }
+            cache.disk()
+        };
+        while self.pool.queued_count() > 0 {
+            tokio::time::sleep(Duration::from_millis(1)).await;
+        }
+        let mut reads = Vec::new();
+        for idx in pending {
+            let (tx, rx) = oneshot::channel();
+            let (dataset, disk) = (self.dataset.clone(), disk.clone());
+            self.pool.execute(move || {
+                let _ = tx.send(read_through(idx, disk, &dataset));
+            });
+            reads.push((idx, rx));
+        }
+        for (idx, rx) in reads {
+            // the read panics
+            let data = match rx.await {
+                Ok(data) => data,
+                Err(_) => continue,
+            };
+            if progress.full {
+                continue;
+            }
+            let mut cache = self.cache.lock().await;
+            if self.store(&mut cache, idx, data, pin) {
+                progress.loaded += 1;
+            } else {
+                progress.full = true;
+            }
+        }
+        progress
+    }
+}
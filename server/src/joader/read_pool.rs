@@ -0,0 +1,96 @@
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use threadpool::ThreadPool;
+use tokio::runtime::Handle;
+use tokio::task::JoinHandle;
+
+// Reading and decoding are CPU-heavy synchronous work, so they run in a dedicated
+// pool and only hand the result back to the async runtime
+#[derive(Debug, Clone)]
+pub struct ReadPool {
+    // ThreadPool is not Sync, but the pool is shared by the async tasks
+    pool: Arc<Mutex<ThreadPool>>,
+    handle: Handle,
+    // sampling is throttled when the number of queued reads reaches it
+    max_queued: usize,
+}
+
+impl ReadPool {
+    // It must be called in the context of a tokio runtime
+    pub fn new(num_threads: usize, max_queued: usize) -> Self {
+        assert!(num_threads > 0 && max_queued > 0);
+        ReadPool {
+            pool: Arc::new(Mutex::new(ThreadPool::with_name(
+                "joader-read".to_string(),
+                num_threads,
+            ))),
+            handle: Handle::current(),
+            max_queued,
+        }
+    }
+
+    pub fn execute<F>(&self, job: F)
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        self.pool.lock().unwrap().execute(job);
+    }
+
+    pub fn spawn<F>(&self, future: F) -> JoinHandle<F::Output>
+    where
+        F: Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        self.handle.spawn(future)
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.queued_count() >= self.max_queued
+    }
+
+    pub fn queued_count(&self) -> usize {
+        self.pool.lock().unwrap().queued_count()
+    }
+
+    pub fn max_count(&self) -> usize {
+        self.pool.lock().unwrap().max_count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Barrier;
+
+    #[tokio::test]
+    async fn test_read_pool() {
+        let pool = ReadPool::new(1, 2);
+        let barrier = Arc::new(Barrier::new(2));
+        let b = barrier.clone();
+        // block the only worker
+        pool.execute(move || {
+            b.wait();
+        });
+        let cnt = Arc::new(AtomicUsize::new(0));
+        for _ in 0..2 {
+            let cnt = cnt.clone();
+            pool.execute(move || {
+                cnt.fetch_add(1, Ordering::SeqCst);
+            });
+        }
+        assert!(pool.is_full());
+        barrier.wait();
+        let p = pool.clone();
+        let res = tokio::task::spawn_blocking(move || {
+            let pool = p.pool.lock().unwrap().clone();
+            pool.join();
+            p.is_full()
+        })
+        .await
+        .unwrap();
+        assert!(!res);
+        assert_eq!(cnt.load(Ordering::SeqCst), 2);
+        assert_eq!(pool.spawn(async { 1 }).await.unwrap(), 1);
+    }
+}
@@ -1,3 +1,4 @@
+use std::convert::TryInto;
 use std::sync::Arc;
 use std::time::{Duration, SystemTime};
 use tokio::sync::mpsc::Receiver;
@@ -5,13 +6,27 @@ use tokio::sync::Mutex;
 use tokio::time::sleep;
 
 use super::joader::*;
+use super::read_pool::ReadPool;
+use crate::cache::cache::entry_size;
+use crate::cache::disk::DiskCache;
+use crate::cache::policy::PolicyType;
+use crate::cache::snapshot::Snapshot;
 use crate::dataset::build_dataset;
 use crate::proto::dataset::{CreateDatasetRequest, DataItem};
 use crate::proto::job::{expr, Condition, Data, Expr};
-use crate::{cache::cache::Cache, dataset::new_dummy, joader::joader_table::JoaderTable, job::Job};
+use crate::sampler::isa_sampler_tree::{Checkpoint, Order, Sampling, Shard};
+use crate::{
+    cache::cache::Cache,
+    dataset::new_dummy,
+    joader::joader_table::JoaderTable,
+    job::{Job, Sample},
+};
 
 async fn write(mut jt: JoaderTable, _len: usize) {
     loop {
+        if jt.is_busy() {
+            sleep(Duration::from_millis(1)).await;
+        }
         jt.next().await;
         if jt.is_empty() {
             break;
@@ -21,7 +36,7 @@ async fn write(mut jt: JoaderTable, _len: usize) {
 
 async fn read(
     _job_id: u64,
-    mut recv: Receiver<Arc<Vec<Data>>>,
+    mut recv: Receiver<Sample>,
     len: usize,
     dur: Duration,
 ) -> Vec<Arc<Vec<Data>>> {
@@ -31,7 +46,7 @@ async fn read(
         let data = recv.recv().await;
         sleep(dur).await;
         match data {
-            Some(data) => res.push(data),
+            Some((_, data)) => res.push(data),
             None => continue,
         }
         if res.len() == len {
@@ -48,7 +63,7 @@ async fn read(
 async fn test_joader_dummy() {
     // log4rs::init_file("log4rs.yaml", Default::default()).unwrap();
     let cache = Arc::new(Mutex::new(Cache::new()));
-    let mut jt = JoaderTable::new(cache);
+    let mut jt = JoaderTable::new(cache, ReadPool::new(4, 64));
 
     let len = 4096;
     let name = "dummy".to_string();
@@ -67,7 +82,7 @@ async fn test_joader_dummy() {
 async fn test_joader_lmdb() {
     log4rs::init_file("log4rs.yaml", Default::default()).unwrap();
     let cache = Arc::new(Mutex::new(Cache::new()));
-    let mut jt = JoaderTable::new(cache);
+    let mut jt = JoaderTable::new(cache, ReadPool::new(4, 64));
 
     let len = 2048;
     let location = "/data/lmdb-imagenet/ILSVRC-train.lmdb".to_string();
@@ -83,6 +98,7 @@ async fn test_joader_lmdb() {
         r#type: crate::proto::dataset::create_dataset_request::Type::Lmdb as i32,
         items,
         weights: vec![0],
+        sampler: 0,
     };
     let dataset = build_dataset(proto, 0);
     let mut joader = Joader::new(dataset);
@@ -99,7 +115,7 @@ async fn test_joader_lmdb() {
 async fn test_joader_multi_lmdb() {
     log4rs::init_file("log4rs.yaml", Default::default()).unwrap();
     let cache = Arc::new(Mutex::new(Cache::new()));
-    let mut jt = JoaderTable::new(cache);
+    let mut jt = JoaderTable::new(cache, ReadPool::new(4, 64));
 
     let len = 2048;
     let location = "/data/lmdb-imagenet/ILSVRC-train.lmdb".to_string();
@@ -115,6 +131,7 @@ async fn test_joader_multi_lmdb() {
         r#type: crate::proto::dataset::create_dataset_request::Type::Lmdb as i32,
         items,
         weights: vec![0],
+        sampler: 0,
     };
     let dataset = build_dataset(proto, 0);
     let mut joader = Joader::new(dataset);
@@ -149,6 +166,7 @@ async fn test_filter() {
         r#type: crate::proto::dataset::create_dataset_request::Type::Lmdb as i32,
         items,
         weights: vec![0],
+        sampler: 0,
     };
     let dataset = build_dataset(proto, 0);
     let mut joader = Joader::new(dataset);
@@ -168,3 +186,495 @@ async fn test_filter() {
     let size = joader.add_job(job.clone(), Some(cond)).await;
     assert_eq!(size, 16);
 }
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_joader_cache_hit() {
+    // refcnt releases the data consumed by all jobs, lru keeps it for the later jobs
+    let cache = Arc::new(Mutex::new(Cache::with_policy(usize::MAX, PolicyType::Lru)));
+    let pool = ReadPool::new(2, 64);
+    let len = 256;
+    let mut joader = Joader::new(new_dummy(len, "dummy".to_string()));
+    // the second job reads the data cached by the first one
+    for id in 0..2 {
+        let (job, recv) = Job::new(id);
+        joader.add_job(job, None).await;
+        while !joader.is_empty() {
+            joader.next(cache.clone(), &pool).await;
+        }
+        read(id, recv, len, Duration::from_millis(0)).await;
+    }
+    let cache = cache.lock().await;
+    assert_eq!(cache.miss_count(), len);
+    assert_eq!(cache.hit_count(), len);
+}
+
+#[test]
+fn test_joader_read_through() {
+    // both datasets have the id 0, like two datasets created in different runs
+    let (old, new) = (
+        new_dummy(4, "dummy".to_string()),
+        new_dummy(5, "dummy".to_string()),
+    );
+    let dir = std::env::temp_dir().join(format!("joader-read-through-{}", std::process::id()));
+    let disk = Arc::new(DiskCache::open(&dir, 1 << 20).unwrap());
+    let spilled = Arc::new(vec![Data {
+        bs: b"spilled".to_vec().into(),
+        ..Default::default()
+    }]);
+    disk.put(cache_key(&old, 1), spilled.clone());
+    disk.flush();
+    assert_eq!(read_through(1, Some(disk.clone()), &old), spilled);
+    assert_eq!(read_through(1, Some(disk.clone()), &new), new.read(1));
+    drop(disk);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_joader_cache_aware() {
+    let cache = Arc::new(Mutex::new(Cache::with_capacity(usize::MAX)));
+    let mut joader = Joader::new(new_dummy(16, "dummy".to_string()));
+    for id in 0..2 {
+        joader.add_job(Job::new(id).0, None).await;
+    }
+    assert!(joader.cache_aware(&*cache.lock().await));
+    // the samples kept by lru may be read by no job
+    assert!(!joader.cache_aware(&Cache::with_policy(usize::MAX, PolicyType::Lru)));
+    // the samples read by the jobs ahead may not be the ones the others read
+    joader
+        .add_job(Job::new(2).0, Some(Condition::default()))
+        .await;
+    assert!(!joader.cache_aware(&*cache.lock().await));
+    joader.del_job(2, cache.clone()).await;
+    assert!(joader.cache_aware(&*cache.lock().await));
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_joader_release() {
+    let cache = Arc::new(Mutex::new(Cache::new()));
+    let pool = ReadPool::new(2, 64);
+    let len = 256;
+    let mut joader = Joader::new(new_dummy(len, "dummy".to_string()));
+    let (job0, recv0) = Job::new(0);
+    let (job1, _recv1) = Job::new(1);
+    joader.add_job(job0, None).await;
+    joader.add_job(job1, None).await;
+    for _ in 0..len / 2 {
+        joader.next(cache.clone(), &pool).await;
+    }
+    // job 1 is deleted, so the data is released once job 0 consumes it
+    joader.del_job(1, cache.clone()).await;
+    while !joader.is_empty() {
+        joader.next(cache.clone(), &pool).await;
+    }
+    read(0, recv0, len, Duration::from_millis(0)).await;
+    let cache = cache.lock().await;
+    assert!(cache.is_empty());
+    assert_eq!(cache.size(), 0);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_joader_prewarm() {
+    let len = 256;
+    let dataset = new_dummy(len, "dummy".to_string());
+    // the cache holds half of the dataset
+    let capacity = len / 2 * entry_size(&dataset.read(0));
+    let cache = Arc::new(Mutex::new(Cache::with_capacity(capacity)));
+    let pool = ReadPool::new(2, 16);
+    let mut joader = Joader::new(dataset);
+    let prewarmer = joader.prewarmer(cache.clone(), pool.clone());
+    assert!(joader.cache_aware(&*cache.lock().await));
+    let indices = (0..len as u32).collect::<Vec<_>>();
+    let mut loaded = 0;
+    let mut full = false;
+    for batch in indices.chunks(prewarmer.batch_size()) {
+        let progress = prewarmer.load(batch).await;
+        loaded += progress.loaded;
+        if progress.full {
+            full = true;
+            break;
+        }
+    }
+    assert!(full);
+    assert_eq!(loaded, len / 2);
+    let progress = prewarmer.load(&indices[..len / 2]).await;
+    assert_eq!((progress.loaded, progress.cached), (0, len / 2));
+    // the prewarmed samples would be preferred
+    assert!(!joader.cache_aware(&*cache.lock().await));
+    // the job reads the prewarmed half from cache
+    let (job, recv) = Job::new(0);
+    joader.add_job(job, None).await;
+    while !joader.is_empty() {
+        joader.next(cache.clone(), &pool).await;
+    }
+    read(0, recv, len, Duration::from_millis(0)).await;
+    assert_eq!(cache.lock().await.hit_count(), len / 2);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_joader_pin() {
+    let len = 64;
+    let dataset = new_dummy(len, "dummy".to_string());
+    let mut cache = Cache::new();
+    cache.set_pin_capacity(len * entry_size(&dataset.read(0)));
+    let cache = Arc::new(Mutex::new(cache));
+    let pool = ReadPool::new(2, 16);
+    let mut jt = JoaderTable::new(cache.clone(), pool.clone());
+    jt.add_joader(Joader::new(dataset));
+    let prewarmer = jt.prewarmer(0).unwrap();
+    let progress = prewarmer.pin(&prewarmer.indices(None)).await;
+    assert_eq!(progress.loaded, len);
+    // refcnt would release the samples read by the last job, but they are pinned
+    for id in 0..2 {
+        let (job, recv) = Job::new(id);
+        jt.get_mut(0).add_job(job, None).await;
+        while !jt.get_mut(0).is_empty() {
+            jt.get_mut(0).next(cache.clone(), &pool).await;
+        }
+        read(id, recv, len, Duration::from_millis(0)).await;
+    }
+    assert_eq!(cache.lock().await.hit_count(), 2 * len);
+    assert_eq!(jt.unpin(0, None).await, len);
+    assert!(cache.lock().await.is_empty());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_joader_snapshot() {
+    let len = 64;
+    let dir = std::env::temp_dir().join(format!("joader-joader-snapshot-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let names = [("dummy".to_string(), 0)].iter().cloned().collect();
+    let table = || {
+        let mut jt = JoaderTable::new(Arc::new(Mutex::new(Cache::new())), ReadPool::new(2, 16));
+        jt.set_snapshot(Snapshot::open(&dir).unwrap());
+        jt
+    };
+    let jt = {
+        let mut jt = table();
+        jt.add_joader(Joader::new(new_dummy(len, "dummy".to_string())));
+        jt
+    };
+    let prewarmer = jt.prewarmer(0).unwrap();
+    prewarmer.load(&prewarmer.indices(None)[..len / 2]).await;
+    jt.save_snapshot(&names).await;
+
+    // restart
+    let mut jt = table();
+    jt.add_joader(Joader::new(new_dummy(len, "dummy".to_string())));
+    let progress = jt.restore("dummy", 0).unwrap().await.unwrap();
+    assert_eq!(progress.loaded, len / 2);
+    let cached = jt.prewarmer(0).unwrap().cached().await;
+    assert_eq!(cached, prewarmer.cached().await);
+    jt.save_snapshot(&names).await;
+
+    // the dataset has changed
+    let mut jt = table();
+    jt.add_joader(Joader::new(new_dummy(len + 1, "dummy".to_string())));
+    let progress = jt.restore("dummy", 0).unwrap().await.unwrap();
+    assert_eq!(progress.loaded, 0);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_joader_epochs() {
+    let len = 16;
+    let epochs = 3;
+    let cache = Arc::new(Mutex::new(Cache::new()));
+    let pool = ReadPool::new(2, 16);
+    let mut joader = Joader::new(new_dummy(len, "dummy".to_string()));
+    let (job, recv) = Job::new(0);
+    let sampling = Sampling {
+        epochs: Some(epochs),
+        ..Default::default()
+    };
+    assert_eq!(joader.add_job_with(job, None, sampling).await, len);
+    let reader = tokio::spawn(read(0, recv, epochs * len, Duration::from_millis(0)));
+    while !joader.is_empty() {
+        joader.next(cache.clone(), &pool).await;
+    }
+    // the samples of an epoch are pushed before the next epoch
+    for samples in reader.await.unwrap().chunks(len) {
+        let mut indices = samples
+            .iter()
+            .map(|s| u32::from_be_bytes(s[0].bs[..].try_into().unwrap()))
+            .collect::<Vec<_>>();
+        indices.sort_unstable();
+        assert_eq!(indices, (0..len as u32).collect::<Vec<_>>());
+    }
+    // the infinite job is never empty until it's deleted
+    let (infinite, _recv) = Job::new(1);
+    let sampling = Sampling {
+        epochs: None,
+        ..Default::default()
+    };
+    joader.add_job_with(infinite, None, sampling).await;
+    for _ in 0..epochs * len {
+        joader.next(cache.clone(), &pool).await;
+    }
+    assert!(!joader.is_empty());
+    joader.del_job(1, cache.clone()).await;
+    assert!(joader.is_empty());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_joader_reset() {
+    let len = 32;
+    let cache = Arc::new(Mutex::new(Cache::new()));
+    let pool = ReadPool::new(2, 16);
+    let mut joader = Joader::new(new_dummy(len, "dummy".to_string()));
+    let (job, _old_recv) = Job::new(0);
+    joader.add_job(job, None).await;
+    let (other, other_recv) = Job::new(1);
+    joader.add_job(other, None).await;
+    let other = tokio::spawn(read(1, other_recv, len, Duration::from_millis(0)));
+    for _ in 0..len / 2 {
+        joader.next(cache.clone(), &pool).await;
+    }
+    // it starts over with a new channel, and the other job goes on
+    let (job, recv) = Job::new(0);
+    assert_eq!(joader.reset_job(job, cache.clone()).await, Some(len));
+    let reader = tokio::spawn(read(0, recv, len, Duration::from_millis(0)));
+    while !joader.is_empty() {
+        joader.next(cache.clone(), &pool).await;
+    }
+    for samples in [reader.await.unwrap(), other.await.unwrap()] {
+        let mut indices = samples
+            .iter()
+            .map(|s| u32::from_be_bytes(s[0].bs[..].try_into().unwrap()))
+            .collect::<Vec<_>>();
+        indices.sort_unstable();
+        assert_eq!(indices, (0..len as u32).collect::<Vec<_>>());
+    }
+    // the samples decided for the old job are released
+    sleep(Duration::from_millis(100)).await;
+    assert!(cache.lock().await.is_empty());
+    let (job, _) = Job::new(2);
+    assert_eq!(joader.reset_job(job, cache.clone()).await, None);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_joader_sequential() {
+    let len = 64;
+    let cache = Arc::new(Mutex::new(Cache::new()));
+    let pool = ReadPool::new(2, 16);
+    let mut joader = Joader::new(new_dummy(len, "dummy".to_string()));
+    let mut jobs = Vec::new();
+    let mut recvs = Vec::new();
+    for (id, order) in [Order::Sequential, Order::Shuffled].iter().enumerate() {
+        let (job, recv) = Job::new(id as u64);
+        let sampling = Sampling {
+            order: *order,
+            ..Default::default()
+        };
+        joader.add_job_with(job.clone(), None, sampling).await;
+        jobs.push(job);
+        recvs.push(recv);
+    }
+    while !joader.is_empty() {
+        joader.next(cache.clone(), &pool).await;
+        // the data is cached before the next round
+        while jobs.iter().any(|job| job.pending() != 0) {
+            sleep(Duration::from_millis(1)).await;
+        }
+    }
+    let sequential = read(0, recvs.remove(0), len, Duration::from_millis(0)).await;
+    let indices = sequential
+        .iter()
+        .map(|s| u32::from_be_bytes(s[0].bs[..].try_into().unwrap()))
+        .collect::<Vec<_>>();
+    assert_eq!(indices, (0..len as u32).collect::<Vec<_>>());
+    // the shuffled job reads the data cached for the sequential job and vice versa
+    let cache = cache.lock().await;
+    assert_eq!(cache.miss_count(), len);
+    assert!(cache.is_empty());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_joader_shard() {
+    let len = 10;
+    let ranks = 3;
+    let cache = Arc::new(Mutex::new(Cache::new()));
+    let pool = ReadPool::new(2, 16);
+    let mut joader = Joader::new(new_dummy(len, "dummy".to_string()));
+    let mut jobs = Vec::new();
+    let mut recvs = Vec::new();
+    for rank in 0..ranks {
+        let (job, recv) = Job::new(rank as u64);
+        let sampling = Sampling {
+            order: Order::Seeded(3),
+            shard: Some(Shard {
+                rank,
+                ranks,
+                drop_uneven: false,
+            }),
+            ..Default::default()
+        };
+        // the epoch is padded to 12 samples
+        assert_eq!(joader.add_job_with(job.clone(), None, sampling).await, 4);
+        jobs.push(job);
+        recvs.push(recv);
+    }
+    let (job, _recv) = Job::new(ranks as u64);
+    joader.add_job(job.clone(), None).await;
+    jobs.push(job);
+    while !joader.is_empty() {
+        joader.next(cache.clone(), &pool).await;
+        while jobs.iter().any(|job| job.pending() != 0) {
+            sleep(Duration::from_millis(1)).await;
+        }
+    }
+    let mut indices = Vec::new();
+    for (rank, recv) in recvs.into_iter().enumerate() {
+        let part = read(rank as u64, recv, 4, Duration::from_millis(0)).await;
+        indices.extend(
+            part.iter()
+                .map(|s| u32::from_be_bytes(s[0].bs[..].try_into().unwrap())),
+        );
+    }
+    indices.sort_unstable();
+    indices.dedup();
+    assert_eq!(indices, (0..len as u32).collect::<Vec<_>>());
+    // the ranks share the reads with the other job
+    let cache = cache.lock().await;
+    assert_eq!(cache.miss_count(), len);
+    assert!(cache.is_empty());
+}
+
+fn indices(samples: &[Arc<Vec<Data>>]) -> Vec<u32> {
+    samples
+        .iter()
+        .map(|s| u32::from_be_bytes(s[0].bs[..].try_into().unwrap()))
+        .collect()
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_joader_checkpoint() {
+    let len = 16;
+    let cache = Arc::new(Mutex::new(Cache::new()));
+    let pool = ReadPool::new(2, 16);
+    let mut joader = Joader::new(new_dummy(len, "dummy".to_string()));
+    let sampling = Sampling {
+        epochs: Some(2),
+        ..Default::default()
+    };
+    let (job, mut recv) = Job::new(0);
+    joader.add_job_with(job.clone(), None, sampling).await;
+    for _ in 0..8 {
+        joader.next(cache.clone(), &pool).await;
+    }
+    // the client has read 5 samples when it checkpoints
+    let mut seen = Vec::new();
+    for _ in 0..5 {
+        seen.push(recv.recv().await.unwrap().0);
+    }
+    let checkpoint = joader.checkpoint(0, 0, &seen).unwrap();
+    assert_eq!(checkpoint.values.len(), len - 5);
+    assert!(checkpoint.values.iter().all(|idx| !seen.contains(idx)));
+    joader.del_job(0, cache.clone()).await;
+    assert!(joader.checkpoint(0, 0, &seen).is_none());
+
+    // the job restarts from the checkpoint
+    let (job, recv) = Job::new(1);
+    assert_eq!(
+        checkpoint,
+        Checkpoint {
+            epoch: 0,
+            values: checkpoint.values.clone()
+        }
+    );
+    assert_eq!(
+        joader
+            .add_job_from(job.clone(), None, sampling, Some(checkpoint))
+            .await,
+        len
+    );
+    while !joader.is_empty() {
+        joader.next(cache.clone(), &pool).await;
+        while job.pending() != 0 {
+            sleep(Duration::from_millis(1)).await;
+        }
+    }
+    let samples = read(1, recv, 2 * len - 5, Duration::from_millis(0)).await;
+    let mut first = indices(&samples[..len - 5]);
+    first.extend(seen);
+    first.sort_unstable();
+    assert_eq!(first, (0..len as u32).collect::<Vec<_>>());
+    let mut second = indices(&samples[len - 5..]);
+    second.sort_unstable();
+    assert_eq!(second, (0..len as u32).collect::<Vec<_>>());
+    sleep(Duration::from_millis(100)).await;
+    assert!(cache.lock().await.is_empty());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_joader_replacement() {
+    let len = 16;
+    let cache = Arc::new(Mutex::new(Cache::new()));
+    let pool = ReadPool::new(2, 16);
+    let mut joader = Joader::new(new_dummy(len, "dummy".to_string()));
+    let (job, recv) = Job::new(0);
+    joader.add_job(job, None).await;
+    let (endless, endless_recv) = Job::new(1);
+    let sampling = Sampling {
+        replacement: true,
+        ..Default::default()
+    };
+    joader.add_job_with(endless, None, sampling).await;
+    // the endless job goes on after the other one is finished
+    let reader = tokio::spawn(read(1, endless_recv, 4 * len, Duration::from_millis(0)));
+    let rounds = tokio::spawn(read(0, recv, len, Duration::from_millis(0)));
+    for _ in 0..4 * len {
+        joader.next(cache.clone(), &pool).await;
+    }
+    rounds.await.unwrap();
+    reader.await.unwrap();
+    assert!(!joader.is_empty());
+    // the data is kept in cache for the endless job
+    sleep(Duration::from_millis(100)).await;
+    assert!(!cache.lock().await.is_empty());
+    joader.del_job(1, cache.clone()).await;
+    assert!(joader.is_empty());
+    assert!(cache.lock().await.is_empty());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_joader_shared_sampler() {
+    let len = 16;
+    let epochs = 2;
+    let cache = Arc::new(Mutex::new(Cache::new()));
+    let pool = ReadPool::new(2, 16);
+    let sampler = Box::new(crate::sampler::sampler_tree::SamplerTree::new());
+    let mut joader = Joader::with_sampler(new_dummy(len, "dummy".to_string()), sampler);
+    let sequential = Sampling {
+        order: Order::Sequential,
+        ..Default::default()
+    };
+    assert!(!joader.supports(&sequential).await);
+    let sampling = Sampling {
+        epochs: Some(epochs),
+        ..Default::default()
+    };
+    let mut readers = Vec::new();
+    for id in 0..2 {
+        let (job, recv) = Job::new(id);
+        assert_eq!(joader.add_job_with(job, None, sampling).await, len);
+        readers.push(tokio::spawn(read(
+            id,
+            recv,
+            epochs * len,
+            Duration::from_millis(0),
+        )));
+    }
+    while !joader.is_empty() {
+        joader.next(cache.clone(), &pool).await;
+    }
+    // each job reads every sample once in each epoch
+    for reader in readers {
+        for samples in reader.await.unwrap().chunks(len) {
+            let mut indices = indices(samples);
+            indices.sort_unstable();
+            assert_eq!(indices, (0..len as u32).collect::<Vec<_>>());
+        }
+    }
+    sleep(Duration::from_millis(100)).await;
+    assert!(cache.lock().await.is_empty());
+}
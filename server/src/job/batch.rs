@@ -0,0 +1,385 @@
+use std::sync::Arc;
+use tokio::sync::mpsc::Receiver;
+
+use super::Sample;
+use crate::proto::job::Data;
+
+// Concatenate the i-th field of every sample into one contiguous buffer,
+// e.g. N images of [H, W, C] become one [N, H, W, C] buffer and N labels become one [N] array.
+// The length of each sample is kept in lens, so that the buffer can be split
+pub fn collate(samples: &[Arc<Vec<Data>>]) -> Result<Vec<Data>, String> {
+    let first = match samples.first() {
+        Some(first) => first,
+        None => return Ok(Vec::new()),
+    };
+    for (i, sample) in samples.iter().enumerate() {
+        if sample.len() != first.len() {
+            return Err(format!(
+                "sample {} has {} fields, but {} is expected",
+                i,
+                sample.len(),
+                first.len()
+            ));
+        }
+        for (field, data) in sample.iter().enumerate() {
+            if data.ty != first[field].ty || data.shape != first[field].shape {
+                return Err(format!(
+                    "field {} of sample {} is {:?} {:?}, but {:?} {:?} is expected",
+                    field, i, data.ty, data.shape, first[field].ty, first[field].shape
+                ));
+            }
+        }
+    }
+    let mut res = Vec::with_capacity(first.len());
+    for (field, data) in first.iter().enumerate() {
+        let lens = samples
+            .iter()
+            .map(|s| s[field].bs.len() as u64)
+            .collect::<Vec<_>>();
+        let bs = if samples.len() == 1 {
+            // only the reference count of the buffer is increased
+            data.bs.clone()
+        } else {
+            let mut bs = Vec::with_capacity(lens.iter().sum::<u64>() as usize);
+            for sample in samples {
+                bs.extend_from_slice(&sample[field].bs);
+            }
+            bs.into()
+        };
+        res.push(Data {
+            bs,
+            ty: data.ty,
+            shape: data.shape.clone(),
+            lens,
+        });
+    }
+    Ok(res)
+}
+
+#[derive(Debug)]
+pub struct BatchReceiver {
+    recv: Receiver<Sample>,
+    // the number of samples which have not been received in this epoch
+    remain: usize,
+    // the samples of the batch being received
+    batch: Vec<Sample>,
+    // the samples of this epoch returned to the client, they are not read again after
+    // resuming from a checkpoint
+    seen: Vec<u32>,
+    // the length of an epoch
+    len: usize,
+    epoch: usize,
+    // None is infinite
+    epochs: Option<usize>,
+    // sampled with replacement, the samples are not tracked since it has no epochs to resume
+    endless: bool,
+}
+
+impl BatchReceiver {
+    pub fn new(recv: Receiver<Sample>, len: usize) -> Self {
+        Self::with_epochs(recv, len, Some(1))
+    }
+
+    // A job sampled with replacement has no epoch, and it never ends
+    pub fn endless(recv: Receiver<Sample>) -> Self {
+        BatchReceiver {
+            endless: true,
+            ..Self::with_epochs(recv, usize::MAX, Some(1))
+        }
+    }
+
+    // The samples of an epoch are pushed before the ones of the next epoch, see Joader
+    pub fn with_epochs(recv: Receiver<Sample>, len: usize, epochs: Option<usize>) -> Self {
+        BatchReceiver {
+            recv,
+            remain: len,
+            batch: Vec::new(),
+            seen: Vec::new(),
+            len,
+            epoch: 0,
+            epochs: epochs.map(|epochs| epochs.max(1)),
+            endless: false,
+        }
+    }
+
+    // Receive from the first epoch of a new channel, the samples of the old one are dropped
+    pub fn reset(&mut self, recv: Receiver<Sample>) {
+        self.recv = recv;
+        self.remain = self.len;
+        self.batch.clear();
+        self.seen.clear();
+        self.epoch = 0;
+    }
+
+    // Start in the middle of an epoch, where remain samples are left
+    pub fn resume(&mut self, epoch: usize, remain: usize) {
+        self.epoch = epoch;
+        self.remain = remain;
+    }
+
+    // The epoch being read and the samples returned in it, nothing for an endless receiver
+    pub fn progress(&self) -> (usize, &[u32]) {
+        (self.epoch, &self.seen)
+    }
+
+    pub fn remain(&self) -> usize {
+        self.remain
+    }
+
+    // The epoch of the last batch
+    pub fn epoch(&self) -> usize {
+        self.epoch
+    }
+
+    // The last batch is the end of its epoch
+    pub fn end_of_epoch(&self) -> bool {
+        self.remain == 0 && self.batch.is_empty()
+    }
+
+    fn has_next_epoch(&self) -> bool {
+        self.len != 0 && self.epochs.map_or(true, |epochs| self.epoch + 1 < epochs)
+    }
+
+    fn next_epoch(&mut self) -> bool {
+        if self.remain != 0 || !self.has_next_epoch() {
+            return false;
+        }
+        self.epoch += 1;
+        self.remain = self.len;
+        self.seen.clear();
+        true
+    }
+
+    async fn recv_sample(&mut self) -> Option<Sample> {
+        if self.remain == 0 {
+            return None;
+        }
+        let sample = self.recv.recv().await;
+        if sample.is_some() {
+            self.remain -= 1;
+        }
+        sample
+    }
+
+    pub async fn recv(&mut self) -> Option<Arc<Vec<Data>>> {
+        let (idx, data) = self.recv_sample().await?;
+        if !self.endless {
+            self.seen.push(idx);
+        }
+        Some(data)
+    }
+
+    // The samples of a batch dropped by drop_last are seen as well
+    fn take_batch(&mut self) -> Vec<Arc<Vec<Data>>> {
+        let batch = std::mem::take(&mut self.batch);
+        if !self.endless {
+            self.seen.extend(batch.iter().map(|(idx, _)| *idx));
+        }
+        batch.into_iter().map(|(_, data)| data).collect()
+    }
+
+    // Receive a batch with at most batch_size samples, the last batch of an epoch is
+    // smaller than batch_size when the length of the epoch is not divisible by batch_size,
+    // and it is dropped if drop_last is set. It's empty after the last epoch.
+    // It's cancel safe, the samples received are kept for the next call
+    pub async fn recv_batch(&mut self, batch_size: usize, drop_last: bool) -> Vec<Arc<Vec<Data>>> {
+        let batch_size = batch_size.max(1);
+        loop {
+            if self.end_of_epoch() && !self.next_epoch() {
+                return Vec::new();
+            }
+            while self.batch.len() < batch_size {
+                match self.recv_sample().await {
+                    Some(sample) => self.batch.push(sample),
+                    None => break,
+                }
+            }
+            let batch = self.take_batch();
+            if drop_last && batch.len() < batch_size {
+                // the next epoch may go on
+                if self.remain == 0 && self.has_next_epoch() {
+                    continue;
+                }
+                return Vec::new();
+            }
+            return batch;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::proto::job::data::DataType;
+    use std::time::Duration;
+    use tokio::sync::mpsc::channel;
+    use tokio::time::timeout;
+
+    fn sample(idx: u32) -> Arc<Vec<Data>> {
+        Arc::new(vec![
+            Data {
+                bs: idx.to_be_bytes().to_vec().into(),
+                ty: DataType::Uint as i32,
+                ..Default::default()
+            },
+            Data {
+                bs: vec![idx as u8; 3].into(),
+                ty: DataType::Image as i32,
+                shape: vec![1, 1, 3],
+                ..Default::default()
+            },
+        ])
+    }
+
+    #[test]
+    fn test_collate() {
+        let samples = (0..4).map(sample).collect::<Vec<_>>();
+        let batch = collate(&samples).unwrap();
+        assert_eq!(batch.len(), 2);
+        assert_eq!(batch[0].ty, DataType::Uint as i32);
+        assert_eq!(batch[0].bs.len(), 4 * 4);
+        assert_eq!(batch[0].lens, vec![4; 4]);
+        assert_eq!(&batch[0].bs[12..], &3u32.to_be_bytes());
+        assert_eq!(batch[1].bs, vec![0, 0, 0, 1, 1, 1, 2, 2, 2, 3, 3, 3]);
+        assert_eq!(batch[1].shape, vec![1, 1, 3]);
+        assert_eq!(batch[1].lens, vec![3; 4]);
+        assert!(collate(&[]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_collate_mismatch() {
+        let mut samples = (0..2).map(sample).collect::<Vec<_>>();
+        samples.push(Arc::new(vec![samples[0][0].clone()]));
+        assert!(collate(&samples).is_err());
+        let mut image = samples[0][1].clone();
+        image.ty = DataType::Uint as i32;
+        samples[2] = Arc::new(vec![samples[0][0].clone(), image]);
+        assert!(collate(&samples).is_err());
+    }
+
+    #[test]
+    fn test_collate_shared() {
+        let samples = vec![sample(7)];
+        let batch = collate(&samples).unwrap();
+        assert_eq!(batch[1].bs, samples[0][1].bs);
+        assert_eq!(batch[1].bs.as_ptr(), samples[0][1].bs.as_ptr());
+        assert_eq!(batch[1].lens, vec![3]);
+    }
+
+    #[tokio::test]
+    async fn test_recv_batch() {
+        let len = 10;
+        for drop_last in [false, true] {
+            let (s, r) = channel(len);
+            for i in 0..len {
+                s.send((i as u32, sample(i as u32))).await.unwrap();
+            }
+            let mut recv = BatchReceiver::new(r, len);
+            assert_eq!(recv.recv_batch(4, drop_last).await.len(), 4);
+            assert_eq!(recv.recv_batch(4, drop_last).await.len(), 4);
+            let last = recv.recv_batch(4, drop_last).await.len();
+            assert_eq!(last, if drop_last { 0 } else { 2 });
+            assert_eq!(recv.remain(), 0);
+            assert!(recv.recv_batch(4, drop_last).await.is_empty());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_recv_batch_epochs() {
+        let len = 10;
+        for drop_last in [false, true] {
+            let (s, r) = channel(3 * len);
+            for i in 0..3 * len {
+                s.send((i as u32, sample(i as u32))).await.unwrap();
+            }
+            let mut recv = BatchReceiver::with_epochs(r, len, Some(3));
+            for epoch in 0..3 {
+                for _ in 0..2 {
+                    assert_eq!(recv.recv_batch(4, drop_last).await.len(), 4);
+                    assert_eq!(recv.epoch(), epoch);
+                    assert!(!recv.end_of_epoch());
+                }
+                if !drop_last {
+                    // the batch does not take the samples of the next epoch
+                    assert_eq!(recv.recv_batch(4, drop_last).await.len(), 2);
+                    assert!(recv.end_of_epoch());
+                }
+            }
+            assert!(recv.recv_batch(4, drop_last).await.is_empty());
+            assert_eq!(recv.epoch(), 2);
+        }
+        // infinite
+        let (s, r) = channel(8);
+        let mut recv = BatchReceiver::with_epochs(r, 2, None);
+        for epoch in 0..4 {
+            s.send((epoch, sample(epoch))).await.unwrap();
+            s.send((epoch, sample(epoch))).await.unwrap();
+            assert_eq!(recv.recv_batch(4, false).await, vec![sample(epoch); 2]);
+            assert_eq!(recv.epoch(), epoch as usize);
+            assert!(recv.end_of_epoch());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_recv_batch_progress() {
+        let (s, r) = channel(16);
+        for i in 0..8 {
+            s.send((i, sample(i))).await.unwrap();
+        }
+        let mut recv = BatchReceiver::with_epochs(r, 4, Some(2));
+        assert_eq!(recv.recv_batch(3, false).await.len(), 3);
+        assert_eq!(recv.progress(), (0, &[0, 1, 2][..]));
+        assert_eq!(recv.recv_batch(3, false).await.len(), 1);
+        assert_eq!(recv.progress(), (0, &[0, 1, 2, 3][..]));
+        assert_eq!(recv.recv_batch(3, true).await.len(), 3);
+        assert_eq!(recv.progress(), (1, &[4, 5, 6][..]));
+        // the last sample is dropped by drop_last
+        assert!(recv.recv_batch(3, true).await.is_empty());
+        assert_eq!(recv.progress(), (1, &[4, 5, 6, 7][..]));
+
+        // resume with the last 2 samples of the second epoch
+        let (s, r) = channel(2);
+        let mut recv = BatchReceiver::with_epochs(r, 4, Some(2));
+        recv.resume(1, 2);
+        for i in 2..4 {
+            s.send((i, sample(i))).await.unwrap();
+        }
+        assert_eq!(recv.recv_batch(4, false).await.len(), 2);
+        assert_eq!(recv.progress(), (1, &[2, 3][..]));
+        assert!(recv.end_of_epoch());
+        assert!(recv.recv_batch(4, false).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_recv_batch_endless() {
+        let (s, r) = channel(16);
+        let mut recv = BatchReceiver::endless(r);
+        for i in 0..16 {
+            s.send((i % 4, sample(i % 4))).await.unwrap();
+        }
+        for _ in 0..3 {
+            assert_eq!(recv.recv_batch(5, true).await.len(), 5);
+            assert_eq!(recv.progress(), (0, &[][..]));
+        }
+        assert!(recv.recv().await.is_some());
+        assert_eq!(recv.progress(), (0, &[][..]));
+        assert!(!recv.end_of_epoch());
+    }
+
+    #[tokio::test]
+    async fn test_recv_batch_cancel() {
+        let (s, r) = channel(4);
+        let mut recv = BatchReceiver::new(r, 4);
+        for i in 0..2 {
+            s.send((i, sample(i))).await.unwrap();
+        }
+        let res = timeout(Duration::from_millis(10), recv.recv_batch(4, false)).await;
+        assert!(res.is_err());
+        for i in 2..4 {
+            s.send((i, sample(i))).await.unwrap();
+        }
+        let batch = recv.recv_batch(4, false).await;
+        assert_eq!(batch.len(), 4);
+        assert_eq!(batch[0], sample(0));
+    }
+}
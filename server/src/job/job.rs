@@ -7,16 +7,20 @@ use tokio::sync::mpsc::{channel, Receiver, Sender};
 use crate::proto::job::Data;
 // Loader store the information of schema, dataset and filter
 const CAP: usize = 1024;
+
+// A sample with its index in the dataset
+pub type Sample = (u32, Arc<Vec<Data>>);
+
 #[derive(Debug)]
 pub struct Job {
     id: u64,
-    sender: Sender<Arc<Vec<Data>>>,
+    sender: Sender<Sample>,
     pending: AtomicUsize
 }
 
 impl Job {
-    pub fn new(id: u64) -> (Arc<Self>, Receiver<Arc<Vec<Data>>>) {
-        let (s, r) = channel::<Arc<Vec<Data>>>(CAP);
+    pub fn new(id: u64) -> (Arc<Self>, Receiver<Sample>) {
+        let (s, r) = channel::<Sample>(CAP);
         (
             Arc::new(Job {
                 id,
@@ -35,9 +39,12 @@ impl Job {
         self.sender.capacity() == 0
     }
 
-    pub async fn push(&self, v: Arc<Vec<Data>>) {
+    pub async fn push(&self, idx: u32, v: Arc<Vec<Data>>) {
         log::debug!("{} push- data with pending {:?} capacity {}", self.id, self.pending.load(Ordering::SeqCst), self.sender.capacity());
-        self.sender.send(v).await.unwrap();
+        // the receiver is dropped when the job is deleted or reset
+        if self.sender.send((idx, v)).await.is_err() {
+            log::debug!("{} drop data pushed after the job is closed", self.id);
+        }
         self.pending.fetch_sub(1, Ordering::SeqCst);
     }
 
@@ -46,6 +53,11 @@ impl Job {
         self.pending.load(Ordering::SeqCst) < self.sender.capacity()
     }
 
+    // The samples decided for the job but not pushed yet
+    pub fn pending(&self) -> usize {
+        self.pending.load(Ordering::SeqCst)
+    }
+
     pub fn add_pending(&self) {
         self.pending.fetch_add(1, Ordering::SeqCst);
     }
@@ -1,4 +1,5 @@
 mod job;
 pub use job::*;
+mod batch;
+pub use batch::*;
 pub mod channel;
-
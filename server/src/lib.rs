@@ -1,7 +1,5 @@
-#![feature(get_mut_unchecked)]
-#![feature(cursor_remaining)]
-#![feature(associated_type_defaults)]
-#![feature(test)]
+// the benchmarks need nightly, the rest builds on stable
+#![cfg_attr(feature = "bench", feature(test))]
 pub mod job;
 pub mod cache;
 pub mod process;
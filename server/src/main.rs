@@ -1,5 +1,9 @@
-use ::joader::cache::cache::Cache;
+use ::joader::cache::cache::{parse_capacity, Cache};
+use ::joader::cache::disk::DiskCache;
+use ::joader::cache::policy::PolicyType;
+use ::joader::cache::snapshot::Snapshot;
 use ::joader::joader::joader_table::JoaderTable;
+use ::joader::joader::read_pool::ReadPool;
 use joader::service::{DatasetSvcImpl, IdGenerator, JobSvcImpl};
 use joader::proto::dataset::dataset_svc_server::DatasetSvcServer;
 use joader::proto::job::job_svc_server::JobSvcServer;
@@ -7,32 +11,56 @@ use std::collections::HashMap;
 use std::net::SocketAddr;
 use std::sync::Arc;
 use std::time::Duration;
+use clap::ArgMatches;
+use tokio::signal::unix::{signal, SignalKind};
 use tokio::sync::Mutex;
 use tokio::time::sleep;
 use tonic::transport::Server;
 
 async fn run(joader_table: Arc<Mutex<JoaderTable>>) {
     loop {
-        {
+        let wait = {
             let mut joader_table = joader_table.lock().await;
             let empty = joader_table.is_empty();
-            if !empty {
+            if empty {
+                log::debug!("sleep ....");
+                Duration::from_millis(1000)
+            } else if joader_table.is_busy() {
+                // wait for the read pool to drain without holding the lock
+                Duration::from_millis(1)
+            } else {
                 joader_table.next().await;
                 continue;
             }
         };
-        log::debug!("sleep ....");
-        sleep(Duration::from_millis(1000)).await;
+        sleep(wait).await;
         // we add it it because the mmap block, in the future, we will use io_uring
     }
 }
 
-async fn start_server(ip: &str, port: &str) -> Result<(), Box<dyn std::error::Error>> {
+async fn start_server(
+    ip: &str,
+    port: &str,
+    cache: Cache,
+    read_threads: usize,
+    lookahead: usize,
+    snapshot: Option<Snapshot>,
+) -> Result<(), Box<dyn std::error::Error>> {
     log::info!("start server");
     let id_gen = IdGenerator::new();
     let dataset_id_table = Arc::new(Mutex::new(HashMap::new()));
-    let cache = Arc::new(Mutex::new(Cache::with_capacity(224*224*3*32*(128))));
-    let joader_table = Arc::new(Mutex::new(JoaderTable::new(cache)));
+    let cache = Arc::new(Mutex::new(cache));
+    // reads and decodes run in the read pool, so the runtime only serves requests
+    log::info!("read pool with {:?} threads", read_threads);
+    let read_pool = ReadPool::new(read_threads, read_threads * 4);
+    let mut joader_table = JoaderTable::new(cache.clone(), read_pool);
+    log::info!("sampler lookahead {:?}", lookahead);
+    joader_table.set_lookahead(lookahead);
+    if let Some(snapshot) = snapshot {
+        joader_table.set_snapshot(snapshot);
+    }
+    let joader_table = Arc::new(Mutex::new(joader_table));
+    tokio::spawn(report(cache, joader_table.clone()));
     let ip_port = ip.to_string() + ":" + port;
     let addr: SocketAddr = ip_port.parse()?;
     let job_id_table = Arc::new(Mutex::new(HashMap::new()));
@@ -49,22 +77,82 @@ async fn start_server(ip: &str, port: &str) -> Result<(), Box<dyn std::error::Er
         dataset_id_table.clone(),
     );
     log::info!("start joader at {:?}......", addr);
-    tokio::spawn(async move { run(joader_table).await });
+    tokio::spawn(run(joader_table.clone()));
     let server = Server::builder()
         .add_service(DatasetSvcServer::new(dataset_svc))
         .add_service(JobSvcServer::new(job_svc))
-        .serve(addr);
+        .serve_with_shutdown(addr, shutdown());
     server.await?;
+    let names = dataset_id_table.lock().await;
+    joader_table.lock().await.save_snapshot(&names).await;
+    log::info!("stop joader");
     Ok(())
 }
 
-#[tokio::main(flavor = "multi_thread", worker_threads = 48)]
+// Wait for ctrl-c or SIGTERM, the server stops taking requests and saves the snapshot
+async fn shutdown() {
+    let mut terminate = signal(SignalKind::terminate()).unwrap();
+    tokio::select! {
+        _ = tokio::signal::ctrl_c() => {}
+        _ = terminate.recv() => {}
+    }
+    log::info!("shutdown joader");
+}
+
+// Log the numbers of cache and the skew between the jobs of each dataset periodically
+async fn report(cache: Arc<Mutex<Cache>>, joader_table: Arc<Mutex<JoaderTable>>) {
+    loop {
+        sleep(Duration::from_secs(10)).await;
+        let stats = cache.lock().await.stats();
+        // dataset id -> skew, the skew is the default one for an independent sampler
+        let skews = joader_table.lock().await.skews().await;
+        log::info!("cache stats {:?} sampler skews {:?}", stats, skews);
+    }
+}
+
+// The yaml interface of clap is deprecated, but cli.yaml is the config of server
+#[allow(deprecated)]
+fn get_matches() -> ArgMatches {
+    let yaml = clap::load_yaml!("cli.yaml");
+    clap::App::from_yaml(yaml).get_matches()
+}
+
+#[tokio::main(flavor = "multi_thread")]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let log4rs_config = "/server/log4rs.yaml";
-    let ip = "0.0.0.0";
-    let port = "4321";
-    log4rs::init_file(log4rs_config, Default::default()).unwrap();
+    let matches = get_matches();
+    let log4rs_config = matches.value_of("log4rs_config").unwrap();
+    let ip = matches.value_of("ip").unwrap();
+    let port = matches.value_of("port").unwrap();
+    let capacity = parse_capacity(matches.value_of("cache_capacity").unwrap())?;
+    let policy = matches.value_of("cache_policy").unwrap().parse()?;
+    let read_threads = match matches.value_of("read_threads").unwrap().parse()? {
+        0 => num_cpus::get(),
+        n => n,
+    };
+    let lookahead = matches.value_of("lookahead").unwrap().parse()?;
+    if policy == PolicyType::Belady && lookahead == 0 {
+        return Err("belady policy needs a nonzero --lookahead".into());
+    }
+    log4rs::init_file(log4rs_config, Default::default())
+        .map_err(|e| format!("fail to load log config {:?}: {}", log4rs_config, e))?;
+    log::info!("cache capacity {:?} with policy {:?}", capacity, policy);
+    let mut cache = Cache::with_policy(capacity, policy);
+    let pin_capacity = parse_capacity(matches.value_of("pin_capacity").unwrap())?;
+    log::info!("pinned capacity {:?}", pin_capacity);
+    cache.set_pin_capacity(pin_capacity);
+    if let Some(dir) = matches.value_of("disk_dir") {
+        let disk_capacity = matches.value_of("disk_capacity").unwrap().parse()?;
+        log::info!("disk cache in {:?} with capacity {:?}", dir, disk_capacity);
+        cache.set_disk(DiskCache::open(dir, disk_capacity)?);
+    }
+    let snapshot = match matches.value_of("snapshot_dir") {
+        Some(dir) => {
+            log::info!("cache snapshot in {:?}", dir);
+            Some(Snapshot::open(dir)?)
+        }
+        None => None,
+    };
     //start server
-    start_server(ip, port).await?;
+    start_server(ip, port, cache, read_threads, lookahead, snapshot).await?;
     Ok(())
 }
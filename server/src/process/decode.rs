@@ -1,8 +1,28 @@
-use opencv::{prelude::Mat, imgcodecs::imdecode, imgproc::{COLOR_BGR2RGB, cvt_color}};
+use opencv::{
+    imgcodecs::{
+        imdecode, IMREAD_COLOR, IMREAD_REDUCED_COLOR_2, IMREAD_REDUCED_COLOR_4,
+        IMREAD_REDUCED_COLOR_8,
+    },
+    imgproc::{cvt_color, COLOR_BGR2RGB},
+    prelude::Mat,
+};
 
 pub fn decode_rgb_from_memory(data: & [u8]) -> Mat {
-    let mat = Mat:: from_slice(data).unwrap();
-    let image = imdecode( & mat, 1).unwrap();
+    decode_rgb_from_memory_reduced(data, 1)
+}
+
+// Decode a JPEG at 1/scale of its size, libjpeg scales it in the DCT domain
+pub fn decode_rgb_from_memory_reduced(data: &[u8], scale: i32) -> Mat {
+    let flag = match scale {
+        1 => IMREAD_COLOR,
+        2 => IMREAD_REDUCED_COLOR_2,
+        4 => IMREAD_REDUCED_COLOR_4,
+        8 => IMREAD_REDUCED_COLOR_8,
+        // reduced_scale only returns the scales above
+        err => unreachable!("unsupported scale {:?}", err),
+    };
+    let mat = Mat::from_slice(data).unwrap();
+    let image = imdecode(&mat, flag).unwrap();
     let mut dst = Mat::default();
     cvt_color(&image, &mut dst, COLOR_BGR2RGB, 0).unwrap();
     dst
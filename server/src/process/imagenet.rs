@@ -1,4 +1,4 @@
-use super::decode_rgb_from_memory;
+use super::{decode_rgb_from_memory, decode_rgb_from_memory_reduced, jpeg_size, reduced_scale};
 use image::imageops::FilterType::Triangle;
 use opencv::{
     core::{Range, Vector, CV_8UC3},
@@ -9,55 +9,59 @@ use rand::distributions::{Distribution, Uniform};
 use std::slice::from_raw_parts;
 use tch::vision::imagenet::load_image_and_resize224_from_memory;
 
-pub fn random_crop(image: &Mat) -> Mat {
-    // Get parameters for ``crop`` for a random sized crop.
-    // Args:
-    //     scale (list): range of scale of the origin size cropped
-    //     ratio (list): range of aspect ratio of the origin aspect ratio cropped
-
-    // Returns:
-    //     tuple: params (i, j, h, w) to be passed to ``crop`` for a random
-    //     sized crop.
-    pub fn random_parame(h: i32, w: i32, scale: &[f32], ratio: &[f32]) -> (i32, i32, i32, i32) {
-        let area = (h * w) as f32;
-        let ratio_range = Uniform::from(ratio[0].ln()..ratio[1].ln());
-        let scale_range = Uniform::from(scale[0]..scale[1]);
-        let mut rng = rand::thread_rng();
-        for _ in 0..10 {
-            let target_area = area * ratio_range.sample(&mut rng);
-            let aspect_ratio = scale_range.sample(&mut rng).exp();
-            let crop_w = ((target_area * aspect_ratio).sqrt()).round() as i32;
-            let crop_h = ((target_area / aspect_ratio).sqrt()).round() as i32;
-            if crop_w > 0 && crop_w <= w && crop_h > 0 && crop_h <= h {
-                let w_range = Uniform::from(0..w - crop_w + 1);
-                let h_range = Uniform::from(0..h - crop_h + 1);
-                let i = h_range.sample(&mut rng);
-                let j = w_range.sample(&mut rng);
-                return (i, j, crop_h, crop_w);
-            }
-        }
+const SCALE: [f32; 2] = [0.08, 1.0];
+const RATIO: [f32; 2] = [0.75, 1.3333333333333333];
 
-        // center crop
-        let in_ratio = w as f32 / h as f32;
-        let crop_w;
-        let crop_h;
-        if in_ratio < ratio[0] {
-            crop_w = w;
-            crop_h = (w as f32 / ratio[0]).round() as i32;
-        } else if in_ratio > ratio[1] {
-            crop_h = h;
-            crop_w = (h as f32 * ratio[1]).round() as i32;
-        } else {
-            crop_h = h;
-            crop_w = w;
+// Get parameters for ``crop`` for a random sized crop.
+// Args:
+//     scale (list): range of scale of the origin size cropped
+//     ratio (list): range of aspect ratio of the origin aspect ratio cropped
+
+// Returns:
+//     tuple: params (i, j, h, w) to be passed to ``crop`` for a random
+//     sized crop.
+pub fn random_parame(h: i32, w: i32, scale: &[f32], ratio: &[f32]) -> (i32, i32, i32, i32) {
+    let area = (h * w) as f32;
+    let ratio_range = Uniform::from(ratio[0].ln()..ratio[1].ln());
+    let scale_range = Uniform::from(scale[0]..scale[1]);
+    let mut rng = rand::thread_rng();
+    for _ in 0..10 {
+        let target_area = area * ratio_range.sample(&mut rng);
+        let aspect_ratio = scale_range.sample(&mut rng).exp();
+        let crop_w = ((target_area * aspect_ratio).sqrt()).round() as i32;
+        let crop_h = ((target_area / aspect_ratio).sqrt()).round() as i32;
+        if crop_w > 0 && crop_w <= w && crop_h > 0 && crop_h <= h {
+            let w_range = Uniform::from(0..w - crop_w + 1);
+            let h_range = Uniform::from(0..h - crop_h + 1);
+            let i = h_range.sample(&mut rng);
+            let j = w_range.sample(&mut rng);
+            return (i, j, crop_h, crop_w);
         }
-        let i = (h - crop_h) / 2;
-        let j = (w - crop_w) / 2;
-        (i, j, crop_h, crop_w)
     }
+
+    // center crop
+    let in_ratio = w as f32 / h as f32;
+    let crop_w;
+    let crop_h;
+    if in_ratio < ratio[0] {
+        crop_w = w;
+        crop_h = (w as f32 / ratio[0]).round() as i32;
+    } else if in_ratio > ratio[1] {
+        crop_h = h;
+        crop_w = (h as f32 * ratio[1]).round() as i32;
+    } else {
+        crop_h = h;
+        crop_w = w;
+    }
+    let i = (h - crop_h) / 2;
+    let j = (w - crop_w) / 2;
+    (i, j, crop_h, crop_w)
+}
+
+pub fn random_crop(image: &Mat) -> Mat {
     let h = image.rows();
     let w = image.cols();
-    let (i, j, h, w) = random_parame(h, w, &[0.08, 1.0], &[0.75, 1.3333333333333333]);
+    let (i, j, h, w) = random_parame(h, w, &SCALE, &RATIO);
     // println!("{:} {:} {:} {:}", i, j, h, w);
     let h_range = Range::new(i, i + h).unwrap();
     let w_range = Range::new(j, j + w).unwrap();
@@ -73,6 +77,34 @@ pub fn decode_resize_224_opencv(data: &[u8]) -> Vec<u8> {
     dst.data_bytes().unwrap().to_vec()
 }
 
+// Same as decode_resize_224_opencv, but the crop is chosen before decoding,
+// so the JPEG can be decoded at 1/2, 1/4 or 1/8 size when the crop is large enough
+pub fn decode_resize_224_reduced(data: &[u8]) -> Vec<u8> {
+    let (h, w) = match jpeg_size(data) {
+        Some(size) => size,
+        None => return decode_resize_224_opencv(data),
+    };
+    let (i, j, crop_h, crop_w) = random_parame(h, w, &SCALE, &RATIO);
+    let scale = reduced_scale(crop_h, crop_w, 224);
+    let image = decode_rgb_from_memory_reduced(data, scale);
+    // imdecode applies the EXIF orientation, so a rotated image doesn't have the size in SOF
+    // and the crop chosen from it doesn't fit
+    if image.rows() != (h + scale - 1) / scale || image.cols() != (w + scale - 1) / scale {
+        return decode_resize_224_opencv(data);
+    }
+    // libjpeg rounds up the size of the scaled image
+    let (i, j) = (i / scale, j / scale);
+    let crop_h = (crop_h / scale).min(image.rows() - i);
+    let crop_w = (crop_w / scale).min(image.cols() - j);
+    let h_range = Range::new(i, i + crop_h).unwrap();
+    let w_range = Range::new(j, j + crop_w).unwrap();
+    let mut image = Mat::ranges(&image, &Vector::from(vec![h_range, w_range])).unwrap();
+    let mut dst = unsafe { Mat::new_rows_cols(224, 224, CV_8UC3).unwrap() };
+    let size = dst.size().unwrap();
+    resize(&mut image, &mut dst, size, 0.0, 0.0, INTER_LINEAR).unwrap();
+    dst.data_bytes().unwrap().to_vec()
+}
+
 pub fn decode_resize_224_tch(data: &[u8]) -> Vec<u8> {
     let tensor = load_image_and_resize224_from_memory(data).unwrap();
     let data = unsafe { from_raw_parts(tensor.data_ptr() as *mut u8, 224 * 224 * 3).to_vec() };
@@ -84,3 +116,98 @@ pub fn decode_resize_224_image(data: &[u8]) -> Vec<u8> {
     let image = image.resize(224, 224, Triangle);
     image.as_bytes().to_vec()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::process::{msg_unpack, MsgObject};
+    use lmdb::{EnvironmentFlags, Transaction};
+    use opencv::core::Scalar;
+    use opencv::imgcodecs::imencode;
+    use std::path::Path;
+    #[cfg(feature = "bench")]
+    use test::Bencher;
+    #[cfg(feature = "bench")]
+    extern crate test;
+
+    fn read_jpeg(idx: u32) -> Vec<u8> {
+        let location = "/data/lmdb-imagenet/ILSVRC-train.lmdb";
+        let env = lmdb::Environment::new()
+            .set_flags(EnvironmentFlags::NO_SUB_DIR | EnvironmentFlags::READ_ONLY)
+            .open_with_permissions(Path::new(location), 0o600)
+            .unwrap();
+        let db = env.open_db(None).unwrap();
+        let txn = env.begin_ro_txn().unwrap();
+        let data: &[u8] = txn.get(db, &idx.to_string()).unwrap();
+        let data = msg_unpack(data);
+        match &data[0] {
+            MsgObject::Array(data) => match data[0].as_ref() {
+                MsgObject::Map(map) => match map["data"].as_ref() {
+                    MsgObject::Bin(bin) => bin.to_vec(),
+                    err => unimplemented!("{:?}", err),
+                },
+                err => unimplemented!("{:?}", err),
+            },
+            err => unimplemented!("{:?}", err),
+        }
+    }
+
+    #[test]
+    fn test_decode_reduced() {
+        let data = read_jpeg(0);
+        let (h, w) = jpeg_size(&data).unwrap();
+        let image = decode_rgb_from_memory(&data);
+        assert_eq!((image.rows(), image.cols()), (h, w));
+        assert_eq!(decode_resize_224_reduced(&data).len(), 224 * 224 * 3);
+    }
+
+    // A JPEG with the EXIF orientation 6, it's rotated by 90 degrees when decoded
+    fn rotated_jpeg(h: i32, w: i32) -> Vec<u8> {
+        let image = Mat::new_rows_cols_with_default(h, w, CV_8UC3, Scalar::all(128.0)).unwrap();
+        let mut buf = Vector::<u8>::new();
+        imencode(".jpg", &image, &mut buf, &Vector::new()).unwrap();
+        let jpeg = buf.to_vec();
+        // big endian TIFF header and an IFD with the only entry of orientation
+        let mut exif = b"Exif\0\0MM\0\x2a\0\0\0\x08".to_vec();
+        exif.extend_from_slice(&[0, 1, 0x01, 0x12, 0, 3, 0, 0, 0, 1, 0, 6, 0, 0, 0, 0, 0, 0]);
+        let mut data = jpeg[..2].to_vec();
+        data.extend_from_slice(&[0xff, 0xe1]);
+        data.extend_from_slice(&(exif.len() as u16 + 2).to_be_bytes());
+        data.extend(exif);
+        data.extend_from_slice(&jpeg[2..]);
+        data
+    }
+
+    #[test]
+    fn test_decode_rotated() {
+        let data = rotated_jpeg(600, 1200);
+        assert_eq!(jpeg_size(&data), Some((600, 1200)));
+        let image = decode_rgb_from_memory(&data);
+        assert_eq!((image.rows(), image.cols()), (1200, 600));
+        for _ in 0..16 {
+            assert_eq!(decode_resize_224_reduced(&data).len(), 224 * 224 * 3);
+        }
+    }
+
+    #[cfg(feature = "bench")]
+    #[bench]
+    fn bench_decode_resize_224_opencv(b: &mut Bencher) {
+        let data = (0..64).map(read_jpeg).collect::<Vec<_>>();
+        b.iter(|| {
+            data.iter()
+                .map(|d| decode_resize_224_opencv(d).len())
+                .sum::<usize>()
+        });
+    }
+
+    #[cfg(feature = "bench")]
+    #[bench]
+    fn bench_decode_resize_224_reduced(b: &mut Bencher) {
+        let data = (0..64).map(read_jpeg).collect::<Vec<_>>();
+        b.iter(|| {
+            data.iter()
+                .map(|d| decode_resize_224_reduced(d).len())
+                .sum::<usize>()
+        });
+    }
+}
@@ -0,0 +1,95 @@
+// https://www.w3.org/Graphics/JPEG/itu-t81.pdf
+const SOI: u8 = 0xD8;
+const SOS: u8 = 0xDA;
+const DHT: u8 = 0xC4;
+const JPG: u8 = 0xC8;
+const DAC: u8 = 0xCC;
+
+// Read (height, width) from the frame header of a JPEG without decoding it
+pub fn jpeg_size(data: &[u8]) -> Option<(i32, i32)> {
+    if data.len() < 4 || data[0] != 0xFF || data[1] != SOI {
+        return None;
+    }
+    let mut pos = 2;
+    while pos + 4 <= data.len() {
+        if data[pos] != 0xFF {
+            return None;
+        }
+        let marker = data[pos + 1];
+        match marker {
+            // fill bytes
+            0xFF => {
+                pos += 1;
+                continue;
+            }
+            // markers without payload
+            0x01 | 0xD0..=0xD7 => {
+                pos += 2;
+                continue;
+            }
+            _ => (),
+        }
+        let len = u16::from_be_bytes([data[pos + 2], data[pos + 3]]) as usize;
+        match marker {
+            // SOF0-SOF15: Lf(2) P(1) Y(2) X(2)
+            0xC0..=0xCF if marker != DHT && marker != JPG && marker != DAC => {
+                if pos + 9 > data.len() {
+                    return None;
+                }
+                let h = u16::from_be_bytes([data[pos + 5], data[pos + 6]]);
+                let w = u16::from_be_bytes([data[pos + 7], data[pos + 8]]);
+                return Some((h as i32, w as i32));
+            }
+            SOS => return None,
+            _ if len < 2 => return None,
+            _ => pos += 2 + len,
+        }
+    }
+    None
+}
+
+// The largest DCT scaling denominator (1, 2, 4 or 8) with which a crop of
+// (crop_h, crop_w) is still at least (target, target) after decoding
+pub fn reduced_scale(crop_h: i32, crop_w: i32, target: i32) -> i32 {
+    let mut scale = 8;
+    while scale > 1 && (crop_h / scale < target || crop_w / scale < target) {
+        scale /= 2;
+    }
+    scale
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn header(h: u16, w: u16) -> Vec<u8> {
+        let mut data = vec![0xFF, SOI];
+        // APP0 with 4 bytes payload
+        data.extend_from_slice(&[0xFF, 0xE0, 0x00, 0x06, b'J', b'F', b'I', b'F']);
+        // DHT must not be taken as a frame header
+        data.extend_from_slice(&[0xFF, DHT, 0x00, 0x03, 0x00]);
+        data.extend_from_slice(&[0xFF, 0xC2, 0x00, 0x11, 0x08]);
+        data.extend_from_slice(&h.to_be_bytes());
+        data.extend_from_slice(&w.to_be_bytes());
+        data.extend_from_slice(&[0x03, 1, 0x22, 0, 2, 0x11, 1, 3, 0x11, 1]);
+        data.extend_from_slice(&[0xFF, SOS]);
+        data
+    }
+
+    #[test]
+    fn test_jpeg_size() {
+        assert_eq!(jpeg_size(&header(375, 500)), Some((375, 500)));
+        assert_eq!(jpeg_size(&header(375, 500)[..20]), None);
+        assert_eq!(jpeg_size(&[0x89, b'P', b'N', b'G']), None);
+    }
+
+    #[test]
+    fn test_reduced_scale() {
+        assert_eq!(reduced_scale(224, 224, 224), 1);
+        assert_eq!(reduced_scale(447, 2000, 224), 1);
+        assert_eq!(reduced_scale(448, 2000, 224), 2);
+        assert_eq!(reduced_scale(1000, 1000, 224), 4);
+        assert_eq!(reduced_scale(4000, 3000, 224), 8);
+        assert_eq!(reduced_scale(100, 100, 224), 1);
+    }
+}
@@ -2,6 +2,8 @@ mod msgpack;
 pub use msgpack::*;
 mod decode;
 pub use decode::*;
+mod jpeg;
+pub use jpeg::*;
 
 mod imagenet;
 pub use imagenet::*;
\ No newline at end of file
@@ -81,7 +81,7 @@ pub fn msg_unpack<'a>(bytes: &'a [u8]) -> Vec<MsgObject<'a>> {
     let mut ret = Vec::new();
     let mut buf = Cursor::new(bytes);
     loop {
-        if buf.is_empty() {
+        if buf.position() as usize >= buf.get_ref().len() {
             break;
         }
         ret.push(parse_object(&mut buf));
@@ -16,6 +16,8 @@ pub struct CreateDatasetRequest {
     pub items: ::prost::alloc::vec::Vec<DataItem>,
     #[prost(uint32, repeated, tag = "5")]
     pub weights: ::prost::alloc::vec::Vec<u32>,
+    #[prost(enumeration = "create_dataset_request::Sampler", tag = "6")]
+    pub sampler: i32,
 }
 /// Nested message and enum types in `CreateDatasetRequest`.
 pub mod create_dataset_request {
@@ -27,6 +29,17 @@ pub mod create_dataset_request {
         /// It's for tests
         Dummy = 2,
     }
+    /// How the jobs of the dataset are sampled
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+    #[repr(i32)]
+    pub enum Sampler {
+        /// Each job is shuffled on its own, and the jobs which read the same sample in a
+        /// round share the read. It supports all orders, ranks and replacement
+        Independent = 0,
+        /// The jobs read the samples they have in common in the same rounds as much as
+        /// possible. It only supports shuffled jobs without ranks or replacement
+        Shared = 1,
+    }
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct CreateDatasetResponse {
@@ -43,6 +56,67 @@ pub struct DeleteDatasetResponse {
     #[prost(message, optional, tag = "2")]
     pub status: ::core::option::Option<super::common::Status>,
 }
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PrewarmRequest {
+    /// The name of dataset
+    #[prost(string, tag = "1")]
+    pub name: ::prost::alloc::string::String,
+    /// The samples of indices in [start, end) are loaded
+    #[prost(uint32, tag = "2")]
+    pub start: u32,
+    #[prost(uint32, tag = "3")]
+    pub end: u32,
+}
+/// The progress of prewarming, it's sent after each batch of samples
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PrewarmResponse {
+    #[prost(uint32, tag = "1")]
+    pub total: u32,
+    /// The samples loaded into cache
+    #[prost(uint32, tag = "2")]
+    pub loaded: u32,
+    /// The samples which were in cache already
+    #[prost(uint32, tag = "3")]
+    pub cached: u32,
+    /// The cache budget is used up, and no more samples are loaded
+    #[prost(bool, tag = "4")]
+    pub full: bool,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PinRequest {
+    /// The name of dataset
+    #[prost(string, tag = "1")]
+    pub name: ::prost::alloc::string::String,
+    /// The samples selected by it are pinned, all samples if it's not set
+    #[prost(message, optional, tag = "2")]
+    pub condition: ::core::option::Option<super::job::Condition>,
+}
+/// The progress of pinning, it's sent after each batch of samples
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PinResponse {
+    #[prost(uint32, tag = "1")]
+    pub total: u32,
+    /// The samples pinned
+    #[prost(uint32, tag = "2")]
+    pub pinned: u32,
+    /// The pinned budget is used up, and no more samples are pinned
+    #[prost(bool, tag = "3")]
+    pub full: bool,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct UnpinRequest {
+    #[prost(string, tag = "1")]
+    pub name: ::prost::alloc::string::String,
+    /// The samples selected by it are unpinned, all samples if it's not set
+    #[prost(message, optional, tag = "2")]
+    pub condition: ::core::option::Option<super::job::Condition>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct UnpinResponse {
+    /// The number of samples unpinned
+    #[prost(uint32, tag = "1")]
+    pub unpinned: u32,
+}
 #[doc = r" Generated client implementations."]
 pub mod dataset_svc_client {
     #![allow(unused_variables, dead_code, missing_docs, clippy::let_unit_value)]
@@ -131,6 +205,58 @@ pub mod dataset_svc_client {
             let path = http::uri::PathAndQuery::from_static("/dataset.DatasetSvc/DeleteDataset");
             self.inner.unary(request.into_request(), path, codec).await
         }
+        #[doc = " Load and decode samples into cache before jobs read them. It never evicts"]
+        #[doc = " cached samples, and it yields to the reads of jobs"]
+        pub async fn prewarm(
+            &mut self,
+            request: impl tonic::IntoRequest<super::PrewarmRequest>,
+        ) -> Result<tonic::Response<tonic::codec::Streaming<super::PrewarmResponse>>, tonic::Status>
+        {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/dataset.DatasetSvc/Prewarm");
+            self.inner
+                .server_streaming(request.into_request(), path, codec)
+                .await
+        }
+        #[doc = " Keep samples in cache until they are unpinned or the dataset is deleted. The"]
+        #[doc = " pinned samples are never evicted, and they are counted in a separate budget"]
+        pub async fn pin(
+            &mut self,
+            request: impl tonic::IntoRequest<super::PinRequest>,
+        ) -> Result<tonic::Response<tonic::codec::Streaming<super::PinResponse>>, tonic::Status>
+        {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/dataset.DatasetSvc/Pin");
+            self.inner
+                .server_streaming(request.into_request(), path, codec)
+                .await
+        }
+        pub async fn unpin(
+            &mut self,
+            request: impl tonic::IntoRequest<super::UnpinRequest>,
+        ) -> Result<tonic::Response<super::UnpinResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/dataset.DatasetSvc/Unpin");
+            self.inner.unary(request.into_request(), path, codec).await
+        }
     }
 }
 #[doc = r" Generated server implementations."]
@@ -148,6 +274,32 @@ pub mod dataset_svc_server {
             &self,
             request: tonic::Request<super::DeleteDatasetRequest>,
         ) -> Result<tonic::Response<super::DeleteDatasetResponse>, tonic::Status>;
+        #[doc = "Server streaming response type for the Prewarm method."]
+        type PrewarmStream: futures_core::Stream<Item = Result<super::PrewarmResponse, tonic::Status>>
+            + Send
+            + Sync
+            + 'static;
+        #[doc = " Load and decode samples into cache before jobs read them. It never evicts"]
+        #[doc = " cached samples, and it yields to the reads of jobs"]
+        async fn prewarm(
+            &self,
+            request: tonic::Request<super::PrewarmRequest>,
+        ) -> Result<tonic::Response<Self::PrewarmStream>, tonic::Status>;
+        #[doc = "Server streaming response type for the Pin method."]
+        type PinStream: futures_core::Stream<Item = Result<super::PinResponse, tonic::Status>>
+            + Send
+            + Sync
+            + 'static;
+        #[doc = " Keep samples in cache until they are unpinned or the dataset is deleted. The"]
+        #[doc = " pinned samples are never evicted, and they are counted in a separate budget"]
+        async fn pin(
+            &self,
+            request: tonic::Request<super::PinRequest>,
+        ) -> Result<tonic::Response<Self::PinStream>, tonic::Status>;
+        async fn unpin(
+            &self,
+            request: tonic::Request<super::UnpinRequest>,
+        ) -> Result<tonic::Response<super::UnpinResponse>, tonic::Status>;
     }
     #[derive(Debug)]
     pub struct DatasetSvcServer<T: DatasetSvc> {
@@ -254,6 +406,103 @@ pub mod dataset_svc_server {
                     };
                     Box::pin(fut)
                 }
+                "/dataset.DatasetSvc/Prewarm" => {
+                    #[allow(non_camel_case_types)]
+                    struct PrewarmSvc<T: DatasetSvc>(pub Arc<T>);
+                    impl<T: DatasetSvc> tonic::server::ServerStreamingService<super::PrewarmRequest> for PrewarmSvc<T> {
+                        type Response = super::PrewarmResponse;
+                        type ResponseStream = T::PrewarmStream;
+                        type Future =
+                            BoxFuture<tonic::Response<Self::ResponseStream>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::PrewarmRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).prewarm(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = PrewarmSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec).apply_compression_config(
+                            accept_compression_encodings,
+                            send_compression_encodings,
+                        );
+                        let res = grpc.server_streaming(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/dataset.DatasetSvc/Pin" => {
+                    #[allow(non_camel_case_types)]
+                    struct PinSvc<T: DatasetSvc>(pub Arc<T>);
+                    impl<T: DatasetSvc> tonic::server::ServerStreamingService<super::PinRequest> for PinSvc<T> {
+                        type Response = super::PinResponse;
+                        type ResponseStream = T::PinStream;
+                        type Future =
+                            BoxFuture<tonic::Response<Self::ResponseStream>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::PinRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).pin(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = PinSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec).apply_compression_config(
+                            accept_compression_encodings,
+                            send_compression_encodings,
+                        );
+                        let res = grpc.server_streaming(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/dataset.DatasetSvc/Unpin" => {
+                    #[allow(non_camel_case_types)]
+                    struct UnpinSvc<T: DatasetSvc>(pub Arc<T>);
+                    impl<T: DatasetSvc> tonic::server::UnaryService<super::UnpinRequest> for UnpinSvc<T> {
+                        type Response = super::UnpinResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::UnpinRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).unpin(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = UnpinSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec).apply_compression_config(
+                            accept_compression_encodings,
+                            send_compression_encodings,
+                        );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
                 _ => Box::pin(async move {
                     Ok(http::Response::builder()
                         .status(200)
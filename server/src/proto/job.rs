@@ -1,9 +1,15 @@
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct Data {
-    #[prost(bytes = "vec", tag = "1")]
-    pub bs: ::prost::alloc::vec::Vec<u8>,
+    #[prost(bytes = "bytes", tag = "1")]
+    pub bs: ::prost::bytes::Bytes,
     #[prost(enumeration = "data::DataType", tag = "2")]
     pub ty: i32,
+    /// The shape of a sample, an image is [H, W, C] with C = 3 in RGB order
+    #[prost(uint32, repeated, tag = "3")]
+    pub shape: ::prost::alloc::vec::Vec<u32>,
+    /// The byte length of each sample in bs, it's set when samples are collated
+    #[prost(uint64, repeated, tag = "4")]
+    pub lens: ::prost::alloc::vec::Vec<u64>,
 }
 /// Nested message and enum types in `Data`.
 pub mod data {
@@ -47,9 +53,39 @@ pub struct CreateJobRequest {
     pub dataset_name: ::prost::alloc::string::String,
     #[prost(message, optional, tag = "3")]
     pub condition: ::core::option::Option<Condition>,
+    /// The number of epochs, 0 is treated as 1. Each epoch reads the samples once
+    #[prost(uint32, tag = "4")]
+    pub epochs: u32,
+    /// Read epochs until the job is deleted, epochs is ignored
+    #[prost(bool, tag = "5")]
+    pub infinite: bool,
+    #[prost(enumeration = "Order", tag = "6")]
+    pub order: i32,
+    /// It's used by SEEDED
+    #[prost(uint64, tag = "7")]
+    pub seed: u64,
+    /// Sample with replacement endlessly, there is no epoch and the job never ends.
+    /// epochs and infinite are ignored, and it can't be SEQUENTIAL
+    #[prost(bool, tag = "8")]
+    pub replacement: bool,
+    /// The number of ranks of distributed data parallel training, 0 is treated as 1. Each
+    /// rank reads a disjoint and equally sized part of every epoch like DistributedSampler,
+    /// a SHUFFLED job is shuffled in the same order for all ranks. It can't be replacement
+    #[prost(uint32, tag = "9")]
+    pub ranks: u32,
+    /// Drop the tail of each epoch to make it evenly divisible by ranks, otherwise the
+    /// epoch is padded with its first samples (drop_last of DistributedSampler)
+    #[prost(bool, tag = "10")]
+    pub drop_uneven: bool,
+    /// Resume from a checkpoint of CheckpointJob in the middle of an epoch, the samples read
+    /// before the checkpoint are not read again in the epoch. The job should be created with
+    /// the options of the checkpointed one
+    #[prost(bytes = "vec", tag = "11")]
+    pub checkpoint: ::prost::alloc::vec::Vec<u8>,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct CreateJobResponse {
+    /// The number of samples of an epoch, of each rank
     #[prost(uint64, tag = "1")]
     pub length: u64,
     #[prost(uint64, tag = "3")]
@@ -59,11 +95,31 @@ pub struct CreateJobResponse {
 pub struct NextRequest {
     #[prost(uint64, tag = "1")]
     pub job_id: u64,
+    /// Collate batch_size samples into one response, 0 is treated as 1
+    #[prost(uint32, tag = "2")]
+    pub batch_size: u32,
+    /// Drop the last batch of each epoch if it is smaller than batch_size
+    #[prost(bool, tag = "3")]
+    pub drop_last: bool,
+    /// The rank of the reader, from 0 to ranks - 1
+    #[prost(uint32, tag = "4")]
+    pub rank: u32,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct NextResponse {
+    /// Each field of the samples is concatenated into one Data, e.g. N images
+    /// become one [N, H, W, C] buffer and N labels become one [N] array
     #[prost(message, repeated, tag = "1")]
     pub data: ::prost::alloc::vec::Vec<Data>,
+    /// The number of samples in the batch
+    #[prost(uint32, tag = "2")]
+    pub batch_size: u32,
+    /// The epoch of the samples from 0, a batch never spans two epochs
+    #[prost(uint32, tag = "3")]
+    pub epoch: u32,
+    /// It's the last batch of the epoch
+    #[prost(bool, tag = "4")]
+    pub end_of_epoch: bool,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct DeleteJobRequest {
@@ -74,6 +130,63 @@ pub struct DeleteJobRequest {
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct DeleteJobResponse {}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ResetJobRequest {
+    #[prost(string, tag = "1")]
+    pub name: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub dataset_name: ::prost::alloc::string::String,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ResetJobResponse {
+    /// The number of samples of an epoch, of each rank
+    #[prost(uint64, tag = "1")]
+    pub length: u64,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CheckpointJobRequest {
+    #[prost(string, tag = "1")]
+    pub name: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub dataset_name: ::prost::alloc::string::String,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CheckpointJobResponse {
+    /// An encoded JobCheckpoint
+    #[prost(bytes = "vec", tag = "1")]
+    pub checkpoint: ::prost::alloc::vec::Vec<u8>,
+}
+/// The progress of a job, it's opaque to clients
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct JobCheckpoint {
+    /// The checkpoint of a changed dataset is rejected
+    #[prost(uint64, tag = "1")]
+    pub fingerprint: u64,
+    #[prost(message, repeated, tag = "2")]
+    pub ranks: ::prost::alloc::vec::Vec<job_checkpoint::Rank>,
+}
+/// Nested message and enum types in `JobCheckpoint`.
+pub mod job_checkpoint {
+    #[derive(Clone, PartialEq, ::prost::Message)]
+    pub struct Rank {
+        /// The epoch being read
+        #[prost(uint32, tag = "1")]
+        pub epoch: u32,
+        /// The samples left in the epoch
+        #[prost(uint32, repeated, tag = "2")]
+        pub values: ::prost::alloc::vec::Vec<u32>,
+    }
+}
+/// The order in which a job reads the samples of each epoch
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum Order {
+    Shuffled = 0,
+    /// The order of items selected by the condition, e.g. for evaluation
+    Sequential = 1,
+    /// Shuffled by the seed, the jobs with the same seed read in the same order
+    Seeded = 2,
+}
 #[doc = r" Generated client implementations."]
 pub mod job_svc_client {
     #![allow(unused_variables, dead_code, missing_docs, clippy::let_unit_value)]
@@ -162,6 +275,26 @@ pub mod job_svc_client {
             let path = http::uri::PathAndQuery::from_static("/job.JobSvc/Next");
             self.inner.unary(request.into_request(), path, codec).await
         }
+        #[doc = " Push batches to the client until the end of the job. A rank of a job is streamed by"]
+        #[doc = " at most one client, and Next fails while the rank is streamed"]
+        pub async fn stream(
+            &mut self,
+            request: impl tonic::IntoRequest<super::NextRequest>,
+        ) -> Result<tonic::Response<tonic::codec::Streaming<super::NextResponse>>, tonic::Status>
+        {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/job.JobSvc/Stream");
+            self.inner
+                .server_streaming(request.into_request(), path, codec)
+                .await
+        }
+        #[doc = " Delete all ranks of the job"]
         pub async fn delete_job(
             &mut self,
             request: impl tonic::IntoRequest<super::DeleteJobRequest>,
@@ -176,6 +309,38 @@ pub mod job_svc_client {
             let path = http::uri::PathAndQuery::from_static("/job.JobSvc/DeleteJob");
             self.inner.unary(request.into_request(), path, codec).await
         }
+        #[doc = " Restart all ranks of the job from the first epoch, the samples not read are dropped."]
+        #[doc = " It fails while a rank is streamed. The other jobs of the dataset are not affected"]
+        pub async fn reset_job(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ResetJobRequest>,
+        ) -> Result<tonic::Response<super::ResetJobResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/job.JobSvc/ResetJob");
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        #[doc = " Export the samples left in the current epoch of each rank, which were not returned by"]
+        #[doc = " Next. It fails while a rank is streamed"]
+        pub async fn checkpoint_job(
+            &mut self,
+            request: impl tonic::IntoRequest<super::CheckpointJobRequest>,
+        ) -> Result<tonic::Response<super::CheckpointJobResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/job.JobSvc/CheckpointJob");
+            self.inner.unary(request.into_request(), path, codec).await
+        }
     }
 }
 #[doc = r" Generated server implementations."]
@@ -193,10 +358,34 @@ pub mod job_svc_server {
             &self,
             request: tonic::Request<super::NextRequest>,
         ) -> Result<tonic::Response<super::NextResponse>, tonic::Status>;
+        #[doc = "Server streaming response type for the Stream method."]
+        type StreamStream: futures_core::Stream<Item = Result<super::NextResponse, tonic::Status>>
+            + Send
+            + Sync
+            + 'static;
+        #[doc = " Push batches to the client until the end of the job. A rank of a job is streamed by"]
+        #[doc = " at most one client, and Next fails while the rank is streamed"]
+        async fn stream(
+            &self,
+            request: tonic::Request<super::NextRequest>,
+        ) -> Result<tonic::Response<Self::StreamStream>, tonic::Status>;
+        #[doc = " Delete all ranks of the job"]
         async fn delete_job(
             &self,
             request: tonic::Request<super::DeleteJobRequest>,
         ) -> Result<tonic::Response<super::DeleteJobResponse>, tonic::Status>;
+        #[doc = " Restart all ranks of the job from the first epoch, the samples not read are dropped."]
+        #[doc = " It fails while a rank is streamed. The other jobs of the dataset are not affected"]
+        async fn reset_job(
+            &self,
+            request: tonic::Request<super::ResetJobRequest>,
+        ) -> Result<tonic::Response<super::ResetJobResponse>, tonic::Status>;
+        #[doc = " Export the samples left in the current epoch of each rank, which were not returned by"]
+        #[doc = " Next. It fails while a rank is streamed"]
+        async fn checkpoint_job(
+            &self,
+            request: tonic::Request<super::CheckpointJobRequest>,
+        ) -> Result<tonic::Response<super::CheckpointJobResponse>, tonic::Status>;
     }
     #[derive(Debug)]
     pub struct JobSvcServer<T: JobSvc> {
@@ -299,6 +488,39 @@ pub mod job_svc_server {
                     };
                     Box::pin(fut)
                 }
+                "/job.JobSvc/Stream" => {
+                    #[allow(non_camel_case_types)]
+                    struct StreamSvc<T: JobSvc>(pub Arc<T>);
+                    impl<T: JobSvc> tonic::server::ServerStreamingService<super::NextRequest> for StreamSvc<T> {
+                        type Response = super::NextResponse;
+                        type ResponseStream = T::StreamStream;
+                        type Future =
+                            BoxFuture<tonic::Response<Self::ResponseStream>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::NextRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).stream(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = StreamSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec).apply_compression_config(
+                            accept_compression_encodings,
+                            send_compression_encodings,
+                        );
+                        let res = grpc.server_streaming(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
                 "/job.JobSvc/DeleteJob" => {
                     #[allow(non_camel_case_types)]
                     struct DeleteJobSvc<T: JobSvc>(pub Arc<T>);
@@ -330,6 +552,68 @@ pub mod job_svc_server {
                     };
                     Box::pin(fut)
                 }
+                "/job.JobSvc/ResetJob" => {
+                    #[allow(non_camel_case_types)]
+                    struct ResetJobSvc<T: JobSvc>(pub Arc<T>);
+                    impl<T: JobSvc> tonic::server::UnaryService<super::ResetJobRequest> for ResetJobSvc<T> {
+                        type Response = super::ResetJobResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ResetJobRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).reset_job(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = ResetJobSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec).apply_compression_config(
+                            accept_compression_encodings,
+                            send_compression_encodings,
+                        );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/job.JobSvc/CheckpointJob" => {
+                    #[allow(non_camel_case_types)]
+                    struct CheckpointJobSvc<T: JobSvc>(pub Arc<T>);
+                    impl<T: JobSvc> tonic::server::UnaryService<super::CheckpointJobRequest> for CheckpointJobSvc<T> {
+                        type Response = super::CheckpointJobResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::CheckpointJobRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).checkpoint_job(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = CheckpointJobSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec).apply_compression_config(
+                            accept_compression_encodings,
+                            send_compression_encodings,
+                        );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
                 _ => Box::pin(async move {
                     Ok(http::Response::builder()
                         .status(200)
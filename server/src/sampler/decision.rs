@@ -1,32 +1,33 @@
-use super::sampler_node::NodeRef;
+use super::residency::Residency;
+use super::sampler_node::{Arena, NodeId};
 use std::collections::HashSet;
 use std::hash::{Hash, Hasher};
 use std::iter::FromIterator;
 
 #[derive(Clone)]
 pub struct Decision {
-    node: NodeRef,
+    node: NodeId,
     job_ids: HashSet<u64>,
     compensation: HashSet<u64>,
     item: u32,
 }
 
 impl Hash for Decision {
-    fn hash<H: Hasher>(&self, _state: &mut H) {
-        self.node.get_job_id().hasher();
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.node.hash(state);
     }
 }
 
 impl PartialEq for Decision {
     fn eq(&self, other: &Self) -> bool {
-        self.node.get_job_id() == other.node.get_job_id()
+        self.node == other.node
     }
 }
 
 impl Eq for Decision {}
 
 impl Decision {
-    pub fn new(node: NodeRef, job_ids: HashSet<u64>) -> Self {
+    pub fn new(node: NodeId, job_ids: HashSet<u64>) -> Self {
         Self {
             node,
             job_ids,
@@ -35,22 +36,24 @@ impl Decision {
         }
     }
 
-    pub fn execute(&mut self, mask: &HashSet<u64>) -> u32 {
-        let mut_ref = self.node.get_mut_unchecked();
+    pub fn execute(
+        &mut self,
+        arena: &mut Arena,
+        mask: &HashSet<u64>,
+        residency: &Residency,
+    ) -> u32 {
         self.job_ids = HashSet::from_iter(self.job_ids.difference(mask).cloned());
-        let (ret, comp) = mut_ref.random_choose(&self.job_ids);
+        let (ret, comp) = arena.random_choose(self.node, &self.job_ids, residency);
         self.compensation = comp;
         self.item = ret;
         ret
     }
 
-    pub fn complent(&mut self, mut root: NodeRef) -> bool {
+    pub fn complent(&mut self, arena: &mut Arena, root: NodeId) -> bool {
         if self.compensation.is_empty() {
             return false;
         }
-        root
-            .get_mut_unchecked()
-            .complent(&mut self.compensation, self.item)
+        arena.complent(root, &mut self.compensation, self.item)
     }
 
     pub fn get_jobs(&self) -> HashSet<u64> {
@@ -1,16 +1,167 @@
-use rand::Rng;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
 
-use std::collections::{HashMap, HashSet};
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use super::Sampler;
 
 #[derive(Clone, Default, Debug)]
 pub struct SamplerTree {
     root: HashMap<u64, Vec<u32>>,
     // (job_id, loader size)
     job_set: Vec<(u64, usize)>,
+    // the values decided for each job ahead of sampling, the front is sampled first
+    window: HashMap<u64, VecDeque<u32>>,
+    lookahead: usize,
+    // the jobs which have epochs left after this one
+    epochs: HashMap<u64, Epochs>,
+    // the jobs which are not shuffled by thread_rng
+    orders: HashMap<u64, Chooser>,
+    // the jobs sampled with replacement, their values are never removed
+    replacement: HashSet<u64>,
+}
+
+// The order in which a job reads its values
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Order {
+    Shuffled,
+    // the order of indices, e.g. the order of keys
+    Sequential,
+    // the epochs of jobs with the same seed are shuffled in the same orders
+    Seeded(u64),
+}
+
+impl Default for Order {
+    fn default() -> Self {
+        Order::Shuffled
+    }
+}
+
+// How a job is sampled
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Sampling {
+    // None is infinite
+    pub epochs: Option<usize>,
+    pub order: Order,
+    // An endless stream without epochs, a value may be drawn again at once. It can't be
+    // sequential, and epochs is ignored
+    pub replacement: bool,
+    // The job is a rank of a job of distributed data parallel training
+    pub shard: Option<Shard>,
+}
+
+impl Default for Sampling {
+    fn default() -> Self {
+        Sampling {
+            epochs: Some(1),
+            order: Order::Shuffled,
+            replacement: false,
+            shard: None,
+        }
+    }
+}
+
+impl Sampling {
+    // The values of the job in the epoch, the part of a rank changes with epochs
+    pub fn epoch_values(&self, indices: Vec<u32>, epoch: usize) -> Vec<u32> {
+        match self.shard {
+            Some(shard) => shard.part(&indices, epoch, self.order),
+            None => indices,
+        }
+    }
+
+    // The length of an epoch of the job
+    pub fn epoch_len(&self, len: usize) -> usize {
+        match self.shard {
+            Some(shard) => shard.part_len(len),
+            None => len,
+        }
+    }
 }
 
-fn random_choose(data: &mut Vec<u32>) -> u32 {
-    let mut rng = rand::thread_rng();
+// Where a job is in its epochs, the values are the ones left in the epoch
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Checkpoint {
+    pub epoch: usize,
+    pub values: Vec<u32>,
+}
+
+// A rank reads a disjoint and equally sized part of every epoch like DistributedSampler.
+// The ranks compute the permutation of an epoch from the seed and the epoch, so the ranks
+// of a job must be sequential or seeded with the same seed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Shard {
+    pub rank: usize,
+    pub ranks: usize,
+    // drop the tail of an epoch to make it evenly divisible by ranks, otherwise it's
+    // padded with the samples from its start
+    pub drop_uneven: bool,
+}
+
+impl Shard {
+    // The number of samples of the rank in an epoch of len samples
+    pub fn part_len(&self, len: usize) -> usize {
+        match self.drop_uneven {
+            true => len / self.ranks,
+            false => (len + self.ranks - 1) / self.ranks,
+        }
+    }
+
+    // The values of the rank in the epoch, in the order they are read
+    fn part(&self, indices: &[u32], epoch: usize, order: Order) -> Vec<u32> {
+        let mut values = indices.to_vec();
+        match order {
+            Order::Sequential => {}
+            Order::Seeded(seed) => {
+                values.shuffle(&mut StdRng::seed_from_u64(seed.wrapping_add(epoch as u64)))
+            }
+            Order::Shuffled => panic!("the ranks of a shuffled job need a seed"),
+        }
+        let total = self.part_len(values.len()) * self.ranks;
+        let padding = total.saturating_sub(values.len());
+        let padding = values
+            .iter()
+            .cycle()
+            .take(padding)
+            .cloned()
+            .collect::<Vec<_>>();
+        values.extend(padding);
+        values.truncate(total);
+        values
+            .into_iter()
+            .skip(self.rank)
+            .step_by(self.ranks)
+            .collect()
+    }
+}
+
+#[derive(Clone, Debug)]
+enum Chooser {
+    // the values are reversed, so the first one is popped first
+    Sequential,
+    Seeded(Box<StdRng>),
+}
+
+#[derive(Clone, Debug)]
+struct Epochs {
+    // they are inserted again at the next epoch
+    indices: Vec<u32>,
+    // the epochs left after this one, None is infinite
+    left: Option<usize>,
+    epoch: usize,
+    // the part of a rank is drawn again from the order at each epoch
+    shard: Option<(Shard, Order)>,
+}
+
+fn draw<R: Rng>(data: &mut Vec<u32>, rng: &mut R, replacement: bool) -> u32 {
+    match replacement {
+        true => data[rng.gen_range(0usize..data.len())],
+        false => random_choose(data, rng),
+    }
+}
+
+fn random_choose<R: Rng>(data: &mut Vec<u32>, rng: &mut R) -> u32 {
     let rand_idx = rng.gen_range(0usize..data.len());
     let last_idx = data.len() - 1;
 
@@ -25,31 +176,192 @@ impl SamplerTree {
         SamplerTree {
             root: HashMap::new(),
             job_set: Vec::new(),
+            window: HashMap::new(),
+            lookahead: 0,
+            epochs: HashMap::new(),
+            orders: HashMap::new(),
+            replacement: HashSet::new(),
         }
     }
 
-    pub fn insert(&mut self, indices: Vec<u32>, id: u64) {
-        self.job_set.push((id, indices.len()));
+    // Decide the next `lookahead` values of each job ahead of sampling
+    pub fn set_lookahead(&mut self, lookahead: usize) {
+        self.lookahead = lookahead;
+        let ids = self.root.keys().cloned().collect::<Vec<_>>();
+        for id in ids {
+            self.fill_window(id);
+        }
+    }
+
+    fn fill_window(&mut self, id: u64) {
+        while self.window.get(&id).map_or(0, |w| w.len()) < self.lookahead {
+            match self.choose(id) {
+                Some(v) => self.window.entry(id).or_default().push_back(v),
+                None => break,
+            }
+        }
+    }
+
+    // Take a value of the job in its order
+    fn choose(&mut self, id: u64) -> Option<u32> {
+        let values = self.root.get_mut(&id)?;
+        if values.is_empty() {
+            return None;
+        }
+        let replacement = self.replacement.contains(&id);
+        let v = match self.orders.get_mut(&id) {
+            Some(Chooser::Sequential) => values.pop().unwrap(),
+            Some(Chooser::Seeded(rng)) => draw(values, rng, replacement),
+            None => draw(values, &mut rand::thread_rng(), replacement),
+        };
+        Some(v)
+    }
+
+    fn set_values(&mut self, id: u64, mut indices: Vec<u32>) {
+        if let Some(Chooser::Sequential) = self.orders.get(&id) {
+            indices.reverse();
+        }
         self.root.insert(id, indices);
     }
 
+    fn next_value(&mut self, id: u64) -> u32 {
+        let v = match self.window.get_mut(&id).and_then(|w| w.pop_front()) {
+            Some(v) => v,
+            None => self.choose(id).unwrap(),
+        };
+        self.fill_window(id);
+        v
+    }
+
+    pub fn insert(&mut self, indices: Vec<u32>, id: u64) {
+        self.insert_with(indices, id, Sampling::default());
+    }
+
+    pub fn insert_with(&mut self, indices: Vec<u32>, id: u64, sampling: Sampling) {
+        self.insert_from(indices, id, sampling, None);
+    }
+
+    // Insert a job which resumes from the checkpoint, it reads the values of the checkpoint
+    // in its epoch and then goes on with the next epoch
+    pub fn insert_from(
+        &mut self,
+        indices: Vec<u32>,
+        id: u64,
+        sampling: Sampling,
+        checkpoint: Option<Checkpoint>,
+    ) {
+        if sampling.replacement {
+            assert!(
+                sampling.order != Order::Sequential,
+                "sequential job {} with replacement",
+                id
+            );
+            assert!(sampling.shard.is_none(), "rank {} with replacement", id);
+            self.replacement.insert(id);
+        }
+        let epoch = checkpoint.as_ref().map_or(0, |checkpoint| checkpoint.epoch);
+        let left = sampling
+            .epochs
+            .map(|epochs| epochs.max(1).saturating_sub(epoch + 1));
+        if left != Some(0) && !indices.is_empty() && !sampling.replacement {
+            let epochs = Epochs {
+                indices: indices.clone(),
+                left,
+                epoch,
+                shard: sampling.shard.map(|shard| (shard, sampling.order)),
+            };
+            self.epochs.insert(id, epochs);
+        }
+        match (sampling.shard, sampling.order) {
+            // the part is in the order of the job
+            (Some(_), _) | (None, Order::Sequential) => {
+                self.orders.insert(id, Chooser::Sequential);
+            }
+            (None, Order::Seeded(seed)) => {
+                let rng = Box::new(StdRng::seed_from_u64(seed));
+                self.orders.insert(id, Chooser::Seeded(rng));
+            }
+            (None, Order::Shuffled) => {}
+        }
+        let values = match checkpoint {
+            Some(checkpoint) => checkpoint.values,
+            None => sampling.epoch_values(indices, 0),
+        };
+        self.job_set.push((id, values.len()));
+        self.set_values(id, values);
+        self.fill_window(id);
+    }
+
+    // Insert the indices of the job again if it has sampled all of this epoch and has
+    // epochs left, the indices are returned
+    pub fn next_epoch(&mut self, id: u64) -> Option<Vec<u32>> {
+        let size = self.job_set.iter_mut().find(|(job_id, _)| *job_id == id)?;
+        if size.1 != 0 {
+            return None;
+        }
+        let epochs = self.epochs.get_mut(&id)?;
+        epochs.epoch += 1;
+        let indices = match epochs.shard {
+            Some((shard, order)) => shard.part(&epochs.indices, epochs.epoch, order),
+            None => epochs.indices.clone(),
+        };
+        if let Some(left) = &mut epochs.left {
+            *left -= 1;
+            if *left == 0 {
+                self.epochs.remove(&id);
+            }
+        }
+        size.1 = indices.len();
+        self.set_values(id, indices.clone());
+        self.fill_window(id);
+        Some(indices)
+    }
+
+    pub fn has_next_epoch(&self, id: u64) -> bool {
+        self.epochs.contains_key(&id)
+    }
+
     pub fn delete(&mut self, id: u64) {
-        todo!()
+        self.root.remove(&id);
+        self.window.remove(&id);
+        self.epochs.remove(&id);
+        self.orders.remove(&id);
+        self.replacement.remove(&id);
+        self.job_set.retain(|(job_id, _)| *job_id != id);
+    }
+
+    // The value -> the number of samples before the value is taken by a job,
+    // the nearest one is returned if it's decided for several jobs
+    pub fn lookahead(&self) -> HashMap<u32, usize> {
+        let mut res = HashMap::new();
+        for window in self.window.values() {
+            for (distance, v) in window.iter().enumerate() {
+                let d = res.entry(*v).or_insert(distance);
+                *d = distance.min(*d);
+            }
+        }
+        res
     }
 
     pub fn sample(&mut self, mask: &HashSet<u64>) -> HashMap<u32, HashSet<u64>> {
         let mut res = HashMap::new();
+        let mut ids = Vec::new();
         for (id, size) in self.job_set.iter_mut() {
             if mask.contains(id) || *size == 0 {
                 continue;
             }
-            *size -= 1;
-            let sample_res = random_choose(&mut self.root.get_mut(id).unwrap());
+            // a job with replacement never shrinks
+            if !self.replacement.contains(id) {
+                *size -= 1;
+            }
+            ids.push(*id);
+        }
+        for id in ids {
+            let sample_res = self.next_value(id);
             if !res.contains_key(&sample_res) {
                 res.insert(sample_res, HashSet::new());
             }
-            res.get_mut(&sample_res).unwrap().insert(*id);
-            
+            res.get_mut(&sample_res).unwrap().insert(id);
         }
         res
     }
@@ -57,15 +369,23 @@ impl SamplerTree {
     pub fn sample_with_buffer(&mut self, mask: &HashSet<u64>) -> HashMap<u32, HashSet<u64>> {
         let mut res = HashMap::new();
         
+        let mut ids = Vec::new();
         for (id, size) in self.job_set.iter_mut() {
             if mask.contains(id) || *size == 0 {
                 continue;
             }
-            *size -= 1;
-            let sample_res = random_choose(&mut self.root.get_mut(id).unwrap());
-            let mut v = HashSet::new();
-            v.insert(*id);
-            res.insert(sample_res, v);
+            // a job with replacement never shrinks
+            if !self.replacement.contains(id) {
+                *size -= 1;
+            }
+            ids.push(*id);
+        }
+        for id in ids {
+            let sample_res = self.next_value(id);
+            // jobs may choose the same data in a round
+            res.entry(sample_res)
+                .or_insert_with(HashSet::new)
+                .insert(id);
         }
         res
     }
@@ -80,7 +400,316 @@ impl SamplerTree {
         capacity != 0
     }
 
+    // The values which have not been sampled for the job, they are all values of a job
+    // with replacement since the values in its window are drawn from them
     pub fn get_job_values(&self, job_id: u64) -> Vec<u32> {
-        Vec::default()
+        let mut res = self.root.get(&job_id).cloned().unwrap_or_default();
+        if self.replacement.contains(&job_id) {
+            return res;
+        }
+        if let Some(window) = self.window.get(&job_id) {
+            res.extend(window.iter());
+        }
+        res
+    }
+}
+
+// Each job is shuffled on its own, and the jobs which read the same value in a round
+// share the read
+impl Sampler for SamplerTree {
+    fn insert(
+        &mut self,
+        indices: Vec<u32>,
+        id: u64,
+        sampling: Sampling,
+        checkpoint: Option<Checkpoint>,
+    ) {
+        self.insert_from(indices, id, sampling, checkpoint);
+    }
+
+    fn delete(&mut self, id: u64) {
+        SamplerTree::delete(self, id);
+    }
+
+    // The cache is not considered
+    fn sample(
+        &mut self,
+        mask: &HashSet<u64>,
+        _resident: Option<&dyn Fn(u32) -> bool>,
+    ) -> HashMap<u32, HashSet<u64>> {
+        self.sample_with_buffer(mask)
+    }
+
+    fn set_lookahead(&mut self, lookahead: usize) {
+        SamplerTree::set_lookahead(self, lookahead);
+    }
+
+    fn lookahead(&self) -> HashMap<u32, usize> {
+        SamplerTree::lookahead(self)
+    }
+
+    fn get_job_values(&self, id: u64) -> Vec<u32> {
+        SamplerTree::get_job_values(self, id)
+    }
+
+    fn next_epoch(&mut self, id: u64) -> Option<Vec<u32>> {
+        SamplerTree::next_epoch(self, id)
+    }
+
+    fn has_next_epoch(&self, id: u64) -> bool {
+        SamplerTree::has_next_epoch(self, id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sample_collision() {
+        let mut tree = SamplerTree::new();
+        tree.insert(vec![7], 0);
+        tree.insert(vec![7, 8], 1);
+        tree.insert(vec![7], 2);
+        let mask = HashSet::from([2]);
+        let mut res = tree.sample_with_buffer(&mask);
+        // job 1 may choose 8
+        if res.contains_key(&8) {
+            assert_eq!(res.remove(&8).unwrap(), HashSet::from([1]));
+            res.entry(7).or_insert_with(HashSet::new).insert(1);
+        }
+        assert_eq!(res, HashMap::from([(7, HashSet::from([0, 1]))]));
+        assert_eq!(tree.get_job_values(2), vec![7]);
+        tree.delete(2);
+        assert!(tree.get_job_values(2).is_empty());
+    }
+
+    #[test]
+    fn test_epochs() {
+        let mut tree = SamplerTree::new();
+        let sampling = |epochs| Sampling {
+            epochs,
+            ..Default::default()
+        };
+        tree.insert_with((0..4).collect(), 0, sampling(Some(2)));
+        tree.insert_with((0..4).collect(), 1, sampling(None));
+        tree.insert_with((0..4).collect(), 2, sampling(Some(1)));
+        assert!(tree.has_next_epoch(0) && tree.has_next_epoch(1) && !tree.has_next_epoch(2));
+        let mask = HashSet::new();
+        for epoch in 0..3 {
+            // the epoch is not over
+            assert_eq!(tree.next_epoch(0), None);
+            let mut seen = HashMap::<u64, Vec<u32>>::new();
+            for _ in 0..4 {
+                for (v, ids) in tree.sample_with_buffer(&mask) {
+                    for id in ids {
+                        seen.entry(id).or_default().push(v);
+                    }
+                }
+            }
+            assert!(tree.sample_with_buffer(&mask).is_empty());
+            for values in seen.values_mut() {
+                values.sort_unstable();
+                assert_eq!(*values, (0..4).collect::<Vec<_>>());
+            }
+            assert_eq!(seen.len(), [3, 2, 1][epoch]);
+            let next = tree.next_epoch(0);
+            assert_eq!(next.is_some(), epoch == 0);
+            assert_eq!(tree.next_epoch(1), Some((0..4).collect()));
+            assert_eq!(tree.next_epoch(2), None);
+        }
+        assert!(!tree.has_next_epoch(0) && tree.has_next_epoch(1));
+        tree.delete(1);
+        assert!(!tree.has_next_epoch(1));
+    }
+
+    #[test]
+    fn test_order() {
+        for lookahead in [0, 3] {
+            let mut tree = SamplerTree::new();
+            tree.set_lookahead(lookahead);
+            let sampling = |order| Sampling {
+                epochs: Some(2),
+                order,
+                ..Default::default()
+            };
+            tree.insert_with((0..8).collect(), 0, sampling(Order::Sequential));
+            tree.insert_with((0..8).collect(), 1, sampling(Order::Seeded(7)));
+            tree.insert_with((0..8).collect(), 2, sampling(Order::Seeded(7)));
+            tree.insert_with((0..8).collect(), 3, sampling(Order::Shuffled));
+            let mask = HashSet::new();
+            let mut seen = HashMap::<u64, Vec<u32>>::new();
+            for _ in 0..2 {
+                for _ in 0..8 {
+                    for (v, ids) in tree.sample_with_buffer(&mask) {
+                        for id in ids {
+                            seen.entry(id).or_default().push(v);
+                        }
+                    }
+                }
+                for id in 0..4 {
+                    tree.next_epoch(id);
+                }
+            }
+            let sequential = (0..8).chain(0..8).collect::<Vec<_>>();
+            assert_eq!(seen[&0], sequential);
+            assert_eq!(seen[&1], seen[&2]);
+            // the epochs are shuffled differently
+            assert_ne!(seen[&1][..8], seen[&1][8..]);
+            let mut shuffled = seen[&3].clone();
+            shuffled.sort_unstable();
+            assert_eq!(shuffled, (0..8).flat_map(|v| [v, v]).collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn test_replacement() {
+        for lookahead in [0, 3] {
+            let mut tree = SamplerTree::new();
+            tree.set_lookahead(lookahead);
+            let replacement = Sampling {
+                replacement: true,
+                ..Default::default()
+            };
+            tree.insert((0..4).collect(), 0);
+            tree.insert_with((0..4).collect(), 1, replacement);
+            let mask = HashSet::new();
+            let mut seen = HashMap::<u64, Vec<u32>>::new();
+            for _ in 0..64 {
+                for (v, ids) in tree.sample_with_buffer(&mask) {
+                    for id in ids {
+                        seen.entry(id).or_default().push(v);
+                    }
+                }
+            }
+            assert_eq!(seen[&0].len(), 4);
+            // it goes on after the other job is finished and draws values again
+            assert_eq!(seen[&1].len(), 64);
+            assert!(seen[&1].iter().all(|v| *v < 4));
+            let mut distinct = seen[&1].clone();
+            distinct.sort_unstable();
+            distinct.dedup();
+            assert!(distinct.len() < seen[&1].len());
+            let mut values = tree.get_job_values(1);
+            values.sort_unstable();
+            assert_eq!(values, vec![0, 1, 2, 3]);
+            assert!(!tree.has_next_epoch(1));
+        }
+    }
+
+    #[test]
+    fn test_shard() {
+        let mask = HashSet::new();
+        for (drop_uneven, len) in [(false, 4), (true, 3)] {
+            let mut tree = SamplerTree::new();
+            let ranks = 3;
+            for rank in 0..ranks {
+                let shard = Shard {
+                    rank,
+                    ranks,
+                    drop_uneven,
+                };
+                assert_eq!(shard.part_len(10), len);
+                let sampling = Sampling {
+                    epochs: Some(2),
+                    order: Order::Seeded(7),
+                    shard: Some(shard),
+                    ..Default::default()
+                };
+                tree.insert_with((0..10).collect(), rank as u64, sampling);
+            }
+            let mut epochs = Vec::new();
+            for _ in 0..2 {
+                let mut seen = HashMap::<u64, Vec<u32>>::new();
+                for _ in 0..len {
+                    for (v, ids) in tree.sample_with_buffer(&mask) {
+                        for id in ids {
+                            seen.entry(id).or_default().push(v);
+                        }
+                    }
+                }
+                assert!(tree.sample_with_buffer(&mask).is_empty());
+                // the permutation is split among the ranks in turn
+                let mut perm = Vec::new();
+                for i in 0..len {
+                    for rank in 0..ranks as u64 {
+                        assert_eq!(seen[&rank].len(), len);
+                        perm.push(seen[&rank][i]);
+                    }
+                }
+                let mut values = perm.clone();
+                values.sort_unstable();
+                values.dedup();
+                match drop_uneven {
+                    // the tail is dropped
+                    true => assert_eq!(values.len(), 9),
+                    // padded with the start of the epoch
+                    false => {
+                        assert_eq!(values, (0..10).collect::<Vec<_>>());
+                        assert_eq!(perm[10..], perm[..2]);
+                    }
+                }
+                epochs.push(perm);
+                for rank in 0..ranks as u64 {
+                    tree.next_epoch(rank);
+                }
+            }
+            assert_ne!(epochs[0], epochs[1]);
+        }
+    }
+
+    #[test]
+    fn test_checkpoint() {
+        let mut tree = SamplerTree::new();
+        let sampling = Sampling {
+            epochs: Some(3),
+            order: Order::Sequential,
+            ..Default::default()
+        };
+        let checkpoint = Checkpoint {
+            epoch: 1,
+            values: vec![1, 3, 5],
+        };
+        tree.insert_from((0..6).collect(), 0, sampling, Some(checkpoint));
+        let mask = HashSet::new();
+        let mut seen = Vec::new();
+        while let Some((v, _)) = tree.sample_with_buffer(&mask).into_iter().next() {
+            seen.push(v);
+        }
+        assert_eq!(seen, vec![1, 3, 5]);
+        // the last epoch is read in full
+        assert_eq!(tree.next_epoch(0), Some((0..6).collect()));
+        assert!(!tree.has_next_epoch(0));
+        assert_eq!(tree.get_job_values(0).len(), 6);
+    }
+
+    #[test]
+    fn test_lookahead() {
+        let mut tree = SamplerTree::new();
+        tree.set_lookahead(4);
+        tree.insert((0..10).collect(), 0);
+        tree.insert((0..10).collect(), 1);
+        let mask = HashSet::new();
+        let mut seen = HashMap::<u64, Vec<u32>>::new();
+        for _ in 0..10 {
+            let lookahead = tree.lookahead();
+            let res = tree.sample_with_buffer(&mask);
+            // the values sampled now are the nearest in the window
+            for v in res.keys() {
+                assert_eq!(lookahead[v], 0);
+            }
+            for (v, ids) in res {
+                for id in ids {
+                    seen.entry(id).or_default().push(v);
+                }
+            }
+        }
+        assert!(tree.lookahead().is_empty());
+        for id in 0..2 {
+            let mut values = seen.remove(&id).unwrap();
+            values.sort();
+            assert_eq!(values, (0..10).collect::<Vec<_>>());
+            assert!(tree.get_job_values(id).is_empty());
+        }
     }
 }
@@ -2,5 +2,71 @@ mod sampler_node;
 pub mod sampler_tree;
 mod decision;
 mod values_set;
+mod residency;
 pub mod isa_sampler_tree;
 
+use isa_sampler_tree::{Checkpoint, Sampling};
+use std::collections::{HashMap, HashSet};
+use std::fmt::Debug;
+
+// How far the jobs of a sampler are apart
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Skew {
+    // the most values held for a job which can't take them, i.e. the rounds it lags behind
+    pub max_lag: usize,
+    // the values held for all jobs
+    pub buffered: usize,
+    // the jobs sampled apart from the others since they lag too far behind
+    pub detached: usize,
+    // how many times jobs have been detached and merged again
+    pub detaches: u64,
+    pub merges: u64,
+}
+
+// It decides which values the jobs of a dataset read in each round. The sampler of a
+// dataset is chosen when the dataset is created, see CreateDatasetRequest.sampler
+pub trait Sampler: Debug + Send {
+    // Add a job which reads the indices, or the values left in the epoch of the checkpoint
+    fn insert(
+        &mut self,
+        indices: Vec<u32>,
+        id: u64,
+        sampling: Sampling,
+        checkpoint: Option<Checkpoint>,
+    );
+
+    fn delete(&mut self, id: u64);
+
+    // The value -> the jobs which read it in this round, the masked jobs are not sampled.
+    // resident tells whether a value is in cache, so the cached values may be preferred,
+    // and None samples the values uniformly
+    fn sample(
+        &mut self,
+        mask: &HashSet<u64>,
+        resident: Option<&dyn Fn(u32) -> bool>,
+    ) -> HashMap<u32, HashSet<u64>>;
+
+    // Decide the values of each job lookahead rounds ahead, so that the cache knows them
+    fn set_lookahead(&mut self, lookahead: usize);
+
+    // The value -> the number of rounds before it's read
+    fn lookahead(&self) -> HashMap<u32, usize>;
+
+    // The values which the job has not read
+    fn get_job_values(&self, id: u64) -> Vec<u32>;
+
+    // Insert the values of the next epoch if the job has read this one, they are returned
+    fn next_epoch(&mut self, id: u64) -> Option<Vec<u32>>;
+
+    fn has_next_epoch(&self, id: u64) -> bool;
+
+    // Whether a job can be sampled so, e.g. in a sequential order
+    fn supports(&self, _sampling: &Sampling) -> bool {
+        true
+    }
+
+    // The values of a job are never held by default
+    fn skew(&self) -> Skew {
+        Skew::default()
+    }
+}
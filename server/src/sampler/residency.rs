@@ -0,0 +1,96 @@
+use super::sampler_node::NodeId;
+use super::values_set::ValueSet;
+use rand::{thread_rng, Rng};
+use std::collections::HashMap;
+
+// The number of values probed to estimate how much of a node is cached
+const PROBES: usize = 8;
+
+fn nothing_cached(_: u32) -> bool {
+    false
+}
+
+// It weighs the values by whether they are in cache: a cached value weighs 1 + bias and
+// the others weigh 1. A job chooses a node by the weights of the nodes, which are
+// estimated from PROBES values of each node, and then a value of the node exactly in
+// proportion to its weight. So a job prefers the cached values it has not read, i.e. the
+// ones read by the jobs ahead.
+//
+// The weights only depend on which values are cached rather than the values themselves,
+// so the order of each job is still a uniform permutation as long as only the reads of
+// the jobs decide what is cached: the jobs read the same indices, a value is released
+// once no job will read it (PolicyType::RefCnt), and no sample is prewarmed or pinned.
+// A job follows the orders of the others in the cache, which are uniform. Joader samples
+// uniformly otherwise, and a bias of 0 keeps the order uniform in any case
+pub struct Residency<'a> {
+    resident: &'a dyn Fn(u32) -> bool,
+    bias: f64,
+    // the weights of the nodes in this round
+    weights: HashMap<NodeId, f64>,
+}
+
+impl<'a> Residency<'a> {
+    pub fn new(resident: &'a dyn Fn(u32) -> bool, bias: f64) -> Self {
+        Residency {
+            resident,
+            bias: bias.max(0.0),
+            weights: HashMap::new(),
+        }
+    }
+
+    pub fn uniform() -> Residency<'static> {
+        Residency::new(&nothing_cached, 0.0)
+    }
+
+    pub fn is_uniform(&self) -> bool {
+        self.bias == 0.0
+    }
+
+    fn weigh(&self, v: u32) -> f64 {
+        match (self.resident)(v) {
+            true => 1.0 + self.bias,
+            false => 1.0,
+        }
+    }
+
+    // The values of a small node are all weighed, and a few of a large one are probed
+    fn probes(values: &ValueSet) -> Vec<u32> {
+        match values.len() <= PROBES {
+            true => values.as_vec(),
+            false => (0..PROBES).map(|_| values.random()).collect(),
+        }
+    }
+
+    // The weight of the values of the node, it's estimated by the probes, which are drawn
+    // with replacement
+    pub fn weight(&mut self, id: NodeId, values: &ValueSet) -> f64 {
+        if self.is_uniform() || values.len() == 0 {
+            return values.len() as f64;
+        }
+        if let Some(weight) = self.weights.get(&id) {
+            return *weight;
+        }
+        let probes = Residency::probes(values);
+        let mean = probes.iter().map(|v| self.weigh(*v)).sum::<f64>() / probes.len() as f64;
+        let weight = values.len() as f64 * mean;
+        self.weights.insert(id, weight);
+        weight
+    }
+
+    // Remove a value of the node in proportion to its weight: a value drawn uniformly is
+    // kept with the probability of its weight over the largest one, so a cached value is
+    // exactly 1 + bias times as likely as the others
+    pub fn pick(&self, values: &mut ValueSet) -> u32 {
+        if self.is_uniform() {
+            return values.random_pick();
+        }
+        let mut rng = thread_rng();
+        loop {
+            let v = values.random();
+            if rng.gen::<f64>() * (1.0 + self.bias) < self.weigh(v) {
+                values.reset(v);
+                return v;
+            }
+        }
+    }
+}
@@ -1,14 +1,14 @@
-use super::{decision::Decision, values_set::ValueSet};
+use super::{decision::Decision, residency::Residency, values_set::ValueSet};
 use rand::{distributions::WeightedIndex, prelude::Distribution, thread_rng};
-use std::{collections::HashSet, iter::FromIterator, sync::Arc};
+use std::{collections::HashSet, iter::FromIterator};
 #[derive(Clone, Debug)]
 pub struct Node {
     values_set: ValueSet,
     // The LoaderId set which hold the data in the Node
     job_id: HashSet<u64>,
     // The left is smaller task, and the right is larger
-    left: Option<NodeRef>,
-    right: Option<NodeRef>,
+    left: Option<NodeId>,
+    right: Option<NodeId>,
 }
 
 #[inline]
@@ -16,29 +16,26 @@ fn random_probility() -> f32 {
     rand::random::<f32>()
 }
 
-fn random_weight(weights: &[usize]) -> usize {
+fn random_weight(weights: &[f64]) -> usize {
     let dist = WeightedIndex::new(weights).unwrap();
     dist.sample(&mut thread_rng())
 }
-pub type NodeRef = Arc<Node>;
 
-impl Node {
-    #[inline]
-    pub fn get_mut_unchecked<'a>(self: &'a mut NodeRef) -> &'a mut Self {
-        unsafe { Arc::get_mut_unchecked(self) }
-    }
+// The index of a node in its arena
+pub type NodeId = usize;
 
-    pub fn new(values: Vec<u32>, job_id: HashSet<u64>) -> NodeRef {
+impl Node {
+    pub fn new(values: Vec<u32>, job_id: HashSet<u64>) -> Self {
         let mut values_set = ValueSet::new();
         for v in values {
             values_set.set(v);
         }
-        Arc::new(Node {
+        Node {
             values_set,
             job_id,
             left: None,
             right: None,
-        })
+        }
     }
 
     pub fn get_job_id(&self) -> &HashSet<u64> {
@@ -46,27 +43,95 @@ impl Node {
     }
 
     fn len(&self) -> usize {
-        return self.values_set.len();
+        self.values_set.len()
+    }
+}
+
+// The nodes of a tree are owned by the arena and linked by their ids, so that a node is
+// mutated through the arena instead of through a shared reference. The slots of removed
+// nodes are reused
+#[derive(Clone, Debug, Default)]
+pub struct Arena {
+    nodes: Vec<Option<Node>>,
+    free: Vec<NodeId>,
+}
+
+impl Arena {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn alloc(&mut self, node: Node) -> NodeId {
+        match self.free.pop() {
+            Some(id) => {
+                self.nodes[id] = Some(node);
+                id
+            }
+            None => {
+                self.nodes.push(Some(node));
+                self.nodes.len() - 1
+            }
+        }
+    }
+
+    // Remove the node, its children are kept
+    fn remove(&mut self, id: NodeId) -> Node {
+        let node = self.nodes[id].take().unwrap();
+        self.free.push(id);
+        node
+    }
+
+    fn remove_tree(&mut self, id: NodeId) {
+        let node = self.remove(id);
+        for child in node.left.into_iter().chain(node.right) {
+            self.remove_tree(child);
+        }
+    }
+
+    pub fn get(&self, id: NodeId) -> &Node {
+        self.nodes[id].as_ref().unwrap()
+    }
+
+    fn get_mut(&mut self, id: NodeId) -> &mut Node {
+        self.nodes[id].as_mut().unwrap()
+    }
+
+    // Two different nodes at once
+    fn pair_mut(&mut self, a: NodeId, b: NodeId) -> (&mut Node, &mut Node) {
+        assert_ne!(a, b);
+        let (lo, hi) = self.nodes.split_at_mut(a.max(b));
+        let (first, second) = (lo[a.min(b)].as_mut().unwrap(), hi[0].as_mut().unwrap());
+        match a < b {
+            true => (first, second),
+            false => (second, first),
+        }
+    }
+
+    // The number of nodes in the arena
+    pub fn len(&self) -> usize {
+        self.nodes.len() - self.free.len()
     }
 
-    fn min_task_length(&self) -> usize {
-        let mut l = self.len();
-        if let Some(left) = &self.left {
-            l += left.len();
+    fn min_task_length(&self, id: NodeId) -> usize {
+        let node = self.get(id);
+        let mut l = node.len();
+        if let Some(left) = node.left {
+            l += self.get(left).len();
         }
         l
     }
 
-    fn intersect_update(&mut self, other: &mut Node) -> NodeRef {
-        let values_set = self.values_set.intersection(&other.values_set);
-        let job_id = self
+    fn intersect_update(&mut self, id: NodeId, other: NodeId) -> NodeId {
+        let (node, other) = self.pair_mut(id, other);
+        let values_set = node.values_set.intersection(&other.values_set);
+        let job_id = node
             .job_id
             .union(&other.job_id)
             .cloned()
             .collect::<HashSet<_>>();
-        self.values_set = self.values_set.difference(&values_set);
+        node.values_set = node.values_set.difference(&values_set);
         other.values_set = other.values_set.difference(&values_set);
-        Arc::new(Node {
+        self.alloc(Node {
             values_set,
             job_id,
             left: None,
@@ -74,123 +139,167 @@ impl Node {
         })
     }
 
-    fn pushdown(&mut self) -> (Option<NodeRef>, Option<NodeRef>) {
-        let mut left = self.left.clone().unwrap();
-        let mut right = self.right.clone().unwrap();
-        let l = left.get_mut_unchecked();
-        l.values_set = l.values_set.union(&self.values_set);
-        let r = right.get_mut_unchecked();
-        r.values_set = r.values_set.union(&self.values_set);
-        return (Some(left), Some(right));
+    fn pushdown(&mut self, id: NodeId) -> (NodeId, NodeId) {
+        let node = self.get(id);
+        let (left, right) = (node.left.unwrap(), node.right.unwrap());
+        let values_set = node.values_set.clone();
+        let l = self.get_mut(left);
+        l.values_set = l.values_set.union(&values_set);
+        let r = self.get_mut(right);
+        r.values_set = r.values_set.union(&values_set);
+        (left, right)
     }
 
-    pub fn insert(self: &mut NodeRef, mut other: NodeRef) -> NodeRef {
-        let mut new_root;
-        let root_ref = self.get_mut_unchecked();
-        if other.len() <= root_ref.min_task_length() {
-            new_root = root_ref.intersect_update(other.get_mut_unchecked());
-            let mut new_root_ref = new_root.get_mut_unchecked();
-            new_root_ref.left = Some(other.clone());
-            new_root_ref.right = Some(self.clone());
+    // Insert the tree of other into the tree of root, the new root is returned
+    pub fn insert(&mut self, root: NodeId, other: NodeId) -> NodeId {
+        let new_root;
+        if self.get(other).len() <= self.min_task_length(root) {
+            new_root = self.intersect_update(root, other);
+            let new_root_ref = self.get_mut(new_root);
+            new_root_ref.left = Some(other);
+            new_root_ref.right = Some(root);
         } else {
-            new_root = root_ref.intersect_update(other.get_mut_unchecked());
-            let mut new_root_ref = new_root.get_mut_unchecked();
-            if let None = root_ref.left {
-                new_root_ref.left = Some(self.clone());
-                new_root_ref.right = Some(other.clone());
+            new_root = self.intersect_update(root, other);
+            if self.get(root).left.is_none() {
+                let new_root_ref = self.get_mut(new_root);
+                new_root_ref.left = Some(root);
+                new_root_ref.right = Some(other);
             } else {
-                let (left_tree, right_tree) = Node::pushdown(root_ref);
-                new_root_ref.left = left_tree;
-                new_root_ref.right = Some(right_tree.unwrap().insert(other));
+                let (left_tree, right_tree) = self.pushdown(root);
+                // the values of root are pushed down to its children
+                self.remove(root);
+                let right_tree = self.insert(right_tree, other);
+                let new_root_ref = self.get_mut(new_root);
+                new_root_ref.left = Some(left_tree);
+                new_root_ref.right = Some(right_tree);
             }
         }
-        return new_root;
+        new_root
     }
 
-    pub fn get_job_values(&self, job_id: u64) -> Vec<u32> {
+    pub fn get_job_values(&self, id: NodeId, job_id: u64) -> Vec<u32> {
+        let node = self.get(id);
         let mut res = Vec::<u32>::new();
-        if self.job_id.contains(&job_id) {
-            res.append(&mut self.values_set.as_vec());
-            if let Some(left) = &self.left {
-                let mut left_v = left.get_job_values(job_id);
+        if node.job_id.contains(&job_id) {
+            res.append(&mut node.values_set.as_vec());
+            if let Some(left) = node.left {
+                let mut left_v = self.get_job_values(left, job_id);
                 res.append(&mut left_v);
             }
-            if let Some(right) = &self.right {
-                let mut right_v = right.get_job_values(job_id);
+            if let Some(right) = node.right {
+                let mut right_v = self.get_job_values(right, job_id);
                 res.append(&mut right_v);
             }
         }
         res
     }
 
-    pub fn get_job_set(&self, job_set: &mut Vec<(u64, usize)>, mut pre_len: usize) {
-        pre_len += self.len();
-        job_set.push((*self.job_id.iter().next().unwrap(), pre_len));
-        if let Some(right) = &self.right {
-            let left = self.left.as_ref().unwrap();
+    // Like get_job_set, the lengths of jobs are the weights of their values
+    pub fn get_job_weights(
+        &self,
+        id: NodeId,
+        residency: &mut Residency,
+        job_set: &mut Vec<(u64, f64)>,
+        mut pre_weight: f64,
+    ) {
+        let node = self.get(id);
+        pre_weight += residency.weight(id, &node.values_set);
+        match (node.left, node.right) {
+            (Some(left), Some(right)) => {
+                let left_node = self.get(left);
+                let weight = pre_weight + residency.weight(left, &left_node.values_set);
+                job_set.push((*left_node.job_id.iter().next().unwrap(), weight));
+                self.get_job_weights(right, residency, job_set, pre_weight);
+            }
+            _ => job_set.push((*node.job_id.iter().next().unwrap(), pre_weight)),
+        }
+    }
+
+    pub fn get_job_set(&self, id: NodeId, job_set: &mut Vec<(u64, usize)>, mut pre_len: usize) {
+        let node = self.get(id);
+        pre_len += node.len();
+        job_set.push((*node.job_id.iter().next().unwrap(), pre_len));
+        if let Some(right) = node.right {
+            let left = self.get(node.left.unwrap());
             job_set.pop();
             job_set.push((
                 *left.get_job_id().iter().next().unwrap(),
                 pre_len + left.len(),
             ));
-            right.get_job_set(job_set, pre_len);
+            self.get_job_set(right, job_set, pre_len);
         }
     }
 
-    pub fn delete(self: &mut NodeRef, id: u64) -> Option<NodeRef> {
-        let mut_ref = self.get_mut_unchecked();
-        mut_ref.job_id.remove(&id);
-        if mut_ref.job_id.is_empty() {
+    // Delete the job from the tree of id, the new root is returned
+    pub fn delete(&mut self, id: NodeId, job_id: u64) -> Option<NodeId> {
+        let node = self.get_mut(id);
+        node.job_id.remove(&job_id);
+        if node.job_id.is_empty() {
+            self.remove_tree(id);
             return None;
         }
 
-        if let Some(left) = &mut mut_ref.left {
-            mut_ref.left = left.delete(id);
-        }
-
-        if let Some(mut right) = mut_ref.right.clone() {
-            if let None = mut_ref.left {
-                mut_ref.values_set = mut_ref.values_set.union(&right.values_set);
-                mut_ref.right = right.right.clone();
-                mut_ref.left = right.left.clone();
-            } else {
-                mut_ref.right = right.delete(id);
+        let (left, right) = (node.left, node.right);
+        let left = left.and_then(|left| self.delete(left, job_id));
+        let right = right.and_then(|right| self.delete(right, job_id));
+        match (left, right) {
+            // the job is deleted from one side, so the node takes the place of the other one
+            (Some(child), None) | (None, Some(child)) => {
+                let child = self.remove(child);
+                let node = self.get_mut(id);
+                node.values_set = node.values_set.union(&child.values_set);
+                node.job_id = child.job_id;
+                node.left = child.left;
+                node.right = child.right;
+            }
+            _ => {
+                let node = self.get_mut(id);
+                node.left = left;
+                node.right = right;
             }
         }
-        return Some((*self).clone());
+        Some(id)
     }
 }
 
 // sampling
-impl Node {
+impl Arena {
+    // Each job chooses a node with the probability of the weight of its values in the node,
+    // see Residency. The jobs which choose the same node read the same value
     pub fn decide(
-        self: &mut NodeRef,
-        jobs: &mut Vec<(u64, usize)>,
+        &self,
+        id: NodeId,
+        jobs: &mut Vec<(u64, f64)>,
         decisions: &mut Vec<Decision>,
-        mut node_set: Vec<NodeRef>,
+        mut node_set: Vec<NodeId>,
+        residency: &mut Residency,
     ) {
         if jobs.is_empty() {
             return;
         }
-        if self.len() != 0 {
-            node_set.push(self.clone());
+        let node = self.get(id);
+        if node.len() != 0 {
+            node_set.push(id);
         }
 
         // push down and add self in node set
         let job_id: HashSet<_> = HashSet::from_iter(jobs.iter().map(|(id, _)| *id));
-        if !self.job_id.eq(&job_id) {
-            if let Some(mut right) = self.right.clone() {
-                right.decide(jobs, decisions, node_set);
+        if !node.job_id.eq(&job_id) {
+            if let Some(right) = node.right {
+                self.decide(right, jobs, decisions, node_set, residency);
             }
             return;
         }
 
-        let common = node_set.iter().fold(0, |x, n| x + n.len());
+        let common = node_set
+            .iter()
+            .map(|n| residency.weight(*n, &self.get(*n).values_set))
+            .sum::<f64>();
         let mut last_common = common;
         let jobs_cloned = jobs.clone();
         let mut decided_loader = HashSet::new();
         for (id, len) in jobs_cloned.iter().cloned() {
-            if random_probility() >= (last_common as f32) / (len as f32) {
+            if random_probility() as f64 >= last_common / len {
                 break;
             }
             //choose current node
@@ -203,18 +312,19 @@ impl Node {
             //The first task choose diff
             let mut job_set = HashSet::new();
             job_set.insert(jobs[0].0);
+            let left = node.left.unwrap();
             log::trace!(
                 "Dicide: {:?} decide node [{:?}, {:?}]",
                 job_set,
-                self.left.as_ref().unwrap().get_job_id(),
-                self.left.as_ref().unwrap().values_set.as_vec(),
+                self.get(left).get_job_id(),
+                self.get(left).values_set.as_vec(),
             );
             jobs.remove(0);
-            let decision = Decision::new(self.left.clone().unwrap(), job_set);
+            let decision = Decision::new(left, job_set);
             decisions.push(decision);
         } else {
             // Some tasks choose intersection
-            self.choose_intersection(decisions, decided_loader, &node_set);
+            self.choose_intersection(decisions, decided_loader, &node_set, residency);
         }
 
         if !jobs.is_empty() {
@@ -222,91 +332,101 @@ impl Node {
                 *len -= common;
             }
             // Other tasks push down right child
-            if let Some(mut right) = self.right.clone() {
-                right.decide(jobs, decisions, vec![])
+            if let Some(right) = node.right {
+                self.decide(right, jobs, decisions, vec![], residency)
             }
         }
     }
 
     fn choose_intersection(
-        self: &mut NodeRef,
+        &self,
         decisions: &mut Vec<Decision>,
         job_set: HashSet<u64>,
-        node_set: &Vec<NodeRef>,
+        node_set: &[NodeId],
+        residency: &mut Residency,
     ) {
-        let weights = node_set.iter().map(|x| x.len()).collect::<Vec<_>>();
-        if weights.iter().sum::<usize>() == 0 {
+        let weights = node_set
+            .iter()
+            .map(|x| residency.weight(*x, &self.get(*x).values_set))
+            .collect::<Vec<_>>();
+        if weights.iter().sum::<f64>() <= 0.0 {
             return;
         }
-        let intersection = node_set[random_weight(&weights)].clone();
+        let intersection = node_set[random_weight(&weights)];
         log::trace!(
             "Dicide: {:?} decide node [{:?}, {:?}]",
             job_set,
-            intersection.get_job_id(),
-            intersection.values_set.as_vec()
+            self.get(intersection).get_job_id(),
+            self.get(intersection).values_set.as_vec()
         );
         let decision = Decision::new(intersection, job_set);
         decisions.push(decision);
     }
 
-    pub fn random_choose(&mut self, job_ids: &HashSet<u64>) -> (u32, HashSet<u64>) {
-        let choice_item = self.values_set.random_pick();
+    pub fn random_choose(
+        &mut self,
+        id: NodeId,
+        job_ids: &HashSet<u64>,
+        residency: &Residency,
+    ) -> (u32, HashSet<u64>) {
+        let node = self.get_mut(id);
+        let choice_item = residency.pick(&mut node.values_set);
         log::trace!(
             "Choose: {:?} choose {:} from node [{:?}]",
             job_ids,
             choice_item,
-            self.job_id,
+            node.job_id,
         );
-        let compensation: HashSet<_> =
-            HashSet::from_iter(self.job_id.difference(job_ids).cloned());
+        let compensation: HashSet<_> = HashSet::from_iter(node.job_id.difference(job_ids).cloned());
 
         (choice_item, compensation)
     }
 
-    pub fn complent(&mut self, comp: &mut HashSet<u64>, item: u32) -> bool {
+    pub fn complent(&mut self, id: NodeId, comp: &mut HashSet<u64>, item: u32) -> bool {
         if comp.is_empty() {
             return false;
         }
-        if self.job_id.is_subset(comp) {
+        let node = self.get_mut(id);
+        if node.job_id.is_subset(comp) {
             // We should complent in next turn to avoild sample it in this turn
-            self.values_set.set(item as u32);
+            node.values_set.set(item as u32);
             log::trace!(
                 "Complent: {:?} in node [{:?}] with compset {:?}",
                 item,
-                self.job_id,
+                node.job_id,
                 comp
             );
-            for task in &self.job_id {
+            for task in &node.job_id {
                 comp.remove(task);
             }
         }
         let mut res = false;
-        if let (Some(left), Some(right)) = (&mut self.left, &mut self.right) {
-            let l = left.get_mut_unchecked();
-            res |= l.complent(comp, item);
-            let r = right.get_mut_unchecked();
-            res |= r.complent(comp, item);
+        if let (Some(left), Some(right)) = (node.left, node.right) {
+            res |= self.complent(left, comp, item);
+            res |= self.complent(right, comp, item);
             log::trace!(
                 "{:?} len: {}, {:?} len: {}",
-                l.get_job_id(),
-                l.min_task_length(),
-                r.get_job_id(),
-                r.min_task_length()
+                self.get(left).get_job_id(),
+                self.min_task_length(left),
+                self.get(right).get_job_id(),
+                self.min_task_length(right)
             );
-            if l.min_task_length() > r.min_task_length() {
+            if self.min_task_length(left) > self.min_task_length(right) {
                 res = true;
-                match (&mut r.left, &mut r.right) {
+                let r = self.get(right);
+                match (r.left, r.right) {
                     (Some(rl), Some(_)) => {
-                        let lid_set = l.job_id.clone();
-                        let lvs = l.values_set.clone();
-                        l.values_set = r.values_set.union(&rl.values_set);
-                        l.job_id = rl.job_id.clone();
-                        remake(r, lvs, lid_set);
+                        let values_set = r.values_set.union(&self.get(rl).values_set);
+                        let job_id = self.get(rl).job_id.clone();
+                        let l = self.get_mut(left);
+                        let lid_set = std::mem::replace(&mut l.job_id, job_id);
+                        let lvs = std::mem::replace(&mut l.values_set, values_set);
+                        self.remake(right, lvs, lid_set);
                     }
                     (None, None) => {
-                        let temp = self.left.clone();
-                        self.left = self.right.clone();
-                        self.right = temp;
+                        let node = self.get_mut(id);
+                        node.left = Some(right);
+                        node.right = Some(left);
                     }
                     _ => unreachable!(),
                 }
@@ -315,26 +435,116 @@ impl Node {
         // if remake, we need to reload task_set
         res
     }
+
+    fn remake(&mut self, id: NodeId, new_vs: ValueSet, new_job_id: HashSet<u64>) {
+        let node = self.get(id);
+        let (left, right) = match (node.left, node.right) {
+            (Some(left), Some(right)) => (left, right),
+            _ => unreachable!(),
+        };
+        log::trace!(
+            "swap {:?} {:?}",
+            self.get(left).get_job_id(),
+            self.get(right).get_job_id()
+        );
+        let (node, l) = self.pair_mut(id, left);
+        for lid in &l.job_id {
+            node.job_id.remove(lid);
+        }
+        for id in &new_job_id {
+            node.job_id.insert(*id);
+        }
+        let diff = node.values_set.difference(&new_vs);
+        node.values_set = node.values_set.intersection(&new_vs);
+        l.values_set = new_vs.difference(&node.values_set);
+        l.job_id = new_job_id;
+        let r = self.get_mut(right);
+        r.values_set = r.values_set.union(&diff);
+    }
 }
 
-fn remake(node: &mut Node, new_vs: ValueSet, new_job_id: HashSet<u64>) {
-    match (&mut node.left, &mut node.right) {
-        (Some(left), Some(right)) => {
-            let l = left.get_mut_unchecked();
-            let r = right.get_mut_unchecked();
-            log::trace!("swap {:?} {:?}", l.get_job_id(), r.get_job_id());
-            for lid in &l.job_id {
-                node.job_id.remove(lid);
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values(arena: &Arena, root: NodeId, job_id: u64) -> Vec<u32> {
+        let mut values = arena.get_job_values(root, job_id);
+        values.sort_unstable();
+        values
+    }
+
+    #[test]
+    fn test_arena() {
+        let mut arena = Arena::new();
+        let root = arena.alloc(Node::new((0..8).collect(), HashSet::from([0])));
+        let other = arena.alloc(Node::new((4..12).collect(), HashSet::from([1])));
+        let root = arena.insert(root, other);
+        let other = arena.alloc(Node::new((0..16).collect(), HashSet::from([2])));
+        let root = arena.insert(root, other);
+        assert_eq!(values(&arena, root, 0), (0..8).collect::<Vec<_>>());
+        assert_eq!(values(&arena, root, 1), (4..12).collect::<Vec<_>>());
+        assert_eq!(values(&arena, root, 2), (0..16).collect::<Vec<_>>());
+        let mut job_set = Vec::new();
+        arena.get_job_set(root, &mut job_set, 0);
+        assert_eq!(
+            job_set.iter().map(|(_, len)| *len).collect::<Vec<_>>(),
+            vec![8, 8, 16]
+        );
+
+        // the nodes of deleted jobs are removed and their slots are reused
+        let nodes = arena.len();
+        let root = arena.delete(root, 1).unwrap();
+        assert!(arena.len() < nodes);
+        assert_eq!(values(&arena, root, 0), (0..8).collect::<Vec<_>>());
+        assert_eq!(values(&arena, root, 2), (0..16).collect::<Vec<_>>());
+        let root = arena.delete(root, 0).unwrap();
+        assert_eq!(arena.delete(root, 2), None);
+        assert_eq!(arena.len(), 0);
+        let id = arena.alloc(Node::new(vec![1], HashSet::from([3])));
+        assert!(id < nodes);
+    }
+
+    // The jobs of a node are the ones of its children, and the left child has one job
+    fn check(arena: &Arena, id: NodeId) -> HashSet<u64> {
+        let node = arena.get(id);
+        if let (Some(left), Some(right)) = (node.left, node.right) {
+            let left = check(arena, left);
+            assert_eq!(left.len(), 1);
+            let jobs = left.union(&check(arena, right)).cloned().collect();
+            assert_eq!(node.job_id, jobs);
+        }
+        node.job_id.clone()
+    }
+
+    #[test]
+    fn test_arena_delete() {
+        let jobs = [0..8, 4..12, 0..16, 8..24];
+        for deleted in 0..jobs.len() as u64 {
+            let mut arena = Arena::new();
+            let mut root = arena.alloc(Node::new(jobs[0].clone().collect(), HashSet::from([0])));
+            for (id, values) in jobs.iter().enumerate().skip(1) {
+                let other = arena.alloc(Node::new(
+                    values.clone().collect(),
+                    HashSet::from([id as u64]),
+                ));
+                root = arena.insert(root, other);
+            }
+            let root = arena.delete(root, deleted).unwrap();
+            let rest = (0..jobs.len() as u64).filter(|id| *id != deleted);
+            assert_eq!(check(&arena, root), rest.clone().collect());
+            for id in rest {
+                assert_eq!(
+                    values(&arena, root, id),
+                    jobs[id as usize].clone().collect::<Vec<_>>()
+                );
             }
-            for id in &new_job_id {
-                node.job_id.insert(*id);
+            assert!(values(&arena, root, deleted).is_empty());
+            let mut job_set = Vec::new();
+            arena.get_job_set(root, &mut job_set, 0);
+            assert_eq!(job_set.len(), jobs.len() - 1);
+            for (id, len) in job_set {
+                assert_eq!(len, jobs[id as usize].len());
             }
-            let diff = node.values_set.difference(&new_vs);
-            node.values_set = node.values_set.intersection(&new_vs);
-            l.values_set = new_vs.difference(&node.values_set);
-            l.job_id = new_job_id;
-            r.values_set = r.values_set.union(&diff);
         }
-        _ => unreachable!(),
     }
 }
@@ -1,20 +1,48 @@
-use super::sampler_node::{Node, NodeRef};
+use super::isa_sampler_tree::{Checkpoint, Order, Sampling};
+use super::residency::Residency;
+use super::sampler_node::{Arena, Node, NodeId};
+use super::{Sampler, Skew};
 use std::collections::{HashMap, HashSet, VecDeque};
 
+// The most values held for a masked job, see SamplerTree::detach
+pub const BUFFER_CAP: usize = 1024;
+// How much more a cached value weighs than the others in sampling, see Residency
+pub const CACHE_BIAS: f64 = 4.0;
+
 #[derive(Clone, Default, Debug)]
 pub struct SamplerTree {
-    root: Option<NodeRef>,
+    arena: Arena,
+    root: Option<NodeId>,
     // (job_id, loader size)
     job_set: Vec<(u64, usize)>,
-    job_buffer: HashMap<u64, VecDeque<u32>>
+    job_buffer: HashMap<u64, VecDeque<u32>>,
+    // the jobs which have epochs left after this one, (indices, the epochs left)
+    epochs: HashMap<u64, (Vec<u32>, Option<usize>)>,
+    buffer_cap: usize,
+    // the values left of the jobs taken out of the tree since they lag too far behind
+    detached: HashMap<u64, Vec<u32>>,
+    detaches: u64,
+    merges: u64,
+    bias: f64,
 }
 
 impl SamplerTree {
     pub fn new() -> Self {
+        SamplerTree::with_buffer_cap(BUFFER_CAP)
+    }
+
+    pub fn with_buffer_cap(buffer_cap: usize) -> Self {
         SamplerTree {
+            arena: Arena::new(),
             root: None,
             job_set: Vec::new(),
             job_buffer: HashMap::new(),
+            epochs: HashMap::new(),
+            buffer_cap: buffer_cap.max(1),
+            detached: HashMap::new(),
+            detaches: 0,
+            merges: 0,
+            bias: CACHE_BIAS,
         }
     }
 
@@ -22,36 +50,37 @@ impl SamplerTree {
         log::debug!("Sampler insert {:?} data {:?}", indices.len(), id);
         let mut job_set = HashSet::new();
         job_set.insert(id);
-        let node = Node::new(indices, job_set);
-        if let Some(mut root) = self.root.clone() {
-            self.root = Some(root.insert(node));
+        let node = self.arena.alloc(Node::new(indices, job_set));
+        if let Some(root) = self.root {
+            self.root = Some(self.arena.insert(root, node));
         } else {
             self.root = Some(node);
         }
         self.job_set.clear();
         // keep order
-        self.root
-            .clone()
-            .unwrap()
-            .get_job_set(&mut self.job_set, 0);
+        self.arena
+            .get_job_set(self.root.unwrap(), &mut self.job_set, 0);
         self.job_buffer.insert(id, VecDeque::new());
     }
 
     pub fn delete(&mut self, id: u64) {
         log::debug!("Del Sampler {}", id);
-        if let Some(root) = &mut self.root {
-            self.root = root.delete(id);
+        if let Some(root) = self.root {
+            self.root = self.arena.delete(root, id);
         }
+        self.job_buffer.remove(&id);
+        self.epochs.remove(&id);
+        self.detached.remove(&id);
         self.job_set.clear();
-        if let Some(root) = &self.root {
-            root.get_job_set(&mut self.job_set, 0);
+        if let Some(root) = self.root {
+            self.arena.get_job_set(root, &mut self.job_set, 0);
         }
-        log::debug!("Del Sampler {} finish.....", id);
+        log::debug!("Del Sampler {} finish, {} nodes left", id, self.arena.len());
     }
 
     pub fn get_task_values(&self, job_id: u64) -> Vec<u32> {
-        if let Some(root) = &self.root {
-            return root.get_job_values(job_id);
+        if let Some(root) = self.root {
+            return self.arena.get_job_values(root, job_id);
         }
         Vec::new()
     }
@@ -66,9 +95,9 @@ impl SamplerTree {
                 new_job_set.push(loader.clone());
             }
         }
-        if let Some(mut root) = self.root.clone() {
-            for id in &del_loader {
-                self.root = root.delete(*id);
+        for id in &del_loader {
+            if let Some(root) = self.root {
+                self.root = self.arena.delete(root, *id);
             }
         }
 
@@ -76,6 +105,24 @@ impl SamplerTree {
         del_loader
     }
 
+    // 0 samples the values of each job uniformly whatever is cached
+    pub fn set_bias(&mut self, bias: f64) {
+        self.bias = bias;
+    }
+
+    // The jobs in the order of job_set, their lengths are weighted by residency
+    fn weigh(&self, jobs: Vec<(u64, usize)>, residency: &mut Residency) -> Vec<(u64, f64)> {
+        let root = match (residency.is_uniform(), self.root) {
+            (false, Some(root)) => root,
+            _ => return jobs.into_iter().map(|(id, len)| (id, len as f64)).collect(),
+        };
+        let mut weights = Vec::new();
+        self.arena
+            .get_job_weights(root, residency, &mut weights, 0.0);
+        let weights = weights.into_iter().collect::<HashMap<_, _>>();
+        jobs.into_iter().map(|(id, _)| (id, weights[&id])).collect()
+    }
+
     pub fn sample(&mut self, mask: &HashSet<u64>) -> HashMap<u32, HashSet<u64>> {
         let mut jobs = Vec::new();
         for loader in &self.job_set {
@@ -84,16 +131,21 @@ impl SamplerTree {
             }
         }
         log::debug!("Sampler sample {:?}", jobs);
+        let mut residency = Residency::uniform();
+        let mut jobs = self.weigh(jobs, &mut residency);
         let mut decisions = Vec::new();
         let mut res = HashMap::<u32, HashSet<u64>>::new();
-        match self.root.clone() {
-            Some(mut root) => root.decide(&mut jobs, &mut decisions, vec![]),
+        match self.root {
+            Some(root) => {
+                self.arena
+                    .decide(root, &mut jobs, &mut decisions, vec![], &mut residency)
+            }
             None => return res,
         }
 
         
         for decision in decisions.iter_mut() {
-            let ret = decision.execute(mask);
+            let ret = decision.execute(&mut self.arena, mask, &residency);
             if let Some(job_set) = res.get_mut(&ret) {
                 for loader in decision.get_jobs() {
                     job_set.insert(loader);
@@ -107,7 +159,7 @@ impl SamplerTree {
 
         let mut reload = false;
         for decision in decisions.iter_mut() {
-            reload |= decision.complent(self.root.clone().unwrap());
+            reload |= decision.complent(&mut self.arena, self.root.unwrap());
         }
         for (id, len) in self.job_set.iter_mut() {
             if !mask.contains(id) && *len != 0 {
@@ -116,8 +168,8 @@ impl SamplerTree {
         }
         if reload {
             self.job_set.clear();
-            if let Some(root) = &self.root {
-                root.get_job_set(&mut self.job_set, 0);
+            if let Some(root) = self.root {
+                self.arena.get_job_set(root, &mut self.job_set, 0);
             }
         }
         self.clear_loader();
@@ -126,6 +178,16 @@ impl SamplerTree {
     }
 
     pub fn sample_with_buffer(&mut self, mask: &HashSet<u64>) -> HashMap<u32, HashSet<u64>> {
+        self.sample_with_residency(mask, None)
+    }
+
+    // Like sample_with_buffer, the cached values are preferred, see Residency
+    pub fn sample_with_residency(
+        &mut self,
+        mask: &HashSet<u64>,
+        resident: Option<&dyn Fn(u32) -> bool>,
+    ) -> HashMap<u32, HashSet<u64>> {
+        self.merge();
         // get the kv job_id: sample
         let mut sampling_res_table = HashMap::new();
         let mut jobs = Vec::new();
@@ -143,15 +205,23 @@ impl SamplerTree {
             }
         }
         log::debug!("Sampler sample {:?} with buffer {:?}", jobs, self.job_buffer);
+        let mut residency = match resident {
+            Some(resident) => Residency::new(resident, self.bias),
+            None => Residency::uniform(),
+        };
+        let mut jobs = self.weigh(jobs, &mut residency);
         let mut decisions = Vec::new();
         let mut res = HashMap::<u32, HashSet<u64>>::new();
-        match self.root.clone() {
-            Some(mut root) => root.decide(&mut jobs, &mut decisions, vec![]),
+        match self.root {
+            Some(root) => {
+                self.arena
+                    .decide(root, &mut jobs, &mut decisions, vec![], &mut residency)
+            }
             None => (),
         }
 
         for decision in decisions.iter_mut() {
-            let ret = decision.execute(&HashSet::new());
+            let ret = decision.execute(&mut self.arena, &HashSet::new(), &residency);
             for job_id in decision.get_jobs() {
                 let buffer = self.job_buffer.get_mut(&job_id).unwrap();
                 buffer.push_back(ret);
@@ -165,14 +235,78 @@ impl SamplerTree {
             res.entry(*v).and_modify(|s| {s.insert(*k);}).or_insert(HashSet::from([*k]));
         }
 
+        // the jobs which chose their own values still read the ones taken from the
+        // intersection by the others
+        let mut reload = false;
+        for decision in decisions.iter_mut() {
+            reload |= decision.complent(&mut self.arena, self.root.unwrap());
+        }
         for (_, len) in self.job_set.iter_mut() {
             *len -= 1;
         }
+        if reload {
+            self.job_set.clear();
+            if let Some(root) = self.root {
+                self.arena.get_job_set(root, &mut self.job_set, 0);
+            }
+        }
         self.clear_loader();
+        self.detach();
         log::debug!("Sampler get {:?}", res);
         res
     }
 
+    // A masked job holds the values decided for the others in its buffer. When it holds
+    // buffer_cap values, the job is taken out of the tree, so the others go on without
+    // holding more values for it, and it reads its buffer alone
+    fn detach(&mut self) {
+        let lagging = self
+            .job_set
+            .iter()
+            .map(|(id, _)| *id)
+            .filter(|id| self.job_buffer[id].len() >= self.buffer_cap)
+            .collect::<Vec<_>>();
+        for id in lagging {
+            let root = self.root.unwrap();
+            let values = self.arena.get_job_values(root, id);
+            self.root = self.arena.delete(root, id);
+            self.job_set.clear();
+            if let Some(root) = self.root {
+                self.arena.get_job_set(root, &mut self.job_set, 0);
+            }
+            log::info!(
+                "Sampler detaches job {} with {} values left",
+                id,
+                values.len()
+            );
+            self.detaches += 1;
+            if !values.is_empty() {
+                self.detached.insert(id, values);
+            }
+        }
+    }
+
+    // A detached job has caught up with the others once it has read its buffer, and its
+    // values left go back to the tree
+    fn merge(&mut self) {
+        let caught_up = self
+            .detached
+            .keys()
+            .filter(|id| self.job_buffer[id].is_empty())
+            .cloned()
+            .collect::<Vec<_>>();
+        for id in caught_up {
+            let values = self.detached.remove(&id).unwrap();
+            log::info!(
+                "Sampler merges job {} with {} values left",
+                id,
+                values.len()
+            );
+            self.merges += 1;
+            self.insert(values, id);
+        }
+    }
+
     pub fn is_empty(&self) -> bool {
         let mut capacity = 0;
         for job in &self.job_set {
@@ -181,18 +315,143 @@ impl SamplerTree {
         capacity != 0
     }
 
+    // The values in the tree and the ones sampled but held for the masked job
     pub fn get_job_values(&self, job_id: u64) -> Vec<u32> {
-        if let Some(root) = self.root.as_ref() {
-            return root.get_job_values(job_id);
+        let mut res = match self.root {
+            Some(root) => self.arena.get_job_values(root, job_id),
+            None => Vec::new(),
+        };
+        if let Some(buffer) = self.job_buffer.get(&job_id) {
+            res.extend(buffer.iter());
+        }
+        if let Some(values) = self.detached.get(&job_id) {
+            res.extend(values.iter());
+        }
+        res
+    }
+
+    // A job has read this epoch when it has left the tree and its buffer is empty
+    fn is_finished(&self, id: u64) -> bool {
+        !self.job_set.iter().any(|(job_id, _)| *job_id == id)
+            && !self.detached.contains_key(&id)
+            && self
+                .job_buffer
+                .get(&id)
+                .map_or(true, |buffer| buffer.is_empty())
+    }
+}
+
+// The jobs are sampled from a tree of the values they have in common, so that they read
+// the same values in the same rounds as much as possible. A job is always shuffled
+impl Sampler for SamplerTree {
+    fn insert(
+        &mut self,
+        indices: Vec<u32>,
+        id: u64,
+        sampling: Sampling,
+        checkpoint: Option<Checkpoint>,
+    ) {
+        assert!(
+            self.supports(&sampling),
+            "job {} can't be sampled by {:?}",
+            id,
+            sampling
+        );
+        let epoch = checkpoint.as_ref().map_or(0, |checkpoint| checkpoint.epoch);
+        let left = sampling
+            .epochs
+            .map(|epochs| epochs.max(1).saturating_sub(epoch + 1));
+        if left != Some(0) && !indices.is_empty() {
+            self.epochs.insert(id, (indices.clone(), left));
+        }
+        let values = match checkpoint {
+            Some(checkpoint) => checkpoint.values,
+            None => indices,
+        };
+        match values.is_empty() {
+            true => {
+                self.job_buffer.insert(id, VecDeque::new());
+            }
+            false => SamplerTree::insert(self, values, id),
+        }
+    }
+
+    fn delete(&mut self, id: u64) {
+        SamplerTree::delete(self, id);
+    }
+
+    fn sample(
+        &mut self,
+        mask: &HashSet<u64>,
+        resident: Option<&dyn Fn(u32) -> bool>,
+    ) -> HashMap<u32, HashSet<u64>> {
+        self.sample_with_residency(mask, resident)
+    }
+
+    // The values are decided round by round
+    fn set_lookahead(&mut self, _lookahead: usize) {}
+
+    // Only the values held for the masked jobs are known ahead
+    fn lookahead(&self) -> HashMap<u32, usize> {
+        let mut res = HashMap::new();
+        for buffer in self.job_buffer.values() {
+            for (distance, v) in buffer.iter().enumerate() {
+                let d = res.entry(*v).or_insert(distance);
+                *d = distance.min(*d);
+            }
+        }
+        res
+    }
+
+    fn get_job_values(&self, id: u64) -> Vec<u32> {
+        SamplerTree::get_job_values(self, id)
+    }
+
+    fn next_epoch(&mut self, id: u64) -> Option<Vec<u32>> {
+        if !self.is_finished(id) {
+            return None;
+        }
+        let (indices, left) = self.epochs.get_mut(&id)?;
+        let indices = indices.clone();
+        if let Some(left) = left {
+            *left -= 1;
+            if *left == 0 {
+                self.epochs.remove(&id);
+            }
+        }
+        SamplerTree::insert(self, indices.clone(), id);
+        Some(indices)
+    }
+
+    fn has_next_epoch(&self, id: u64) -> bool {
+        self.epochs.contains_key(&id)
+    }
+
+    fn supports(&self, sampling: &Sampling) -> bool {
+        sampling.order == Order::Shuffled && !sampling.replacement && sampling.shard.is_none()
+    }
+
+    fn skew(&self) -> Skew {
+        let lags = self.job_buffer.values().map(|buffer| buffer.len());
+        Skew {
+            max_lag: lags.clone().max().unwrap_or_default(),
+            buffered: lags.sum(),
+            detached: self.detached.len(),
+            detaches: self.detaches,
+            merges: self.merges,
         }
-        Vec::default()
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::cache::cache::{entry_size, Cache};
+    use crate::cache::key::CacheKey;
+    use crate::cache::policy::PolicyType;
+    use crate::proto::job::Data;
     use rand::Rng;
+    use std::sync::Arc;
     use std::{iter::FromIterator, time::Instant};
     #[test]
     fn test_bm_mask() {
@@ -384,4 +643,269 @@ mod tests {
             assert_eq!(keys, set);
         }
     }
+
+    #[test]
+    fn test_sampler_epochs() {
+        let mut sampler: Box<dyn Sampler> = Box::new(SamplerTree::new());
+        let sampling = Sampling {
+            epochs: Some(2),
+            ..Default::default()
+        };
+        assert!(!sampler.supports(&Sampling {
+            order: Order::Sequential,
+            ..Default::default()
+        }));
+        sampler.insert((0..4).collect(), 0, sampling, None);
+        sampler.insert((2..6).collect(), 1, Sampling::default(), None);
+        let mut read: HashMap<u64, Vec<u32>> = HashMap::new();
+        // job 1 is masked for a round, so its values are held in the buffer
+        let mut mask = HashSet::from([1]);
+        loop {
+            let res = sampler.sample(&mask, None);
+            mask.clear();
+            if res.is_empty() {
+                break;
+            }
+            for (v, jobs) in res {
+                for job in jobs {
+                    read.entry(job).or_default().push(v);
+                }
+            }
+            if let Some(indices) = sampler.next_epoch(0) {
+                assert_eq!(indices, (0..4).collect::<Vec<_>>());
+                assert!(!sampler.has_next_epoch(0));
+            }
+            assert_eq!(sampler.next_epoch(1), None);
+        }
+        let mut values = read.remove(&0).unwrap();
+        values.sort_unstable();
+        assert_eq!(values, vec![0, 0, 1, 1, 2, 2, 3, 3]);
+        let mut values = read.remove(&1).unwrap();
+        values.sort_unstable();
+        assert_eq!(values, (2..6).collect::<Vec<_>>());
+
+        sampler.insert((0..4).collect(), 2, sampling, None);
+        sampler.sample(&HashSet::from([2]), None);
+        assert_eq!(sampler.lookahead().len(), 1);
+        assert_eq!(sampler.get_job_values(2).len(), 4);
+        sampler.delete(2);
+        assert!(sampler.get_job_values(2).is_empty());
+        assert!(sampler.lookahead().is_empty());
+        assert!(!sampler.has_next_epoch(2));
+    }
+
+    #[test]
+    fn test_detach() {
+        let cap = 4;
+        let mut sampler = SamplerTree::with_buffer_cap(cap);
+        sampler.insert((0..16).collect(), 0);
+        sampler.insert((0..16).collect(), 1);
+        let mut read: HashMap<u64, Vec<u32>> = HashMap::new();
+        let mut round = 0;
+        loop {
+            // job 1 stalls for a while, e.g. in an evaluation
+            let mask = match round < 8 {
+                true => HashSet::from([1]),
+                false => HashSet::new(),
+            };
+            round += 1;
+            let res = sampler.sample_with_buffer(&mask);
+            let skew = Sampler::skew(&sampler);
+            assert!(skew.max_lag <= cap);
+            if round == 8 {
+                assert_eq!(skew.detached, 1);
+                assert_eq!(skew.max_lag, cap);
+                assert_eq!(sampler.get_job_values(1).len(), 16);
+            }
+            if res.is_empty() {
+                break;
+            }
+            for (v, jobs) in res {
+                for job in jobs {
+                    read.entry(job).or_default().push(v);
+                }
+            }
+        }
+        let skew = Sampler::skew(&sampler);
+        assert_eq!((skew.detached, skew.detaches, skew.merges), (0, 1, 1));
+        for id in 0..2 {
+            let mut values = read.remove(&id).unwrap();
+            values.sort_unstable();
+            assert_eq!(values, (0..16).collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn test_detach_merge_epochs() {
+        let cap = 4;
+        let mut sampler: Box<dyn Sampler> = Box::new(SamplerTree::with_buffer_cap(cap));
+        let indices: [Vec<u32>; 3] = [(0..24).collect(), (8..32).collect(), (0..32).collect()];
+        let sampling = Sampling {
+            epochs: Some(2),
+            ..Default::default()
+        };
+        for (id, indices) in indices.iter().enumerate() {
+            sampler.insert(indices.clone(), id as u64, sampling, None);
+        }
+        // the values read by each job in each epoch
+        let mut read = vec![vec![Vec::new()]; 3];
+        let mut merged = None;
+        for round in 0.. {
+            // job 2 stalls until it's detached, and the others go on in their first epoch
+            let mask = match (2..10).contains(&round) {
+                true => HashSet::from([2]),
+                false => HashSet::new(),
+            };
+            let res = sampler.sample(&mask, None);
+            let skew = sampler.skew();
+            assert!(skew.max_lag <= cap);
+            if round == 9 {
+                assert_eq!((skew.detached, skew.detaches), (1, 1));
+            }
+            if skew.merges == 1 && merged.is_none() {
+                merged = Some((read[0].len(), read[1].len()));
+            }
+            for (v, jobs) in &res {
+                for job in jobs {
+                    read[*job as usize].last_mut().unwrap().push(*v);
+                }
+            }
+            let mut restarted = false;
+            for (id, read) in read.iter_mut().enumerate() {
+                if sampler.next_epoch(id as u64).is_some() {
+                    read.push(Vec::new());
+                    restarted = true;
+                }
+            }
+            if res.is_empty() && !restarted {
+                break;
+            }
+        }
+        // job 2 is merged while the others are in their first epoch
+        assert_eq!(merged, Some((1, 1)));
+        let skew = sampler.skew();
+        assert_eq!((skew.detached, skew.detaches, skew.merges), (0, 1, 1));
+        for (id, epochs) in read.into_iter().enumerate() {
+            assert_eq!(epochs.len(), 2);
+            for mut values in epochs {
+                values.sort_unstable();
+                assert_eq!(values, indices[id]);
+            }
+            assert!(!sampler.has_next_epoch(id as u64));
+        }
+    }
+
+    // The experiment of cache-aware sampling: job 1 starts when job 0 has read lag of the
+    // len samples, and they read through an LRU cache of capacity samples, where the
+    // samples below pinned are pinned. The hit rate of cache and the order of job 1 are
+    // returned
+    fn hit_rate(bias: f64, len: u32, capacity: usize, lag: usize, pinned: u32) -> (f64, Vec<u32>) {
+        let data = Arc::new(vec![Data::default()]);
+        let mut cache = Cache::with_policy(capacity * entry_size(&data), PolicyType::Lru);
+        cache.set_pin_capacity(pinned as usize * entry_size(&data));
+        for v in 0..pinned {
+            assert!(cache.pin(CacheKey::new(v as u64, 0), data.clone(), 0));
+        }
+        let mut sampler = SamplerTree::new();
+        sampler.set_bias(bias);
+        sampler.insert((0..len).collect(), 0);
+        let mut order = Vec::new();
+        for round in 0.. {
+            if round == lag {
+                sampler.insert((0..len).collect(), 1);
+            }
+            let resident = |idx| cache.contains(CacheKey::new(idx as u64, 0));
+            let res = sampler.sample_with_residency(&HashSet::new(), Some(&resident));
+            if res.is_empty() && round > lag {
+                break;
+            }
+            for (v, jobs) in res {
+                let key = CacheKey::new(v as u64, 0);
+                if cache.get(key).is_none() {
+                    cache.set(key, data.clone(), jobs.len());
+                }
+                if jobs.contains(&1) {
+                    order.push(v);
+                }
+            }
+        }
+        let hits = cache.hit_count() as f64;
+        (hits / (hits + cache.miss_count() as f64), order)
+    }
+
+    #[test]
+    fn test_cache_aware_hit_rate() {
+        let trials = 20;
+        let mut rates = Vec::new();
+        for bias in [0.0, 1.0, CACHE_BIAS, 16.0] {
+            let rate = (0..trials)
+                .map(|_| hit_rate(bias, 512, 128, 256, 0).0)
+                .sum::<f64>();
+            println!("hit rate with bias {}: {:.3}", bias, rate / trials as f64);
+            rates.push(rate / trials as f64);
+        }
+        assert!(rates[2] > rates[0] * 1.2, "{:?}", rates);
+    }
+
+    #[test]
+    fn test_cache_aware_uniform() {
+        // the mean position of each sample in the order of the job ahead and the lagging
+        // one which prefers the cached samples
+        let (len, trials) = (32, 400);
+        let mut positions = vec![0; len as usize];
+        for _ in 0..trials {
+            let (_, order) = hit_rate(CACHE_BIAS, len, 8, len as usize / 2, 0);
+            let mut sorted = order.clone();
+            sorted.sort_unstable();
+            assert_eq!(sorted, (0..len).collect::<Vec<_>>());
+            for (pos, v) in order.into_iter().enumerate() {
+                positions[v as usize] += pos;
+            }
+        }
+        let mean = (len - 1) as f64 / 2.0;
+        for (v, pos) in positions.into_iter().enumerate() {
+            let pos = pos as f64 / trials as f64;
+            assert!(
+                (pos - mean).abs() < 3.0,
+                "sample {} at {} on average",
+                v,
+                pos
+            );
+        }
+    }
+    // Pearson's chi-square of the positions of the values in the orders against uniform
+    // permutations, the positions are grouped into buckets. It has (len - 1) * (buckets - 1)
+    // degrees of freedom
+    fn chi_square(orders: &[Vec<u32>], len: usize, buckets: usize) -> f64 {
+        let mut counts = vec![vec![0.0; buckets]; len];
+        for order in orders {
+            for (pos, v) in order.iter().enumerate() {
+                counts[*v as usize][pos * buckets / len] += 1.0;
+            }
+        }
+        let expected = orders.len() as f64 / buckets as f64;
+        counts
+            .iter()
+            .flatten()
+            .map(|count| (count - expected).powi(2) / expected)
+            .sum()
+    }
+
+    #[test]
+    fn test_cache_aware_chi_square() {
+        let (len, trials, buckets) = (32, 400, 4);
+        let orders = |pinned| {
+            (0..trials)
+                .map(|_| hit_rate(CACHE_BIAS, len, 8, len as usize / 2, pinned).1)
+                .collect::<Vec<_>>()
+        };
+        // 93 degrees of freedom, the chi-square of uniform permutations is above 165 with
+        // a probability below 1e-5
+        let uniform = chi_square(&orders(0), len as usize, buckets);
+        assert!(uniform < 165.0, "chi-square {}", uniform);
+        // the pinned samples are preferred by both jobs whatever their orders, so the
+        // guarantee stops here and Joader samples uniformly once a sample is pinned
+        let pinned = chi_square(&orders(len / 4), len as usize, buckets);
+        assert!(pinned > 330.0, "chi-square {}", pinned);
+    }
 }
@@ -1,4 +1,5 @@
 use bitmaps::Bitmap;
+use rand::Rng;
 
 const BASE: usize = 128;
 #[derive(Debug, PartialEq, Clone, Copy)]
@@ -36,16 +37,6 @@ impl BitmapOff {
     pub fn len(&self) -> usize {
         self.bm.len()
     }
-
-    pub fn pick_first(&mut self) -> u32 {
-        for i in 0..BASE {
-            if self.bm.get(i) == true {
-                self.bm.set(i, false);
-                return (i + self.off) as u32;
-            }
-        }
-        unreachable!();
-    }
 }
 
 #[derive(Debug, PartialEq, Clone)]
@@ -202,15 +193,25 @@ impl ValueSet {
         Self { set, size }
     }
 
-    pub fn random_pick(&mut self) -> u32 {
-        let len = self.set.len();
-        let choice_idx = (rand::random::<f32>() * (len as f32)) as usize;
-        assert_eq!(self.set[choice_idx].is_empty(), false);
-        let res = self.set[choice_idx].pick_first();
-        if self.set[choice_idx].is_empty() {
-            self.set.remove(choice_idx);
+    // A value chosen uniformly at random, it's not removed. A random slot of a random
+    // bitmap is tried until it holds a value, so that no bitmap is scanned
+    pub fn random(&self) -> u32 {
+        assert!(self.size != 0, "pick from an empty set");
+        let mut rng = rand::thread_rng();
+        loop {
+            let bm = &self.set[rng.gen_range(0..self.set.len())];
+            let idx = rng.gen_range(0..BASE);
+            if bm.bm.get(idx) {
+                return (idx + bm.off) as u32;
+            }
         }
-        self.size -= 1;
+    }
+
+    // Remove a value chosen uniformly at random, so the values are picked in a uniform
+    // permutation
+    pub fn random_pick(&mut self) -> u32 {
+        let res = self.random();
+        self.reset(res);
         res
     }
 
@@ -249,7 +250,7 @@ impl ValueSet {
         let mut res = Vec::with_capacity(self.size);
         for &bm in self.set.iter() {
             for v in bm.bm.into_iter() {
-                res.push(v as u32);
+                res.push((v + bm.off) as u32);
             }
         }
         assert_eq!(res.len(), self.size);
@@ -264,6 +265,7 @@ impl ValueSet {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::collections::HashMap;
 
     #[test]
     fn test_set() {
@@ -296,6 +298,28 @@ mod tests {
         vec.sort();
         assert_eq!(vec, (0..size).collect::<Vec<u32>>());
     }
+    #[test]
+    fn test_random() {
+        let mut v = ValueSet::new();
+        for i in [3, 130, 200, 1000] {
+            v.set(i);
+        }
+        assert_eq!(v.as_vec(), vec![3, 130, 200, 1000]);
+        // a value of a full bitmap is as likely as the only value of another
+        let mut counts = HashMap::new();
+        let mut v = ValueSet::init(128);
+        v.set(1000);
+        for _ in 0..129000 {
+            *counts.entry(v.random()).or_insert(0) += 1;
+        }
+        assert_eq!(counts.len(), 129);
+        assert!(
+            counts.values().all(|c| (800..1200).contains(c)),
+            "{:?}",
+            counts
+        );
+    }
+
     #[test]
     fn test_itersection() {
         let l = ValueSet::init(129);
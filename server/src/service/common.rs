@@ -21,6 +21,11 @@ impl IdGenerator {
         self.job_id.load(Ordering::SeqCst)
     }
 
+    // The first of n consecutive job ids
+    pub fn get_job_ids(&self, n: u64) -> u64 {
+        self.job_id.fetch_add(n, Ordering::SeqCst) + 1
+    }
+
     pub fn new() -> Self {
         Self {
             dataset_id: Arc::new(AtomicU64::new(0)),
@@ -1,10 +1,17 @@
 use crate::dataset::build_dataset;
 use crate::joader::joader::Joader;
 use crate::joader::joader_table::JoaderTable;
+use crate::joader::prewarm::Prewarmer;
+use crate::proto::dataset::create_dataset_request::Sampler as DatasetSampler;
 use crate::proto::dataset::dataset_svc_server::DatasetSvc;
 use crate::proto::dataset::*;
+use crate::sampler::isa_sampler_tree::SamplerTree as IndependentSampler;
+use crate::sampler::sampler_tree::SamplerTree as SharedSampler;
+use crate::sampler::Sampler;
 use std::sync::Arc;
+use tokio::sync::mpsc::channel;
 use tokio::sync::Mutex;
+use tokio_stream::wrappers::ReceiverStream;
 use tonic::{async_trait, Request, Response, Status};
 
 use super::{IDTable, IdGenerator};
@@ -16,6 +23,15 @@ pub struct DatasetSvcImpl {
 }
 
 impl DatasetSvcImpl {
+    async fn get_prewarmer(&self, name: &str) -> Result<Prewarmer, Status> {
+        let jt = self.joader_table.lock().await;
+        let dt = self.dataset_id_table.lock().await;
+        match dt.get(name) {
+            Some(id) => Ok(jt.prewarmer(*id).unwrap()),
+            None => Err(Status::not_found(format!("dataset {:?} not found", name))),
+        }
+    }
+
     pub fn new(
         joader_table: Arc<Mutex<JoaderTable>>,
         dataset_id_table: IDTable,
@@ -45,12 +61,24 @@ impl DatasetSvc for DatasetSvcImpl {
             )));
         }
 
+        let sampler: Box<dyn Sampler> = match DatasetSampler::from_i32(request.sampler) {
+            Some(DatasetSampler::Independent) => Box::new(IndependentSampler::new()),
+            Some(DatasetSampler::Shared) => Box::new(SharedSampler::new()),
+            None => {
+                return Err(Status::invalid_argument(format!(
+                    "unknown sampler {}",
+                    request.sampler
+                )))
+            }
+        };
+
         log::debug!("Create dataset {:?}", request);
         let id = self.id_gen.get_dataset_id();
         dt.insert(request.name.clone(), id);
         // insert dataset to dataset table
-        let joader = Joader::new(build_dataset(request.clone(), id));
+        let joader = Joader::with_sampler(build_dataset(request.clone(), id), sampler);
         jt.add_joader(joader);
+        jt.restore(&request.name, id);
         Ok(Response::new(CreateDatasetResponse { status: None }))
     }
 
@@ -64,11 +92,93 @@ impl DatasetSvc for DatasetSvcImpl {
         let mut dt = self.dataset_id_table.lock().await;
         match dt.get(&request.name) {
             Some(id) => {
-                jt.del_joader(*id);
+                jt.del_joader(*id).await;
                 dt.remove(&request.name);
                 Ok(Response::new(DeleteDatasetResponse { status: None }))
             }
             None => Err(Status::not_found(format!("{:?} not found", request))),
         }
     }
+
+    type PrewarmStream = ReceiverStream<Result<PrewarmResponse, Status>>;
+
+    async fn prewarm(
+        &self,
+        request: Request<PrewarmRequest>,
+    ) -> Result<Response<Self::PrewarmStream>, Status> {
+        log::debug!("call prewarm {:?}", request);
+        let request = request.into_inner();
+        let prewarmer = self.get_prewarmer(&request.name).await?;
+        if request.start >= request.end || request.end as usize > prewarmer.len() {
+            return Err(Status::invalid_argument(format!(
+                "[{}, {}) is out of the {} samples",
+                request.start,
+                request.end,
+                prewarmer.len()
+            )));
+        }
+        let (tx, rx) = channel(1);
+        tokio::spawn(async move {
+            let indices = (request.start..request.end).collect::<Vec<_>>();
+            let mut resp = PrewarmResponse {
+                total: indices.len() as u32,
+                ..Default::default()
+            };
+            for batch in indices.chunks(prewarmer.batch_size()) {
+                let progress = prewarmer.load(batch).await;
+                resp.loaded += progress.loaded as u32;
+                resp.cached += progress.cached as u32;
+                resp.full = progress.full;
+                // stop when the client is gone or the cache is full
+                if tx.send(Ok(resp.clone())).await.is_err() || resp.full {
+                    break;
+                }
+            }
+            log::info!("Prewarm {:?}: {:?}", request.name, resp);
+        });
+        Ok(Response::new(ReceiverStream::new(rx)))
+    }
+
+    type PinStream = ReceiverStream<Result<PinResponse, Status>>;
+
+    async fn pin(&self, request: Request<PinRequest>) -> Result<Response<Self::PinStream>, Status> {
+        log::debug!("call pin {:?}", request);
+        let request = request.into_inner();
+        let prewarmer = self.get_prewarmer(&request.name).await?;
+        let (tx, rx) = channel(1);
+        tokio::spawn(async move {
+            let indices = prewarmer.indices(request.condition);
+            let mut resp = PinResponse {
+                total: indices.len() as u32,
+                ..Default::default()
+            };
+            for batch in indices.chunks(prewarmer.batch_size()) {
+                let progress = prewarmer.pin(batch).await;
+                resp.pinned += (progress.loaded + progress.cached) as u32;
+                resp.full = progress.full;
+                if tx.send(Ok(resp.clone())).await.is_err() || resp.full {
+                    break;
+                }
+            }
+            log::info!("Pin {:?}: {:?}", request.name, resp);
+        });
+        Ok(Response::new(ReceiverStream::new(rx)))
+    }
+
+    async fn unpin(
+        &self,
+        request: Request<UnpinRequest>,
+    ) -> Result<Response<UnpinResponse>, Status> {
+        log::debug!("call unpin {:?}", request);
+        let request = request.into_inner();
+        let mut jt = self.joader_table.lock().await;
+        let dt = self.dataset_id_table.lock().await;
+        match dt.get(&request.name) {
+            Some(id) => {
+                let unpinned = jt.unpin(*id, request.condition).await as u32;
+                Ok(Response::new(UnpinResponse { unpinned }))
+            }
+            None => Err(Status::not_found(format!("{:?} not found", request))),
+        }
+    }
 }
@@ -1,22 +1,33 @@
 use super::{IDTable, IdGenerator};
+use crate::joader::joader::Joader;
 use crate::joader::joader_table::JoaderTable;
-use crate::job::Job;
+use crate::job::{collate, BatchReceiver, Job};
 use crate::proto::job::job_svc_server::JobSvc;
-use crate::proto::job::Data;
 use crate::proto::job::*;
-use std::collections::HashMap;
+use crate::sampler::isa_sampler_tree::{Checkpoint, Order as SampleOrder, Sampling, Shard};
+use prost::Message;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
-use tokio::sync::mpsc::Receiver;
+use tokio::sync::mpsc::channel;
 use tokio::sync::Mutex;
+use tokio_stream::wrappers::ReceiverStream;
 use tonic::{async_trait, Request, Response, Status};
 
+// The number of batches buffered in a stream, the rest wait for the HTTP/2 window
+const STREAM_CAP: usize = 2;
+
+// the receivers of the ranks of a job, rank r reads the job job_id + r
+type Receivers = Vec<Arc<Mutex<BatchReceiver>>>;
+
 #[derive(Debug)]
 pub struct JobSvcImpl {
     id_gen: IdGenerator,
     joader_table: Arc<Mutex<JoaderTable>>,
     job_id_table: IDTable,
     dataset_id_table: IDTable,
-    recv_table: Arc<Mutex<HashMap<u64, Arc<Mutex<Receiver<Arc<Vec<Data>>>>>>>>,
+    recv_table: Arc<Mutex<HashMap<u64, Receivers>>>,
+    // the (job, rank) read by Stream
+    stream_set: Arc<Mutex<HashSet<(u64, u32)>>>,
 }
 
 impl JobSvcImpl {
@@ -29,11 +40,123 @@ impl JobSvcImpl {
         Self {
             joader_table,
             recv_table: Default::default(),
+            stream_set: Default::default(),
             job_id_table,
             id_gen,
             dataset_id_table,
         }
     }
+
+    async fn get_recvs(&self, job_id: u64) -> Result<Receivers, Status> {
+        let rt = self.recv_table.lock().await;
+        let recvs = rt.get(&job_id).cloned();
+        recvs.ok_or_else(|| Status::not_found(format!("Loader {:} not found", job_id)))
+    }
+
+    async fn get_recv(&self, job_id: u64, rank: u32) -> Result<Arc<Mutex<BatchReceiver>>, Status> {
+        let recvs = self.get_recvs(job_id).await?;
+        let recv = recvs.get(rank as usize).cloned();
+        recv.ok_or_else(|| {
+            Status::invalid_argument(format!("Loader {:} has no rank {:}", job_id, rank))
+        })
+    }
+}
+
+fn sampling(request: &CreateJobRequest) -> Result<Sampling, Status> {
+    let epochs = match request.infinite {
+        true => None,
+        false => Some(request.epochs.max(1) as usize),
+    };
+    let order = match Order::from_i32(request.order) {
+        Some(Order::Shuffled) => SampleOrder::Shuffled,
+        Some(Order::Sequential) => SampleOrder::Sequential,
+        Some(Order::Seeded) => SampleOrder::Seeded(request.seed),
+        None => {
+            return Err(Status::invalid_argument(format!(
+                "unknown order {}",
+                request.order
+            )))
+        }
+    };
+    if request.replacement && order == SampleOrder::Sequential {
+        return Err(Status::invalid_argument(
+            "a sequential job can't be sampled with replacement",
+        ));
+    }
+    let ranks = request.ranks.max(1) as usize;
+    if request.replacement && ranks > 1 {
+        return Err(Status::invalid_argument(
+            "a job of several ranks can't be sampled with replacement",
+        ));
+    }
+    let (order, shard) = match ranks {
+        1 => (order, None),
+        ranks => {
+            // the ranks split the same permutation of each epoch
+            let order = match order {
+                SampleOrder::Shuffled => SampleOrder::Seeded(rand::random()),
+                order => order,
+            };
+            let shard = Shard {
+                rank: 0,
+                ranks,
+                drop_uneven: request.drop_uneven,
+            };
+            (order, Some(shard))
+        }
+    };
+    Ok(Sampling {
+        epochs,
+        order,
+        replacement: request.replacement,
+        shard,
+    })
+}
+
+// The checkpoint of each rank to resume from
+fn checkpoints(
+    request: &CreateJobRequest,
+    sampling: &Sampling,
+    joader: &Joader,
+) -> Result<Vec<Option<Checkpoint>>, Status> {
+    let ranks = request.ranks.max(1) as usize;
+    if request.checkpoint.is_empty() {
+        return Ok(vec![None; ranks]);
+    }
+    if sampling.replacement {
+        return Err(Status::failed_precondition(
+            "a job sampled with replacement has no epochs to resume",
+        ));
+    }
+    let invalid = |msg: String| Status::invalid_argument(format!("invalid checkpoint: {}", msg));
+    let checkpoint =
+        JobCheckpoint::decode(&request.checkpoint[..]).map_err(|e| invalid(e.to_string()))?;
+    if checkpoint.fingerprint != joader.fingerprint() {
+        return Err(invalid(format!(
+            "dataset {} has changed",
+            request.dataset_name
+        )));
+    }
+    if checkpoint.ranks.len() != ranks {
+        return Err(invalid(format!("it has {} ranks", checkpoint.ranks.len())));
+    }
+    let mut res = Vec::new();
+    for rank in checkpoint.ranks {
+        let epoch = rank.epoch as usize;
+        if sampling.epochs.map_or(false, |epochs| epoch >= epochs) {
+            return Err(invalid(format!("epoch {} is out of range", epoch)));
+        }
+        if !joader.selects(request.condition.clone(), &rank.values) {
+            return Err(invalid(
+                "the samples are not selected by the condition".into(),
+            ));
+        }
+        res.push(Some(Checkpoint {
+            epoch,
+            values: rank.values,
+        }));
+    }
+    Ok(res)
 }
 
 #[async_trait]
@@ -51,33 +174,131 @@ impl JobSvc for JobSvcImpl {
         let dataset_id = *dt
             .get(&request.dataset_name)
             .ok_or_else(|| Status::not_found(&request.dataset_name))?;
+        let sampling = sampling(&request)?;
         let joader = jt.get_mut(dataset_id);
+        if !joader.supports(&sampling).await {
+            return Err(Status::invalid_argument(format!(
+                "the sampler of {} can't sample {:?}",
+                request.dataset_name, sampling
+            )));
+        }
+        let checkpoints = checkpoints(&request, &sampling, joader)?;
 
-        let job_id = self.id_gen.get_job_id();
-        let (job, r) = Job::new(job_id);
-        let length = joader.add_job(job, request.condition).await as u64;
-        rt.insert(job_id, Arc::new(Mutex::new(r)));
+        let ranks = request.ranks.max(1) as u64;
+        let job_id = self.id_gen.get_job_ids(ranks);
+        let mut recvs = Vec::new();
+        let mut length = 0;
+        for (rank, checkpoint) in (0..ranks).zip(checkpoints) {
+            let sampling = Sampling {
+                shard: sampling.shard.map(|shard| Shard {
+                    rank: rank as usize,
+                    ..shard
+                }),
+                ..sampling
+            };
+            let (job, r) = Job::new(job_id + rank);
+            let resume = checkpoint.as_ref().map(|c| (c.epoch, c.values.len()));
+            length = joader
+                .add_job_from(job, request.condition.clone(), sampling, checkpoint)
+                .await;
+            let mut recv = match sampling.replacement {
+                true => BatchReceiver::endless(r),
+                false => BatchReceiver::with_epochs(r, length, sampling.epochs),
+            };
+            if let Some((epoch, remain)) = resume {
+                recv.resume(epoch, remain);
+            }
+            recvs.push(Arc::new(Mutex::new(recv)));
+        }
+        rt.insert(job_id, recvs);
         job_id_table.insert(request.name.clone(), job_id);
-        Ok(Response::new(CreateJobResponse { length, job_id }))
+        Ok(Response::new(CreateJobResponse {
+            length: length as u64,
+            job_id,
+        }))
     }
 
     async fn next(&self, request: Request<NextRequest>) -> Result<Response<NextResponse>, Status> {
         let request = request.into_inner();
-        let job_id = request.job_id;
-
-        let recv = {
-            let rt = self.recv_table.lock().await;
-            let recv = rt.get(&job_id).cloned();
-            recv.ok_or_else(|| Status::not_found(format!("Loader {:} not found", job_id)))
-        }?;
+        if self
+            .stream_set
+            .lock()
+            .await
+            .contains(&(request.job_id, request.rank))
+        {
+            return Err(Status::failed_precondition(format!(
+                "Job {:} rank {:} is being streamed",
+                request.job_id, request.rank
+            )));
+        }
+        let recv = self.get_recv(request.job_id, request.rank).await?;
         let mut recv = recv.lock().await;
-        let data = recv.recv().await;
-        match data {
-            Some(data) => Ok(Response::new(NextResponse {
-                data: (*data).clone(),
-            })),
-            None => Ok(Response::new(NextResponse { data: Vec::new() })),
+        let batch = recv
+            .recv_batch(request.batch_size as usize, request.drop_last)
+            .await;
+        Ok(Response::new(NextResponse {
+            data: collate(&batch).map_err(Status::internal)?,
+            batch_size: batch.len() as u32,
+            epoch: recv.epoch() as u32,
+            end_of_epoch: recv.end_of_epoch(),
+        }))
+    }
+
+    type StreamStream = ReceiverStream<Result<NextResponse, Status>>;
+
+    async fn stream(
+        &self,
+        request: Request<NextRequest>,
+    ) -> Result<Response<Self::StreamStream>, Status> {
+        let request = request.into_inner();
+        let job_id = request.job_id;
+        let rank = request.rank;
+        let recv = self.get_recv(job_id, rank).await?;
+        if !self.stream_set.lock().await.insert((job_id, rank)) {
+            return Err(Status::already_exists(format!(
+                "Job {:} rank {:} is being streamed",
+                job_id, rank
+            )));
         }
+        let stream_set = self.stream_set.clone();
+        let (tx, rx) = channel(STREAM_CAP);
+        tokio::spawn(async move {
+            let mut recv = recv.lock().await;
+            loop {
+                // a batch is taken from the job only when there is room for it
+                let permit = match tx.reserve().await {
+                    Ok(permit) => permit,
+                    Err(_) => break,
+                };
+                let batch = tokio::select! {
+                    batch = recv.recv_batch(request.batch_size as usize, request.drop_last) => batch,
+                    // the samples received are kept in recv for the next read
+                    _ = tx.closed() => break,
+                };
+                // the stream is closed when the job is finished
+                if batch.is_empty() {
+                    break;
+                }
+                let resp = collate(&batch)
+                    .map(|data| NextResponse {
+                        data,
+                        batch_size: batch.len() as u32,
+                        epoch: recv.epoch() as u32,
+                        end_of_epoch: recv.end_of_epoch(),
+                    })
+                    .map_err(Status::internal);
+                let is_err = resp.is_err();
+                permit.send(resp);
+                if is_err {
+                    break;
+                }
+            }
+            if tx.is_closed() {
+                log::debug!("Stream of job {} rank {} is closed by client", job_id, rank);
+            }
+            stream_set.lock().await.remove(&(job_id, rank));
+        });
+        Ok(Response::new(ReceiverStream::new(rx)))
     }
 
     async fn delete_job(
@@ -88,15 +309,141 @@ impl JobSvc for JobSvcImpl {
         let request = request.into_inner();
         let mut rt = self.recv_table.lock().await;
         let mut jt = self.joader_table.lock().await;
-        let job_id_table = self.job_id_table.lock().await;
-        let dataset_id_table = self.dataset_id_table.lock().await;
-        let job_id = job_id_table[&request.name];
-        let dataset_id = dataset_id_table[&request.dataset_name];
-        // 1 remove loader
-        let joader = jt.get_mut(dataset_id);
-        joader.del_job(job_id).await;
-        // 2 remove recv table
+        let mut job_id_table = self.job_id_table.lock().await;
+        let dataset_id = *self
+            .dataset_id_table
+            .lock()
+            .await
+            .get(&request.dataset_name)
+            .ok_or_else(|| Status::not_found(&request.dataset_name))?;
+        let job_id = *job_id_table
+            .get(&request.name)
+            .ok_or_else(|| Status::not_found(&request.name))?;
+        let ranks = rt
+            .get(&job_id)
+            .ok_or_else(|| Status::not_found(&request.name))?
+            .len() as u64;
+        // 1 remove loader of each rank
+        for rank in 0..ranks {
+            jt.del_job(dataset_id, job_id + rank).await;
+        }
+        // 2 remove recv table and the name, so it can be created again
         rt.remove(&job_id);
+        job_id_table.remove(&request.name);
         Ok(Response::new(DeleteJobResponse {}))
     }
+
+    async fn reset_job(
+        &self,
+        request: Request<ResetJobRequest>,
+    ) -> Result<Response<ResetJobResponse>, Status> {
+        log::info!("call reset loader {:?}", request);
+        let request = request.into_inner();
+        let job_id = *self
+            .job_id_table
+            .lock()
+            .await
+            .get(&request.name)
+            .ok_or_else(|| Status::not_found(&request.name))?;
+        let dataset_id = *self
+            .dataset_id_table
+            .lock()
+            .await
+            .get(&request.dataset_name)
+            .ok_or_else(|| Status::not_found(&request.dataset_name))?;
+        if self
+            .stream_set
+            .lock()
+            .await
+            .iter()
+            .any(|(id, _)| *id == job_id)
+        {
+            return Err(Status::failed_precondition(format!(
+                "Job {:} is being streamed",
+                job_id
+            )));
+        }
+        let recvs = self.get_recvs(job_id).await?;
+        let mut length = 0;
+        for (rank, recv) in recvs.iter().enumerate() {
+            let (job, r) = Job::new(job_id + rank as u64);
+            length = {
+                let mut jt = self.joader_table.lock().await;
+                jt.reset_job(dataset_id, job)
+                    .await
+                    .ok_or_else(|| Status::not_found(format!("Loader {:} not found", job_id)))?
+            };
+            // a Next waiting on the old channel returns when the old job is dropped
+            recv.lock().await.reset(r);
+        }
+        Ok(Response::new(ResetJobResponse {
+            length: length as u64,
+        }))
+    }
+
+    async fn checkpoint_job(
+        &self,
+        request: Request<CheckpointJobRequest>,
+    ) -> Result<Response<CheckpointJobResponse>, Status> {
+        log::info!("call checkpoint loader {:?}", request);
+        let request = request.into_inner();
+        let job_id = *self
+            .job_id_table
+            .lock()
+            .await
+            .get(&request.name)
+            .ok_or_else(|| Status::not_found(&request.name))?;
+        let dataset_id = *self
+            .dataset_id_table
+            .lock()
+            .await
+            .get(&request.dataset_name)
+            .ok_or_else(|| Status::not_found(&request.dataset_name))?;
+        if self
+            .stream_set
+            .lock()
+            .await
+            .iter()
+            .any(|(id, _)| *id == job_id)
+        {
+            return Err(Status::failed_precondition(format!(
+                "Job {:} is being streamed",
+                job_id
+            )));
+        }
+        let not_found = || Status::not_found(format!("Loader {:} not found", job_id));
+        let mut checkpoint = JobCheckpoint::default();
+        for (rank, recv) in self.get_recvs(job_id).await?.iter().enumerate() {
+            let id = job_id + rank as u64;
+            // the receiver of a job sampled with replacement does not track its samples
+            let replacement = {
+                let mut jt = self.joader_table.lock().await;
+                let sampling = jt.get_mut(dataset_id).sampling(id);
+                sampling.ok_or_else(not_found)?.replacement
+            };
+            if replacement {
+                return Err(Status::failed_precondition(format!(
+                    "Job {:} is sampled with replacement, it has no epochs",
+                    job_id
+                )));
+            }
+            // a Next waiting for data holds the receiver until the joader table pushes it
+            let (epoch, seen) = {
+                let recv = recv.lock().await;
+                let (epoch, seen) = recv.progress();
+                (epoch, seen.to_vec())
+            };
+            let mut jt = self.joader_table.lock().await;
+            let joader = jt.get_mut(dataset_id);
+            let progress = joader.checkpoint(id, epoch, &seen).ok_or_else(not_found)?;
+            checkpoint.fingerprint = joader.fingerprint();
+            checkpoint.ranks.push(job_checkpoint::Rank {
+                epoch: progress.epoch as u32,
+                values: progress.values,
+            });
+        }
+        Ok(Response::new(CheckpointJobResponse {
+            checkpoint: checkpoint.encode_to_vec(),
+        }))
+    }
 }